@@ -0,0 +1,91 @@
+#![no_std]
+#![no_main]
+
+mod utils;
+
+use crate::hal::{
+    adc::{
+        config::{Continuous, Dma as AdcDma, DualMode, SampleTime, Sequence},
+        unpack_dual_sample, AdcClaim, ClockSource, DualAdc,
+    },
+    delay::SYSTDelayExt,
+    dma::{config::DmaConfig, stream::DMAExt, TransferExt},
+    gpio::GpioExt,
+    pwr::PwrExt,
+    rcc::{Config, RccExt},
+    stm32::Peripherals,
+};
+use stm32g4xx_hal as hal;
+
+use cortex_m_rt::entry;
+use utils::logger::info;
+
+#[entry]
+fn main() -> ! {
+    utils::logger::init();
+
+    info!("start");
+
+    let dp = Peripherals::take().unwrap();
+    let cp = cortex_m::Peripherals::take().expect("cannot take core peripherals");
+
+    info!("rcc");
+    let rcc = dp.RCC.constrain();
+    let pwr = dp.PWR.constrain().freeze();
+    let mut rcc = rcc.freeze(Config::hsi(), pwr);
+
+    let streams = dp.DMA1.split(&rcc);
+    let config = DmaConfig::default()
+        .transfer_complete_interrupt(false)
+        .circular_buffer(true)
+        .memory_increment(true);
+
+    info!("Setup Gpio");
+    let gpioa = dp.GPIOA.split(&mut rcc);
+    let pa0 = gpioa.pa0.into_analog();
+    let pa1 = gpioa.pa1.into_analog();
+
+    info!("Setup Adc1 and Adc2");
+    let mut delay = cp.SYST.delay(&rcc.clocks);
+    let mut adc1 = dp
+        .ADC1
+        .claim(ClockSource::SystemClock, &rcc, &mut delay, true);
+    let mut adc2 = dp
+        .ADC2
+        .claim(ClockSource::SystemClock, &rcc, &mut delay, true);
+
+    // Both ADCs need the same sequence length and continuous mode
+    adc1.set_continuous(Continuous::Continuous);
+    adc1.reset_sequence();
+    adc1.configure_channel(&pa0, Sequence::One, SampleTime::Cycles_640_5);
+    adc2.set_continuous(Continuous::Continuous);
+    adc2.reset_sequence();
+    adc2.configure_channel(&pa1, Sequence::One, SampleTime::Cycles_640_5);
+
+    let adc = DualAdc::new(adc1.enable(), adc2.enable(), DualMode::RegularSimultaneous);
+
+    info!("Setup DMA");
+    let first_buffer = cortex_m::singleton!(: [u32; 16] = [0; 16]).unwrap();
+    let mut transfer = streams.0.into_circ_peripheral_to_memory_transfer(
+        adc.enable_dma(AdcDma::Continuous),
+        &mut first_buffer[..],
+        config,
+    );
+
+    transfer.start(|adc| adc.start_conversion());
+
+    loop {
+        let mut b = [0_u32; 4];
+        let r = transfer.read_exact(&mut b);
+        assert!(
+            !transfer.get_overrun_flag(),
+            "DMA did not have time to read the ADC value before ADC was done with a new conversion"
+        );
+        assert!(r == b.len());
+
+        for sample in b {
+            let (pa0, pa1) = unpack_dual_sample(sample);
+            info!("pa0: {}, pa1: {}", pa0, pa1);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+#![no_main]
+#![no_std]
+
+use crate::hal::{
+    adc::{
+        config::{InjectedSequence, SampleTime, TriggerMode},
+        AdcClaim, ClockSource, Vref,
+    },
+    delay::SYSTDelayExt,
+    gpio::GpioExt,
+    pwr::PwrExt,
+    rcc::{Config, RccExt},
+    stm32::Peripherals,
+};
+use stm32g4xx_hal as hal;
+
+use cortex_m_rt::entry;
+
+use utils::logger::info;
+
+#[macro_use]
+mod utils;
+
+#[entry]
+fn main() -> ! {
+    utils::logger::init();
+
+    info!("start");
+
+    let dp = Peripherals::take().unwrap();
+    let cp = cortex_m::Peripherals::take().expect("cannot take core peripherals");
+
+    let rcc = dp.RCC.constrain();
+    let pwr = dp.PWR.constrain().freeze();
+    let mut rcc = rcc.freeze(Config::hsi(), pwr);
+
+    let gpioa = dp.GPIOA.split(&mut rcc);
+    let pa0 = gpioa.pa0.into_analog();
+    let pa1 = gpioa.pa1.into_analog();
+
+    let mut delay = cp.SYST.delay(&rcc.clocks);
+    let mut adc = dp
+        .ADC1
+        .claim(ClockSource::SystemClock, &rcc, &mut delay, true);
+
+    adc.enable_vref(&dp.ADC12_COMMON);
+
+    // Injected sequence started by software. For motor control use e.g.
+    // `(TriggerMode::RisingEdge, InjectedExternalTrigger12::Tim_1_trgo_2)` instead.
+    adc.set_injected_external_trigger((TriggerMode::Disabled, Default::default()));
+    adc.configure_injected_channel(&pa0, InjectedSequence::One, SampleTime::Cycles_92_5);
+    adc.configure_injected_channel(&pa1, InjectedSequence::Two, SampleTime::Cycles_92_5);
+    adc.configure_injected_channel(&Vref, InjectedSequence::Three, SampleTime::Cycles_640_5);
+    let mut adc = adc.enable();
+
+    loop {
+        adc.start_injected_conversion();
+        adc.wait_for_injected_sequence();
+        adc.clear_injected_end_of_sequence_flag();
+
+        let pa0 = adc.sample_to_millivolts(adc.injected_sample(InjectedSequence::One));
+        let pa1 = adc.sample_to_millivolts(adc.injected_sample(InjectedSequence::Two));
+        let vref = Vref::sample_to_millivolts(adc.injected_sample(InjectedSequence::Three));
+        info!("pa0: {}mV, pa1: {}mV, vref: {}mV", pa0, pa1, vref);
+    }
+}
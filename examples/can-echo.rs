@@ -3,6 +3,12 @@
 
 use crate::hal::{
     can::CanExt,
+    fdcan::{
+        config::NominalBitTiming,
+        filter::{StandardFilter, StandardFilterSlot},
+        frame::{FrameFormat, TxFrameHeader},
+        id::StandardId,
+    },
     gpio::{GpioExt as _, Speed},
     nb::block,
     pwr::PwrExt,
@@ -10,12 +16,6 @@ use crate::hal::{
     stm32::Peripherals,
     time::RateExtU32,
 };
-use fdcan::{
-    config::NominalBitTiming,
-    filter::{StandardFilter, StandardFilterSlot},
-    frame::{FrameFormat, TxFrameHeader},
-    id::StandardId,
-};
 use stm32g4xx_hal as hal;
 
 use core::num::{NonZeroU16, NonZeroU8};
@@ -107,7 +107,9 @@ fn main() -> ! {
     let mut can = can1;
 
     info!("Create Message Data");
-    let mut buffer: [u8; 8] = [0xAA, 0xAA, 0xAA, 0xAA, 0xFF, 0xFF, 0xFF, 0xFF];
+    let mut buffer: [u32; 16] = [0; 16];
+    buffer[0] = 0xAAAA_AAAA;
+    buffer[1] = 0xFFFF_FFFF;
     info!("Create Message Header");
     let header = TxFrameHeader {
         len: 2 * 4,
@@ -119,11 +121,19 @@ fn main() -> ! {
     info!("Initial Header: {:#X?}", &header);
 
     info!("Transmit initial message");
-    block!(can.transmit(header, &buffer)).unwrap();
+    block!(can.transmit(header, &mut |b| b.copy_from_slice(&buffer[..b.len()]))).unwrap();
 
     loop {
-        if let Ok(rxheader) = block!(can.receive0(&mut buffer)) {
-            block!(can.transmit(rxheader.unwrap().to_tx_header(None), &buffer)).unwrap();
+        if let Ok(rxheader) = block!(can.receive0(&mut |header, data| {
+            buffer[..data.len()].copy_from_slice(data);
+            header
+        })) {
+            block!(
+                can.transmit(rxheader.unwrap().to_tx_header(None), &mut |b| {
+                    b.copy_from_slice(&buffer[..b.len()])
+                })
+            )
+            .unwrap();
         }
     }
 }
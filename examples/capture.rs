@@ -0,0 +1,46 @@
+//This example measures the PWM signal on PA0 using TIM2, and timestamps the edges on PA6 using TIM3.
+#![no_main]
+#![no_std]
+
+use cortex_m_rt::entry;
+use hal::capture::{CaptureEdge, CaptureExt, PwmInputExt};
+use hal::gpio::gpioa::{PA0, PA6};
+use hal::gpio::{Alternate, AF1, AF2};
+use hal::prelude::*;
+use hal::stm32;
+use hal::timer::InputFilter;
+use stm32g4xx_hal as hal;
+extern crate cortex_m_rt as rt;
+
+#[macro_use]
+mod utils;
+
+use utils::logger::info;
+
+#[entry]
+fn main() -> ! {
+    utils::logger::init();
+
+    let dp = stm32::Peripherals::take().expect("cannot take peripherals");
+    let mut rcc = dp.RCC.constrain();
+    let gpioa = dp.GPIOA.split(&mut rcc);
+    let pwm: PA0<Alternate<AF1>> = gpioa.pa0.into_alternate();
+    let edges: PA6<Alternate<AF2>> = gpioa.pa6.into_alternate();
+
+    let mut pwm_input = dp.TIM2.pwm_input(pwm, 1.MHz(), &mut rcc);
+
+    let (_timer, (ch1, _, _, _)) = dp.TIM3.capture(1.MHz(), &mut rcc);
+    let mut ch1 = ch1.capture(edges);
+    ch1.set_edge(CaptureEdge::Both);
+    ch1.set_filter(InputFilter::FckInt_N4);
+    ch1.enable();
+
+    loop {
+        if let Ok((period, high)) = pwm_input.read() {
+            info!("pwm period: {} us, high: {} us", period, high);
+        }
+        if let Ok(timestamp) = ch1.read() {
+            info!("edge at {} us", timestamp);
+        }
+    }
+}
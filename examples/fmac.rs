@@ -0,0 +1,78 @@
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+extern crate cortex_m;
+extern crate cortex_m_rt as rt;
+extern crate stm32g4xx_hal as hal;
+
+use hal::dma::{config::DmaConfig, stream::DMAExt, TransferExt};
+use hal::fmac::{Ext as _, Fir, Iir, I1F15};
+use hal::prelude::*;
+use hal::pwr::PwrExt;
+use hal::rcc::Config;
+use hal::stm32;
+use rt::entry;
+
+#[macro_use]
+mod utils;
+
+use utils::logger::println;
+
+const SAMPLES: usize = 16;
+
+#[entry]
+fn main() -> ! {
+    let dp = stm32::Peripherals::take().expect("cannot take peripherals");
+    let pwr = dp.PWR.constrain().freeze();
+    let mut rcc = dp.RCC.freeze(Config::hsi(), pwr);
+
+    // polling operation, 4 tap moving average
+
+    let coefficients = [I1F15::from_num(0.25); 4];
+    let mut fmac = dp.FMAC.constrain(&mut rcc).start(&Fir::new(&coefficients));
+
+    for _ in 0..4 {
+        let y = fmac.process(I1F15::from_num(0.5)).unwrap();
+        println!("fir: {}", y.to_num::<f32>());
+    }
+
+    // DMA operation, first order low pass
+
+    let feedforward = [I1F15::from_num(0.05), I1F15::from_num(0.05)];
+    let feedback = [I1F15::from_num(0.9)];
+    let fmac = fmac
+        .stop()
+        .start(&Iir::new(&feedforward, &feedback).headroom(2));
+    let (input, output) = fmac.into_dma();
+
+    let streams = dp.DMA1.split(&rcc);
+    let config = DmaConfig::default()
+        .transfer_complete_interrupt(false)
+        .memory_increment(true);
+
+    let x =
+        cortex_m::singleton!(: [i16; SAMPLES] = [I1F15::from_num(0.5).to_bits(); SAMPLES]).unwrap();
+    let y = cortex_m::singleton!(: [i16; SAMPLES] = [0; SAMPLES]).unwrap();
+
+    let mut rx = streams
+        .1
+        .into_peripheral_to_memory_transfer(output, &mut y[..], config);
+    let mut tx = streams
+        .0
+        .into_memory_to_peripheral_transfer(input, &x[..], config);
+    rx.start(|_| {});
+    tx.start(|_| {});
+
+    while !rx.get_transfer_complete_flag() {}
+
+    let (_, output, y) = rx.free();
+    let (_, input, _) = tx.free();
+    let _fmac = input.join(output).stop();
+
+    for sample in y.iter() {
+        println!("iir: {}", I1F15::from_bits(*sample).to_num::<f32>());
+    }
+
+    loop {}
+}
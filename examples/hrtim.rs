@@ -0,0 +1,62 @@
+//This example puts HRTIM timer A in push-pull mode on PA8 and PA9 with a duty cycle of 33%.
+#![no_main]
+#![no_std]
+
+use cortex_m_rt::entry;
+use hal::gpio::gpioa::{PA8, PA9};
+use hal::gpio::Alternate;
+use hal::gpio::AF13;
+use hal::hrtim::{HrParts, Pscl4};
+use hal::prelude::*;
+use hal::stm32;
+use stm32g4xx_hal as hal;
+extern crate cortex_m_rt as rt;
+
+#[macro_use]
+mod utils;
+
+#[entry]
+fn main() -> ! {
+    utils::logger::init();
+
+    let dp = stm32::Peripherals::take().expect("cannot take peripherals");
+    let mut rcc = dp.RCC.constrain();
+    let gpioa = dp.GPIOA.split(&mut rcc);
+    let pin_a: PA8<Alternate<AF13>> = gpioa.pa8.into_alternate();
+    let pin_b: PA9<Alternate<AF13>> = gpioa.pa9.into_alternate();
+
+    let (mut control, _flt_inputs, _eev_inputs) = dp
+        .HRTIM_COMMON
+        .hr_control(&mut rcc)
+        .wait_for_calibration();
+
+    let HrParts {
+        mut timer,
+        mut cr1,
+        out: (mut out1, mut out2),
+        ..
+    } = dp
+        .HRTIM_TIMA
+        .pwm_advanced((pin_a, pin_b), &mut rcc)
+        .prescaler(Pscl4)
+        .period(0xFFFF)
+        .push_pull_mode(true)
+        .finalize(&mut control);
+
+    out1.enable_rst_event(&cr1); // Set low on compare match with cr1
+    out2.enable_rst_event(&cr1);
+
+    out1.enable_set_event(&timer); // Set high at new period
+    out2.enable_set_event(&timer);
+
+    cr1.set_duty(timer.get_period() / 3);
+
+    out1.enable();
+    out2.enable();
+
+    timer.start(&mut control);
+
+    loop {
+        cortex_m::asm::nop()
+    }
+}
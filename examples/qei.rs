@@ -0,0 +1,38 @@
+//This example reads a quadrature encoder on PA0 (A), PA1 (B) and PA5 (index) using TIM2.
+#![no_main]
+#![no_std]
+
+use cortex_m_rt::entry;
+use hal::gpio::gpioa::{PA0, PA1, PA5};
+use hal::gpio::{Alternate, AF1, AF2};
+use hal::prelude::*;
+use hal::qei::{EncoderMode, IndexDirection, IndexPosition};
+use hal::stm32;
+use hal::timer::InputFilter;
+use stm32g4xx_hal as hal;
+extern crate cortex_m_rt as rt;
+
+#[macro_use]
+mod utils;
+
+use utils::logger::info;
+
+#[entry]
+fn main() -> ! {
+    utils::logger::init();
+
+    let dp = stm32::Peripherals::take().expect("cannot take peripherals");
+    let mut rcc = dp.RCC.constrain();
+    let gpioa = dp.GPIOA.split(&mut rcc);
+    let a: PA0<Alternate<AF1>> = gpioa.pa0.into_alternate();
+    let b: PA1<Alternate<AF1>> = gpioa.pa1.into_alternate();
+    let index: PA5<Alternate<AF2>> = gpioa.pa5.into_alternate();
+
+    let mut qei = dp.TIM2.qei((a, b), EncoderMode::X4, &mut rcc);
+    qei.set_filter(InputFilter::FckInt_N8);
+    qei.enable_index(&index, IndexPosition::AB00, IndexDirection::Both);
+
+    loop {
+        info!("count: {}", qei.count());
+    }
+}
@@ -0,0 +1,57 @@
+#![no_main]
+#![no_std]
+
+use stm32g4xx_hal::{
+    prelude::*,
+    rcc::{Config, RTCSrc},
+    rtc::{Alarm, Event},
+    stm32,
+    time::{Date, Time},
+};
+
+use cortex_m_rt::entry;
+
+#[macro_use]
+mod utils;
+
+use utils::logger::info;
+
+#[entry]
+fn main() -> ! {
+    utils::logger::init();
+
+    let dp = stm32::Peripherals::take().expect("cannot take peripherals");
+    let rcc = dp.RCC.constrain();
+    let pwr = dp.PWR.constrain().freeze();
+    let mut rcc = rcc.freeze(Config::hsi(), pwr);
+
+    let mut rtc = dp.RTC.constrain(RTCSrc::LSI, &mut rcc);
+
+    // A magic value in the backup registers tells us if the calendar survived a reset
+    if rtc.read_backup_register(0) != 0xC0FFEE {
+        rtc.set_date_time(
+            &Date::new(2024.year(), 1.month(), 1.day()),
+            &Time::new(12.hours(), 0.minutes(), 0.secs(), false),
+        );
+        rtc.write_backup_register(0, 0xC0FFEE);
+    }
+
+    // Fire alarm A every minute, at second 30
+    rtc.set_alarm_a(Alarm::new().set_seconds(30.secs()));
+    rtc.listen(Event::AlarmA);
+
+    rtc.enable_wakeup(1_000_000.micros());
+
+    loop {
+        if rtc.check_event(Event::WakeupTimer) {
+            let (date, time) = rtc.get_date_time();
+            info!(
+                "{}-{:02}-{:02} {:02}:{:02}:{:02}",
+                date.year, date.month, date.day, time.hours, time.minutes, time.seconds
+            );
+        }
+        if rtc.check_event(Event::AlarmA) {
+            info!("Alarm A");
+        }
+    }
+}
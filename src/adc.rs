@@ -18,6 +18,7 @@ use crate::{
     signature::{VtempCal130, VtempCal30, VDDA_CALIB},
     stm32,
 };
+use core::cmp;
 use core::fmt;
 use core::marker::PhantomData;
 use embedded_hal_02::{
@@ -25,7 +26,7 @@ use embedded_hal_02::{
     blocking::delay::DelayUs,
 };
 
-use self::config::ExternalTrigger12;
+use self::config::{ExternalTrigger12, InjectedExternalTrigger12};
 
 #[cfg(any(
     feature = "stm32g471",
@@ -36,7 +37,7 @@ use self::config::ExternalTrigger12;
     feature = "stm32g491",
     feature = "stm32g4a1",
 ))]
-use self::config::ExternalTrigger345;
+use self::config::{ExternalTrigger345, InjectedExternalTrigger345};
 
 /// Vref internal signal, used for calibration
 pub struct Vref;
@@ -52,6 +53,16 @@ impl Vref {
     pub fn sample_to_millivolts(sample: u16) -> u16 {
         Self::sample_to_millivolts_ext(sample, VDDA_CALIB, config::Resolution::Twelve)
     }
+    /// Converts millivolts to a sample value using calibrated VDDA and configured resolution
+    #[inline(always)]
+    pub fn millivolts_to_sample_ext(
+        millivolts: u16,
+        vdda: u32,
+        resolution: config::Resolution,
+    ) -> u16 {
+        let mx_s = resolution.to_max_sample();
+        cmp::min((u32::from(millivolts) * mx_s) / vdda, mx_s) as u16
+    }
 }
 
 /// Vbat internal signal, used for monitoring the battery (if used)
@@ -168,6 +179,8 @@ macro_rules! adc_op_follower {
 
 /// Contains types related to ADC configuration
 pub mod config {
+    use super::TriggerType;
+    use core::marker::PhantomData;
     use embedded_hal_02::adc::Channel;
 
     /// The place in the sequence a given channel should be captured
@@ -694,6 +707,531 @@ pub mod config {
         }
     }
 
+    /// The place in the injected sequence a given channel should be captured
+    #[derive(Debug, PartialEq, PartialOrd, Copy, Clone)]
+    pub enum InjectedSequence {
+        /// 1
+        One,
+        /// 2
+        Two,
+        /// 3
+        Three,
+        /// 4
+        Four,
+    }
+
+    impl From<InjectedSequence> for u8 {
+        fn from(s: InjectedSequence) -> u8 {
+            match s {
+                InjectedSequence::One => 0,
+                InjectedSequence::Two => 1,
+                InjectedSequence::Three => 2,
+                InjectedSequence::Four => 3,
+            }
+        }
+    }
+
+    /// Possible external triggers the injected group can listen to
+    ///
+    /// This applies to ADC1 and ADC2
+    #[derive(Debug, Clone, Copy, Default)]
+    pub enum InjectedExternalTrigger12 {
+        /// TIM1 trigger out
+        #[default]
+        Tim_1_trgo,
+        /// TIM1 compare channel 4
+        Tim_1_cc_4,
+        /// TIM2 trigger out
+        Tim_2_trgo,
+        /// TIM2 compare channel 1
+        Tim_2_cc_1,
+        /// TIM3 compare channel 4
+        Tim_3_cc_4,
+        /// TIM4 trigger out
+        Tim_4_trgo,
+        /// External interupt line 15
+        Exti_15,
+        /// TIM8 compare channel 4
+        Tim_8_cc_4,
+        /// TIM1 trigger out 2
+        Tim_1_trgo_2,
+        /// TIM8 trigger out
+        Tim_8_trgo,
+        /// TIM8 trigger out 2
+        Tim_8_trgo_2,
+        /// TIM3 compare channel 3
+        Tim_3_cc_3,
+        /// TIM3 trigger out
+        Tim_3_trgo,
+        /// TIM3 compare channel 1
+        Tim_3_cc_1,
+        /// TIM6 trigger out
+        Tim_6_trgo,
+        /// TIM15 trigger out
+        Tim_15_trgo,
+        /// TIM20 trigger out
+        Tim_20_trgo,
+        /// TIM20 trigger out 2
+        Tim_20_trgo_2,
+        /// TIM20 compare channel 4
+        Tim_20_cc_4,
+        /// hrtim_adc_trg2
+        Hrtim_adc_trg_2,
+        /// hrtim_adc_trg4
+        Hrtim_adc_trg_4,
+        /// hrtim_adc_trg5
+        Hrtim_adc_trg_5,
+        /// hrtim_adc_trg6
+        Hrtim_adc_trg_6,
+        /// hrtim_adc_trg7
+        Hrtim_adc_trg_7,
+        /// hrtim_adc_trg8
+        Hrtim_adc_trg_8,
+        /// hrtim_adc_trg9
+        Hrtim_adc_trg_9,
+        /// hrtim_adc_trg10
+        Hrtim_adc_trg_10,
+        /// TIM16 compare channel 1
+        Tim_16_cc_1,
+        /// LP_timeout
+        Lp_timeout,
+        /// TIM7 trigger out
+        Tim_7_trgo,
+    }
+
+    impl From<InjectedExternalTrigger12> for u8 {
+        fn from(et: InjectedExternalTrigger12) -> u8 {
+            match et {
+                InjectedExternalTrigger12::Tim_1_trgo => 0b00000,
+                InjectedExternalTrigger12::Tim_1_cc_4 => 0b00001,
+                InjectedExternalTrigger12::Tim_2_trgo => 0b00010,
+                InjectedExternalTrigger12::Tim_2_cc_1 => 0b00011,
+                InjectedExternalTrigger12::Tim_3_cc_4 => 0b00100,
+                InjectedExternalTrigger12::Tim_4_trgo => 0b00101,
+                InjectedExternalTrigger12::Exti_15 => 0b00110,
+                InjectedExternalTrigger12::Tim_8_cc_4 => 0b00111,
+                InjectedExternalTrigger12::Tim_1_trgo_2 => 0b01000,
+                InjectedExternalTrigger12::Tim_8_trgo => 0b01001,
+                InjectedExternalTrigger12::Tim_8_trgo_2 => 0b01010,
+                InjectedExternalTrigger12::Tim_3_cc_3 => 0b01011,
+                InjectedExternalTrigger12::Tim_3_trgo => 0b01100,
+                InjectedExternalTrigger12::Tim_3_cc_1 => 0b01101,
+                InjectedExternalTrigger12::Tim_6_trgo => 0b01110,
+                InjectedExternalTrigger12::Tim_15_trgo => 0b01111,
+                InjectedExternalTrigger12::Tim_20_trgo => 0b10000,
+                InjectedExternalTrigger12::Tim_20_trgo_2 => 0b10001,
+                InjectedExternalTrigger12::Tim_20_cc_4 => 0b10010,
+                InjectedExternalTrigger12::Hrtim_adc_trg_2 => 0b10011,
+                InjectedExternalTrigger12::Hrtim_adc_trg_4 => 0b10100,
+                InjectedExternalTrigger12::Hrtim_adc_trg_5 => 0b10101,
+                InjectedExternalTrigger12::Hrtim_adc_trg_6 => 0b10110,
+                InjectedExternalTrigger12::Hrtim_adc_trg_7 => 0b10111,
+                InjectedExternalTrigger12::Hrtim_adc_trg_8 => 0b11000,
+                InjectedExternalTrigger12::Hrtim_adc_trg_9 => 0b11001,
+                InjectedExternalTrigger12::Hrtim_adc_trg_10 => 0b11010,
+                InjectedExternalTrigger12::Tim_16_cc_1 => 0b11011,
+                InjectedExternalTrigger12::Lp_timeout => 0b11100,
+                InjectedExternalTrigger12::Tim_7_trgo => 0b11101,
+                // Reserved => 0b11110
+                // Reserved => 0b11111
+            }
+        }
+    }
+
+    /// Possible external triggers the injected group can listen to
+    ///
+    /// This applies to ADC3, ADC4 and ADC5
+    #[cfg(any(
+        feature = "stm32g471",
+        feature = "stm32g473",
+        feature = "stm32g474",
+        feature = "stm32g483",
+        feature = "stm32g484",
+        feature = "stm32g491",
+        feature = "stm32g4a1",
+    ))]
+    #[derive(Debug, Clone, Copy, Default)]
+    pub enum InjectedExternalTrigger345 {
+        /// TIM1 trigger out
+        #[default]
+        Tim_1_trgo,
+        /// TIM1 compare channel 4
+        Tim_1_cc_4,
+        /// TIM2 trigger out
+        Tim_2_trgo,
+        /// TIM8 compare channel 2
+        Tim_8_cc_2,
+        /// TIM4 compare channel 3
+        Tim_4_cc_3,
+        /// TIM4 trigger out
+        Tim_4_trgo,
+        /// TIM4 compare channel 4
+        Tim_4_cc_4,
+        /// TIM8 compare channel 4
+        Tim_8_cc_4,
+        /// TIM1 trigger out 2
+        Tim_1_trgo_2,
+        /// TIM8 trigger out
+        Tim_8_trgo,
+        /// TIM8 trigger out 2
+        Tim_8_trgo_2,
+        /// TIM1 compare channel 3
+        Tim_1_cc_3,
+        /// TIM3 trigger out
+        Tim_3_trgo,
+        /// External interupt line 3
+        Exti_3,
+        /// TIM6 trigger out
+        Tim_6_trgo,
+        /// TIM15 trigger out
+        Tim_15_trgo,
+        /// TIM20 trigger out
+        Tim_20_trgo,
+        /// TIM20 trigger out 2
+        Tim_20_trgo_2,
+        /// TIM20 compare channel 2
+        Tim_20_cc_2,
+        /// hrtim_adc_trg2
+        Hrtim_adc_trg_2,
+        /// hrtim_adc_trg4
+        Hrtim_adc_trg_4,
+        /// hrtim_adc_trg5
+        Hrtim_adc_trg_5,
+        /// hrtim_adc_trg6
+        Hrtim_adc_trg_6,
+        /// hrtim_adc_trg7
+        Hrtim_adc_trg_7,
+        /// hrtim_adc_trg8
+        Hrtim_adc_trg_8,
+        /// hrtim_adc_trg9
+        Hrtim_adc_trg_9,
+        /// hrtim_adc_trg10
+        Hrtim_adc_trg_10,
+        /// hrtim_adc_trg1
+        Hrtim_adc_trg_1,
+        /// LP_timeout
+        Lp_timeout,
+        /// TIM7 trigger out
+        Tim_7_trgo,
+    }
+
+    #[cfg(any(
+        feature = "stm32g471",
+        feature = "stm32g473",
+        feature = "stm32g474",
+        feature = "stm32g483",
+        feature = "stm32g484",
+        feature = "stm32g491",
+        feature = "stm32g4a1",
+    ))]
+    impl From<InjectedExternalTrigger345> for u8 {
+        fn from(et: InjectedExternalTrigger345) -> u8 {
+            match et {
+                InjectedExternalTrigger345::Tim_1_trgo => 0b00000,
+                InjectedExternalTrigger345::Tim_1_cc_4 => 0b00001,
+                InjectedExternalTrigger345::Tim_2_trgo => 0b00010,
+                InjectedExternalTrigger345::Tim_8_cc_2 => 0b00011,
+                InjectedExternalTrigger345::Tim_4_cc_3 => 0b00100,
+                InjectedExternalTrigger345::Tim_4_trgo => 0b00101,
+                InjectedExternalTrigger345::Tim_4_cc_4 => 0b00110,
+                InjectedExternalTrigger345::Tim_8_cc_4 => 0b00111,
+                InjectedExternalTrigger345::Tim_1_trgo_2 => 0b01000,
+                InjectedExternalTrigger345::Tim_8_trgo => 0b01001,
+                InjectedExternalTrigger345::Tim_8_trgo_2 => 0b01010,
+                InjectedExternalTrigger345::Tim_1_cc_3 => 0b01011,
+                InjectedExternalTrigger345::Tim_3_trgo => 0b01100,
+                InjectedExternalTrigger345::Exti_3 => 0b01101,
+                InjectedExternalTrigger345::Tim_6_trgo => 0b01110,
+                InjectedExternalTrigger345::Tim_15_trgo => 0b01111,
+                InjectedExternalTrigger345::Tim_20_trgo => 0b10000,
+                InjectedExternalTrigger345::Tim_20_trgo_2 => 0b10001,
+                InjectedExternalTrigger345::Tim_20_cc_2 => 0b10010,
+                InjectedExternalTrigger345::Hrtim_adc_trg_2 => 0b10011,
+                InjectedExternalTrigger345::Hrtim_adc_trg_4 => 0b10100,
+                InjectedExternalTrigger345::Hrtim_adc_trg_5 => 0b10101,
+                InjectedExternalTrigger345::Hrtim_adc_trg_6 => 0b10110,
+                InjectedExternalTrigger345::Hrtim_adc_trg_7 => 0b10111,
+                InjectedExternalTrigger345::Hrtim_adc_trg_8 => 0b11000,
+                InjectedExternalTrigger345::Hrtim_adc_trg_9 => 0b11001,
+                InjectedExternalTrigger345::Hrtim_adc_trg_10 => 0b11010,
+                InjectedExternalTrigger345::Hrtim_adc_trg_1 => 0b11011,
+                InjectedExternalTrigger345::Lp_timeout => 0b11100,
+                InjectedExternalTrigger345::Tim_7_trgo => 0b11101,
+                // Reserved => 0b11110
+                // Reserved => 0b11111
+            }
+        }
+    }
+
+    /// Behaviour of the queue of injected contexts
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum InjectedQueue {
+        /// No queue, JSQR may only be written while no injected conversion is ongoing
+        Disabled,
+        /// Up to two contexts are queued, the last one is kept once the queue runs empty
+        KeepLast,
+        /// Up to two contexts are queued, injected triggers are ignored once the queue runs empty
+        EmptyStops,
+    }
+
+    /// A context of the injected group: a sequence of up to four channels and the trigger
+    /// starting it
+    ///
+    /// The sample time of the channels is the one set with `set_channel_sample_time` or
+    /// `configure_injected_channel`.
+    pub struct InjectedContext<ADC: TriggerType> {
+        trigger: (TriggerMode, ADC::InjectedTrigger),
+        channels: [u8; 4],
+        len: usize,
+        _adc: PhantomData<ADC>,
+    }
+
+    impl<ADC: TriggerType> InjectedContext<ADC> {
+        /// Creates an empty context, `TriggerMode::Disabled` starts it by software
+        pub fn new(trigger: (TriggerMode, ADC::InjectedTrigger)) -> Self {
+            Self {
+                trigger,
+                channels: [0; 4],
+                len: 0,
+                _adc: PhantomData,
+            }
+        }
+
+        /// Appends `channel` to the sequence
+        ///
+        /// Panics if the sequence already holds four channels
+        pub fn channel<CHANNEL>(mut self, _channel: &CHANNEL) -> Self
+        where
+            CHANNEL: Channel<ADC, ID = u8>,
+        {
+            assert!(self.len < 4);
+            self.channels[self.len] = CHANNEL::channel();
+            self.len += 1;
+            self
+        }
+
+        /// The value of the JSQR register
+        pub(crate) fn bits(&self) -> u32 {
+            assert!(self.len > 0);
+            let (edge, trigger) = self.trigger;
+            let jextsel: u8 = trigger.into();
+            let jexten: u8 = edge.into();
+
+            let mut bits = (self.len as u32 - 1) | (jextsel as u32) << 2 | (jexten as u32) << 7;
+            for (i, channel) in self.channels[..self.len].iter().enumerate() {
+                bits |= (*channel as u32) << (9 + 6 * i);
+            }
+            bits
+        }
+    }
+
+    /// One of the three analog watchdogs of an ADC
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum AnalogWatchdog {
+        /// Analog watchdog 1, guards a single or all channels with full resolution thresholds
+        One,
+        /// Analog watchdog 2, guards any set of channels with 8 bit thresholds
+        Two,
+        /// Analog watchdog 3, guards any set of channels with 8 bit thresholds
+        Three,
+    }
+    impl From<AnalogWatchdog> for u8 {
+        fn from(awd: AnalogWatchdog) -> u8 {
+            match awd {
+                AnalogWatchdog::One => 0,
+                AnalogWatchdog::Two => 1,
+                AnalogWatchdog::Three => 2,
+            }
+        }
+    }
+
+    /// Number of consecutive out-of-window conversions before analog watchdog 1 raises its flag
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum AnalogWatchdogFilter {
+        /// No filtering
+        Detections_1,
+        /// 2 consecutive detections
+        Detections_2,
+        /// 3 consecutive detections
+        Detections_3,
+        /// 4 consecutive detections
+        Detections_4,
+        /// 5 consecutive detections
+        Detections_5,
+        /// 6 consecutive detections
+        Detections_6,
+        /// 7 consecutive detections
+        Detections_7,
+        /// 8 consecutive detections
+        Detections_8,
+    }
+    impl From<AnalogWatchdogFilter> for u8 {
+        fn from(f: AnalogWatchdogFilter) -> u8 {
+            match f {
+                AnalogWatchdogFilter::Detections_1 => 0,
+                AnalogWatchdogFilter::Detections_2 => 1,
+                AnalogWatchdogFilter::Detections_3 => 2,
+                AnalogWatchdogFilter::Detections_4 => 3,
+                AnalogWatchdogFilter::Detections_5 => 4,
+                AnalogWatchdogFilter::Detections_6 => 5,
+                AnalogWatchdogFilter::Detections_7 => 6,
+                AnalogWatchdogFilter::Detections_8 => 7,
+            }
+        }
+    }
+
+    /// Dual ADC mode of an ADC pair, see `DualAdc`
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum DualMode {
+        /// Both ADCs work independently
+        Independent,
+        /// Combined regular simultaneous and injected simultaneous mode
+        RegularSimultaneousInjectedSimultaneous,
+        /// Combined regular simultaneous and alternate trigger mode
+        RegularSimultaneousAlternateTrigger,
+        /// Combined interleaved and injected simultaneous mode
+        InterleavedInjectedSimultaneous,
+        /// Injected simultaneous mode only
+        InjectedSimultaneous,
+        /// Regular simultaneous mode only
+        RegularSimultaneous,
+        /// Interleaved mode only
+        Interleaved,
+        /// Alternate trigger mode only
+        AlternateTrigger,
+    }
+
+    impl From<DualMode> for u8 {
+        fn from(m: DualMode) -> u8 {
+            match m {
+                DualMode::Independent => 0b00000,
+                DualMode::RegularSimultaneousInjectedSimultaneous => 0b00001,
+                DualMode::RegularSimultaneousAlternateTrigger => 0b00010,
+                DualMode::InterleavedInjectedSimultaneous => 0b00011,
+                DualMode::InjectedSimultaneous => 0b00101,
+                DualMode::RegularSimultaneous => 0b00110,
+                DualMode::Interleaved => 0b00111,
+                DualMode::AlternateTrigger => 0b01001,
+            }
+        }
+    }
+
+    /// One of the four offset registers, each of them applies to a single channel
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Offset {
+        /// Offset register 1
+        One,
+        /// Offset register 2
+        Two,
+        /// Offset register 3
+        Three,
+        /// Offset register 4
+        Four,
+    }
+
+    /// Configuration of an offset applied to the conversions of a channel
+    ///
+    /// The offset is a raw sample at the configured resolution. It is subtracted from the
+    /// conversion by default, which makes the result signed unless saturation is enabled.
+    pub struct OffsetConfig<ADC> {
+        channel: u8,
+        offset: u16,
+        positive: bool,
+        saturate: bool,
+        _adc: PhantomData<ADC>,
+    }
+
+    impl<ADC> OffsetConfig<ADC> {
+        /// Creates an offset subtracted from the conversions of `channel`
+        pub fn new<CHANNEL>(_channel: &CHANNEL, offset: u16) -> Self
+        where
+            CHANNEL: Channel<ADC, ID = u8>,
+        {
+            Self {
+                channel: CHANNEL::channel(),
+                offset,
+                positive: false,
+                saturate: false,
+                _adc: PhantomData,
+            }
+        }
+
+        /// Adds the offset to the conversions instead of subtracting it
+        pub fn positive(mut self, positive: bool) -> Self {
+            self.positive = positive;
+            self
+        }
+
+        /// Saturates the result to the unsigned range of the resolution instead of
+        /// producing a signed result
+        pub fn saturate(mut self, saturate: bool) -> Self {
+            self.saturate = saturate;
+            self
+        }
+
+        /// Value of the OFRy register, `shift` scales the offset to 12 bits
+        pub(crate) fn bits(&self, shift: u8) -> u32 {
+            1 << 31
+                | (self.channel as u32) << 26
+                | (self.saturate as u32) << 25
+                | (self.positive as u32) << 24
+                | ((self.offset << shift) as u32 & 0xFFF)
+        }
+    }
+
+    /// Configuration of an analog watchdog
+    ///
+    /// The thresholds are raw samples at the configured resolution, `millivolts_to_sample`
+    /// converts from millivolts. Analog watchdogs 2 and 3 only compare the 8 most significant
+    /// bits of the thresholds.
+    pub struct AnalogWatchdogConfig<ADC> {
+        pub(crate) low: u16,
+        pub(crate) high: u16,
+        pub(crate) channels: u32,
+        pub(crate) filter: AnalogWatchdogFilter,
+        _adc: PhantomData<ADC>,
+    }
+
+    impl<ADC> AnalogWatchdogConfig<ADC> {
+        /// Bit set of all channels
+        pub(crate) const ALL_CHANNELS: u32 = (1 << 19) - 1;
+
+        /// Creates a watchdog guarding no channel yet, it is raised once a conversion is
+        /// below `low` or above `high`
+        pub fn new(low: u16, high: u16) -> Self {
+            Self {
+                low,
+                high,
+                channels: 0,
+                filter: AnalogWatchdogFilter::Detections_1,
+                _adc: PhantomData,
+            }
+        }
+
+        /// Adds `channel` to the guarded channels
+        ///
+        /// Analog watchdog 1 only guards a single channel or all of them
+        pub fn channel<CHANNEL>(mut self, _channel: &CHANNEL) -> Self
+        where
+            CHANNEL: Channel<ADC, ID = u8>,
+        {
+            self.channels |= 1 << CHANNEL::channel();
+            self
+        }
+
+        /// Guards all channels
+        pub fn all_channels(mut self) -> Self {
+            self.channels = Self::ALL_CHANNELS;
+            self
+        }
+
+        /// Sets the filtering, only supported by analog watchdog 1
+        pub fn filter(mut self, filter: AnalogWatchdogFilter) -> Self {
+            self.filter = filter;
+            self
+        }
+    }
+
     /// Possible oversampling shift
     #[derive(Debug, Clone, Copy)]
     pub enum OverSamplingShift {
@@ -1201,10 +1739,6 @@ impl<ADC: TriggerType> Conversion<ADC> {
 /// devices and some pins may be missing on some variants. The implementation has been split up and commented
 /// to show which pins are available on certain device variants but currently the library doesn't enforce this.
 /// To fully support the right pins would require 10+ more features for the various variants.
-/// ## Todo
-/// * Injected conversions
-/// * Analog watchdog config
-/// * Discontinuous mode
 /// # Examples
 /// ## One-shot conversion
 /// ```
@@ -1356,11 +1890,42 @@ where
     }
 }
 
-/// ADC Clock Source selection
-#[derive(Debug, Clone, Copy)]
-pub enum ClockSource {
-    /// Use the System Clock as Clock Source
-    SystemClock,
+/// Two ADCs sharing a common block, running in dual mode
+///
+/// The master ADC (ADC1 or ADC3) controls the slave ADC (ADC2 or ADC4): starting and stopping
+/// conversions on the master applies to both, and the triggers of the slave are ignored. Both
+/// ADCs should be configured with the same resolution, continuous mode and sequence lengths.
+///
+/// With DMA enabled, each request transfers the samples of both ADCs packed into the common
+/// data register, see [`unpack_dual_sample`]. Use a `u32` buffer with `dma::Transfer`.
+pub struct DualAdc<MASTER: TriggerType, SLAVE: TriggerType, STATUS> {
+    master: DynamicAdc<MASTER>,
+    slave: DynamicAdc<SLAVE>,
+    _status: PhantomData<STATUS>,
+}
+impl<MASTER: TriggerType, SLAVE: TriggerType, STATUS> fmt::Debug
+    for DualAdc<MASTER, SLAVE, STATUS>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DualAdc: {{ master: {:?}, slave: {:?} }}",
+            self.master, self.slave
+        )
+    }
+}
+
+/// Splits a sample of the common data register into the `(master, slave)` samples
+#[inline(always)]
+pub fn unpack_dual_sample(sample: u32) -> (u16, u16) {
+    (sample as u16, (sample >> 16) as u16)
+}
+
+/// ADC Clock Source selection
+#[derive(Debug, Clone, Copy)]
+pub enum ClockSource {
+    /// Use the System Clock as Clock Source
+    SystemClock,
     /// use the Internal PLL as Clock Source
     PLL_P,
 }
@@ -1404,6 +1969,8 @@ trait AdcConfig {
 pub trait TriggerType {
     /// Specifies what External trigger type the ADC uses
     type ExternalTrigger: fmt::Debug;
+    /// Specifies what External trigger type the injected group of the ADC uses
+    type InjectedTrigger: fmt::Debug + Copy + Into<u8>;
 }
 
 #[inline(always)]
@@ -1431,6 +1998,210 @@ fn configure_clock_source345(cs: ClockSource, rcc: &Rcc) {
 
 macro_rules! adc {
 
+    (watchdog_config: $adc_type:ident) => {
+        /// Configures and enables an analog watchdog
+        ///
+        /// Panics if analog watchdog 1 is asked to guard more than one, but not all, channels
+        #[inline(always)]
+        pub fn configure_watchdog(&mut self, awd: config::AnalogWatchdog, config: &config::AnalogWatchdogConfig<stm32::$adc_type>) {
+            self.adc.configure_watchdog(awd, config)
+        }
+
+        /// Disables an analog watchdog
+        #[inline(always)]
+        pub fn disable_watchdog(&mut self, awd: config::AnalogWatchdog) {
+            self.adc.disable_watchdog(awd)
+        }
+    };
+
+    (conversion_config: $adc_type:ident) => {
+        /// Enables and disables dis-/continuous mode
+        #[inline(always)]
+        pub fn set_continuous(&mut self, continuous: config::Continuous) {
+            self.adc.set_continuous(continuous)
+        }
+
+        /// Set subgroup length, number of AD readings per trigger event (only relevant in Discontinuous mode)
+        #[inline(always)]
+        pub fn set_subgroup_len(&mut self, subgroup_len: config::SubGroupLength) {
+            self.adc.set_subgroup_len(subgroup_len);
+        }
+
+        /// Enables and disables discontinuous mode of the injected group,
+        /// converting one injected channel per trigger.
+        /// Can not be combined with auto injection.
+        #[inline(always)]
+        pub fn set_injected_discontinuous(&mut self, discontinuous: bool) {
+            self.adc.set_injected_discontinuous(discontinuous)
+        }
+
+        /// Configures and enables an offset register
+        #[inline(always)]
+        pub fn configure_offset(&mut self, offset: config::Offset, config: &config::OffsetConfig<stm32::$adc_type>) {
+            self.adc.configure_offset(offset, config)
+        }
+
+        /// Disables an offset register
+        #[inline(always)]
+        pub fn disable_offset(&mut self, offset: config::Offset) {
+            self.adc.disable_offset(offset)
+        }
+
+        /// Enables gain compensation, every conversion is multiplied by `coefficient / 4096`
+        #[inline(always)]
+        pub fn enable_gain_compensation(&mut self, coefficient: u16) {
+            self.adc.enable_gain_compensation(coefficient)
+        }
+
+        /// Disables gain compensation
+        #[inline(always)]
+        pub fn disable_gain_compensation(&mut self) {
+            self.adc.disable_gain_compensation()
+        }
+    };
+
+    (watchdog_flags) => {
+        /// Enable/disable the interrupt of an analog watchdog
+        #[inline(always)]
+        pub fn set_watchdog_interrupt(&mut self, awd: config::AnalogWatchdog, enable: bool) {
+            self.adc.set_watchdog_interrupt(awd, enable)
+        }
+
+        /// Read the flag of an analog watchdog
+        #[inline(always)]
+        pub fn get_watchdog_flag(&self, awd: config::AnalogWatchdog) -> bool {
+            self.adc.get_watchdog_flag(awd)
+        }
+
+        /// Resets the flag of an analog watchdog
+        #[inline(always)]
+        pub fn clear_watchdog_flag(&mut self, awd: config::AnalogWatchdog) {
+            self.adc.clear_watchdog_flag(awd)
+        }
+    };
+
+    (injected_context: $adc_type:ident) => {
+        /// Writes a complete context of the injected group.
+        /// With the queue enabled the context is queued behind the ones not yet consumed by a trigger,
+        /// otherwise it replaces the current one.
+        #[inline(always)]
+        pub fn queue_injected_context(&mut self, context: &config::InjectedContext<stm32::$adc_type>) {
+            self.adc.queue_injected_context(context)
+        }
+    };
+
+    (injected_config: $adc_type:ident => ($injected_trigger_type:ident)) => {
+        /// Sets the sample time of a channel, shared by the regular and the injected group
+        #[inline(always)]
+        pub fn set_channel_sample_time<CHANNEL>(&mut self, channel: &CHANNEL, sample_time: config::SampleTime)
+        where
+            CHANNEL: Channel<stm32::$adc_type, ID=u8>
+        {
+            self.adc.set_channel_sample_time(channel, sample_time)
+        }
+
+        /// Configure a channel of the injected group.
+        /// It will make sure the injected sequence is at least as long as the `sequence` provided.
+        /// # Arguments
+        /// * `channel` - channel to configure
+        /// * `sequence` - where in the injected sequence to sample the channel
+        /// * `sample_time` - how long to sample for, this is shared with the regular group
+        #[inline(always)]
+        pub fn configure_injected_channel<CHANNEL>(&mut self, channel: &CHANNEL, sequence: config::InjectedSequence, sample_time: config::SampleTime)
+        where
+            CHANNEL: Channel<stm32::$adc_type, ID=u8>
+        {
+            self.adc.configure_injected_channel(channel, sequence, sample_time)
+        }
+
+        /// Sets which external trigger starts the injected group and if it is disabled, rising, falling or both
+        #[inline(always)]
+        pub fn set_injected_external_trigger(&mut self, (edge, jextsel): (config::TriggerMode, $injected_trigger_type)) {
+            self.adc.set_injected_external_trigger((edge, jextsel))
+        }
+
+        /// Sets the end-of-conversion behaviour of the injected group.
+        /// The end-of-conversion interrupt occur either per conversion or for the whole sequence.
+        #[inline(always)]
+        pub fn set_injected_end_of_conversion_interrupt(&mut self, eoc: config::Eoc) {
+            self.adc.set_injected_end_of_conversion_interrupt(eoc)
+        }
+
+        /// Enable/disable the injected context queue overflow interrupt
+        #[inline(always)]
+        pub fn set_injected_queue_overflow_interrupt(&mut self, enable: bool) {
+            self.adc.set_injected_queue_overflow_interrupt(enable)
+        }
+    };
+
+    (injected_run) => {
+        /// Starts the injected sequence, or arms it when it is started by an external trigger
+        #[inline(always)]
+        pub fn start_injected_conversion(&mut self) {
+            self.adc.start_injected_conversion()
+        }
+
+        /// Cancels an ongoing injected conversion, this flushes the queue of injected contexts
+        #[inline(always)]
+        pub fn cancel_injected_conversion(&mut self) {
+            self.adc.cancel_injected_conversion()
+        }
+
+        /// Returns if the injected group is started or armed
+        #[inline(always)]
+        pub fn is_injected_conversion_active(&self) -> bool {
+            self.adc.is_injected_conversion_active()
+        }
+
+        /// Block until the injected sequence is completed
+        #[inline(always)]
+        pub fn wait_for_injected_sequence(&mut self) {
+            self.adc.wait_for_injected_sequence()
+        }
+
+        /// Read injected end-of-conversion flag
+        #[inline(always)]
+        pub fn get_injected_end_of_conversion_flag(&self) -> bool {
+            self.adc.get_injected_end_of_conversion_flag()
+        }
+
+        /// Resets the injected end-of-conversion flag
+        #[inline(always)]
+        pub fn clear_injected_end_of_conversion_flag(&mut self) {
+            self.adc.clear_injected_end_of_conversion_flag()
+        }
+
+        /// Read injected end-of-sequence flag
+        #[inline(always)]
+        pub fn get_injected_end_of_sequence_flag(&self) -> bool {
+            self.adc.get_injected_end_of_sequence_flag()
+        }
+
+        /// Resets the injected end-of-sequence flag
+        #[inline(always)]
+        pub fn clear_injected_end_of_sequence_flag(&mut self) {
+            self.adc.clear_injected_end_of_sequence_flag()
+        }
+
+        /// Read injected context queue overflow flag
+        #[inline(always)]
+        pub fn get_injected_queue_overflow_flag(&self) -> bool {
+            self.adc.get_injected_queue_overflow_flag()
+        }
+
+        /// Resets the injected context queue overflow flag
+        #[inline(always)]
+        pub fn clear_injected_queue_overflow_flag(&mut self) {
+            self.adc.clear_injected_queue_overflow_flag()
+        }
+
+        /// Returns the sample of the channel at `sequence` in the injected sequence
+        #[inline(always)]
+        pub fn injected_sample(&self, sequence: config::InjectedSequence) -> u16 {
+            self.adc.injected_sample(sequence)
+        }
+    };
+
     (vbat => ($common_type:ident)) => {
         /// Enables the vbat internal channel
         #[inline(always)]
@@ -1549,10 +2320,11 @@ macro_rules! adc {
     (additionals: $adc_type:ident => ($common_type:ident)) => {
     };
 
-    ($($adc_type:ident => ($trigger_type:ident, $configure_clocks_fn_name:ident, $mux:expr, ($common_type:ident) )),+ $(,)*) => {
+    ($($adc_type:ident => ($trigger_type:ident, $injected_trigger_type:ident, $configure_clocks_fn_name:ident, $mux:expr, ($common_type:ident) )),+ $(,)*) => {
         $(
             impl TriggerType for stm32::$adc_type {
                 type ExternalTrigger = $trigger_type;
+                type InjectedTrigger = $injected_trigger_type;
             }
 
             impl AdcConfig for stm32::$adc_type {
@@ -1569,6 +2341,12 @@ macro_rules! adc {
                     Vref::sample_to_millivolts_ext(sample, self.calibrated_vdda, self.config.resolution)
                 }
 
+                /// Converts millivolts to a sample value using calibrated VDDA and configured resolution
+                #[inline(always)]
+                pub fn millivolts_to_sample(&self, millivolts: u16) -> u16 {
+                    Vref::millivolts_to_sample_ext(millivolts, self.calibrated_vdda, self.config.resolution)
+                }
+
                 /// Disables the Voltage Regulator and release the ADC
                 #[inline(always)]
                 pub fn release(mut self) -> stm32::$adc_type {
@@ -1765,9 +2543,10 @@ macro_rules! adc {
                     );
                 }
 
+                /// Set subgroup length, number of AD readings per trigger event (only relevant in Discontinuous mode)
                 #[inline(always)]
                 // NOTE: The software is allowed to write these bits only when ADSTART = 0
-                fn set_subgroup_len(&mut self, subgroup_len: config::SubGroupLength) {
+                pub fn set_subgroup_len(&mut self, subgroup_len: config::SubGroupLength) {
                     self.config.subgroup_len = subgroup_len;
                     self.adc_reg.cfgr().modify(|_, w| unsafe{w.discnum().bits(subgroup_len as u8);})
                 }
@@ -1933,7 +2712,20 @@ macro_rules! adc {
                         config::Sequence::Sixteen  => self.adc_reg.sqr4().modify(|_, w| unsafe {w.sq16().bits(channel); }),
                     }
 
-                    //Set the sample time for the channel
+                    self.set_sample_time(channel, sample_time);
+                }
+
+                /// Sets the sample time of a channel, shared by the regular and the injected group
+                #[inline(always)]
+                pub fn set_channel_sample_time<CHANNEL>(&mut self, _channel: &CHANNEL, sample_time: config::SampleTime)
+                where
+                    CHANNEL: Channel<stm32::$adc_type, ID=u8>
+                {
+                    self.set_sample_time(CHANNEL::channel(), sample_time);
+                }
+
+                #[inline(always)]
+                fn set_sample_time(&mut self, channel: u8, sample_time: config::SampleTime) {
                     let st = u8::from(sample_time);
                     match channel {
                         0 => self.adc_reg.smpr1().modify(|_, w| unsafe{w.smp0().bits(st);} ),
@@ -1958,6 +2750,317 @@ macro_rules! adc {
                         _ => unimplemented!(),
                     }
                 }
+
+                /// Configure a channel of the injected group.
+                /// It will make sure the injected sequence is at least as long as the `sequence` provided.
+                /// # Arguments
+                /// * `channel` - channel to configure
+                /// * `sequence` - where in the injected sequence to sample the channel
+                /// * `sample_time` - how long to sample for, this is shared with the regular group
+                pub fn configure_injected_channel<CHANNEL>(&mut self, _channel: &CHANNEL, sequence: config::InjectedSequence, sample_time: config::SampleTime)
+                where
+                    CHANNEL: Channel<stm32::$adc_type, ID=u8>
+                {
+                    let channel = CHANNEL::channel();
+
+                    self.adc_reg.jsqr().modify(|r, w| unsafe {
+                        //Check the sequence is long enough
+                        let rank = u8::from(sequence);
+                        if r.jl().bits() < rank {
+                            w.jl().bits(rank);
+                        }
+
+                        //Set the channel in the right sequence field
+                        match sequence {
+                            config::InjectedSequence::One   => w.jsq1().bits(channel),
+                            config::InjectedSequence::Two   => w.jsq2().bits(channel),
+                            config::InjectedSequence::Three => w.jsq3().bits(channel),
+                            config::InjectedSequence::Four  => w.jsq4().bits(channel),
+                        };
+                    });
+
+                    self.set_sample_time(channel, sample_time);
+                }
+
+                /// Sets which external trigger starts the injected group and if it is disabled, rising, falling or both
+                #[inline(always)]
+                pub fn set_injected_external_trigger(&mut self, (edge, jextsel): (config::TriggerMode, $injected_trigger_type)) {
+                    self.adc_reg.jsqr().modify(|_, w| unsafe { w
+                        .jextsel().bits(jextsel.into())
+                        .jexten().bits(edge.into());
+                    });
+                }
+
+                /// Writes a complete context of the injected group.
+                /// With the queue enabled the context is queued behind the ones not yet consumed by a trigger,
+                /// otherwise it replaces the current one.
+                #[inline(always)]
+                pub fn queue_injected_context(&mut self, context: &config::InjectedContext<stm32::$adc_type>) {
+                    self.adc_reg.jsqr().write(|w| unsafe { w.bits(context.bits()); });
+                }
+
+                /// Sets the behaviour of the queue of injected contexts
+                #[inline(always)]
+                pub fn set_injected_queue(&mut self, queue: config::InjectedQueue) {
+                    let (jqdis, jqm) = match queue {
+                        config::InjectedQueue::Disabled => (true, false),
+                        config::InjectedQueue::KeepLast => (false, false),
+                        config::InjectedQueue::EmptyStops => (false, true),
+                    };
+                    self.adc_reg.cfgr().modify(|_, w| {w
+                        .jqdis().bit(jqdis)
+                        .jqm().bit(jqm);
+                    });
+                }
+
+                /// Automatically converts the injected group after the regular group
+                #[inline(always)]
+                pub fn set_auto_injection(&mut self, auto: bool) {
+                    self.adc_reg.cfgr().modify(|_, w| {w.jauto().bit(auto);});
+                }
+
+                /// Sets the end-of-conversion behaviour of the injected group.
+                /// The end-of-conversion interrupt occur either per conversion or for the whole sequence.
+                #[inline(always)]
+                pub fn set_injected_end_of_conversion_interrupt(&mut self, eoc: config::Eoc) {
+                    let (jeocie, jeosie) = match eoc {
+                        config::Eoc::Disabled => (false, false),
+                        config::Eoc::Conversion => (true, false),
+                        config::Eoc::Sequence => (false, true),
+                    };
+                    self.adc_reg.ier().modify(|_, w| {w
+                        .jeocie().bit(jeocie)
+                        .jeosie().bit(jeosie);
+                    });
+                }
+
+                /// Enable/disable the injected context queue overflow interrupt
+                ///
+                /// This is triggered when a context is written while the queue is full
+                #[inline(always)]
+                pub fn set_injected_queue_overflow_interrupt(&mut self, enable: bool) {
+                    self.adc_reg.ier().modify(|_, w| {w.jqovfie().bit(enable);});
+                }
+
+                /// Starts the injected sequence, or arms it when it is started by an external trigger
+                #[inline(always)]
+                pub fn start_injected_conversion(&mut self) {
+                    self.adc_reg.cr().modify(|_, w| {w.jadstart().set_bit();});
+                }
+
+                /// Cancels an ongoing injected conversion, this flushes the queue of injected contexts
+                #[inline(always)]
+                pub fn cancel_injected_conversion(&mut self) {
+                    self.adc_reg.cr().modify(|_, w| {w.jadstp().set_bit();});
+                    while self.adc_reg.cr().read().jadstart().bit_is_set() {}
+                }
+
+                /// Returns if the injected group is started or armed
+                #[inline(always)]
+                pub fn is_injected_conversion_active(&self) -> bool {
+                    self.adc_reg.cr().read().jadstart().bit_is_set()
+                }
+
+                /// Block until the injected sequence is completed
+                pub fn wait_for_injected_sequence(&mut self) {
+                    while !self.adc_reg.isr().read().jeos().bit_is_set() {}
+                }
+
+                /// Read injected end-of-conversion flag
+                #[inline(always)]
+                pub fn get_injected_end_of_conversion_flag(&self) -> bool {
+                    self.adc_reg.isr().read().jeoc().bit()
+                }
+
+                /// Resets the injected end-of-conversion flag
+                #[inline(always)]
+                pub fn clear_injected_end_of_conversion_flag(&mut self) {
+                    self.adc_reg.isr().write(|w| {w.jeoc().set_bit();});
+                }
+
+                /// Read injected end-of-sequence flag
+                #[inline(always)]
+                pub fn get_injected_end_of_sequence_flag(&self) -> bool {
+                    self.adc_reg.isr().read().jeos().bit()
+                }
+
+                /// Resets the injected end-of-sequence flag
+                #[inline(always)]
+                pub fn clear_injected_end_of_sequence_flag(&mut self) {
+                    self.adc_reg.isr().write(|w| {w.jeos().set_bit();});
+                }
+
+                /// Read injected context queue overflow flag
+                #[inline(always)]
+                pub fn get_injected_queue_overflow_flag(&self) -> bool {
+                    self.adc_reg.isr().read().jqovf().bit()
+                }
+
+                /// Resets the injected context queue overflow flag
+                #[inline(always)]
+                pub fn clear_injected_queue_overflow_flag(&mut self) {
+                    self.adc_reg.isr().write(|w| {w.jqovf().set_bit();});
+                }
+
+                /// Returns the sample of the channel at `sequence` in the injected sequence
+                #[inline(always)]
+                pub fn injected_sample(&self, sequence: config::InjectedSequence) -> u16 {
+                    match sequence {
+                        config::InjectedSequence::One   => self.adc_reg.jdr1().read().jdata().bits(),
+                        config::InjectedSequence::Two   => self.adc_reg.jdr2().read().jdata().bits(),
+                        config::InjectedSequence::Three => self.adc_reg.jdr3().read().jdata().bits(),
+                        config::InjectedSequence::Four  => self.adc_reg.jdr4().read().jdata().bits(),
+                    }
+                }
+                /// Configures and enables an analog watchdog
+                ///
+                /// Panics if analog watchdog 1 is asked to guard more than one, but not all, channels
+                pub fn configure_watchdog(&mut self, awd: config::AnalogWatchdog, config: &config::AnalogWatchdogConfig<stm32::$adc_type>) {
+                    // The thresholds are compared against the conversion scaled to 12 bits
+                    let shift = self.resolution_shift();
+                    let low = config.low << shift;
+                    let high = config.high << shift;
+
+                    match awd {
+                        config::AnalogWatchdog::One => {
+                            let all = config.channels == config::AnalogWatchdogConfig::<stm32::$adc_type>::ALL_CHANNELS;
+                            assert!(all || config.channels.count_ones() == 1);
+                            let channel = if all { 0 } else { config.channels.trailing_zeros() as u8 };
+
+                            self.adc_reg.tr1().modify(|_, w| unsafe { w
+                                .lt1().bits(low)
+                                .ht1().bits(high)
+                                .awdfilt().bits(config.filter.into());
+                            });
+                            self.adc_reg.cfgr().modify(|_, w| unsafe { w
+                                .awd1ch().bits(channel)
+                                .awd1sgl().bit(!all)
+                                .awd1en().set_bit()
+                                .jawd1en().set_bit();
+                            });
+                        },
+                        config::AnalogWatchdog::Two => {
+                            // Only the 8 most significant bits are compared
+                            self.adc_reg.tr2().modify(|_, w| unsafe { w
+                                .lt2().bits((low >> 4) as u8)
+                                .ht2().bits((high >> 4) as u8);
+                            });
+                            self.adc_reg.awd2cr().write(|w| unsafe { w.awd2ch().bits(config.channels); });
+                        },
+                        config::AnalogWatchdog::Three => {
+                            // Only the 8 most significant bits are compared
+                            self.adc_reg.tr3().modify(|_, w| unsafe { w
+                                .lt3().bits((low >> 4) as u8)
+                                .ht3().bits((high >> 4) as u8);
+                            });
+                            self.adc_reg.awd3cr().write(|w| unsafe { w.awd3ch().bits(config.channels); });
+                        },
+                    }
+                }
+
+                /// Disables an analog watchdog
+                #[inline(always)]
+                pub fn disable_watchdog(&mut self, awd: config::AnalogWatchdog) {
+                    match awd {
+                        config::AnalogWatchdog::One => self.adc_reg.cfgr().modify(|_, w| {w.awd1en().clear_bit().jawd1en().clear_bit();}),
+                        config::AnalogWatchdog::Two => self.adc_reg.awd2cr().write(|w| unsafe {w.awd2ch().bits(0);}),
+                        config::AnalogWatchdog::Three => self.adc_reg.awd3cr().write(|w| unsafe {w.awd3ch().bits(0);}),
+                    }
+                }
+
+                /// Enable/disable the interrupt of an analog watchdog
+                #[inline(always)]
+                pub fn set_watchdog_interrupt(&mut self, awd: config::AnalogWatchdog, enable: bool) {
+                    match awd {
+                        config::AnalogWatchdog::One => self.adc_reg.ier().modify(|_, w| {w.awd1ie().bit(enable);}),
+                        config::AnalogWatchdog::Two => self.adc_reg.ier().modify(|_, w| {w.awd2ie().bit(enable);}),
+                        config::AnalogWatchdog::Three => self.adc_reg.ier().modify(|_, w| {w.awd3ie().bit(enable);}),
+                    }
+                }
+
+                /// Read the flag of an analog watchdog
+                #[inline(always)]
+                pub fn get_watchdog_flag(&self, awd: config::AnalogWatchdog) -> bool {
+                    let isr = self.adc_reg.isr().read();
+                    match awd {
+                        config::AnalogWatchdog::One => isr.awd1().bit(),
+                        config::AnalogWatchdog::Two => isr.awd2().bit(),
+                        config::AnalogWatchdog::Three => isr.awd3().bit(),
+                    }
+                }
+
+                /// Resets the flag of an analog watchdog
+                #[inline(always)]
+                pub fn clear_watchdog_flag(&mut self, awd: config::AnalogWatchdog) {
+                    match awd {
+                        config::AnalogWatchdog::One => self.adc_reg.isr().write(|w| {w.awd1().set_bit();}),
+                        config::AnalogWatchdog::Two => self.adc_reg.isr().write(|w| {w.awd2().set_bit();}),
+                        config::AnalogWatchdog::Three => self.adc_reg.isr().write(|w| {w.awd3().set_bit();}),
+                    }
+                }
+
+                /// Configures and enables an offset register
+                ///
+                /// NOTE: Only while no regular or injected conversion is ongoing
+                #[inline(always)]
+                pub fn configure_offset(&mut self, offset: config::Offset, config: &config::OffsetConfig<stm32::$adc_type>) {
+                    // The offset is subtracted from the conversion scaled to 12 bits
+                    let bits = config.bits(self.resolution_shift());
+                    match offset {
+                        config::Offset::One => self.adc_reg.ofr1().write(|w| unsafe {w.bits(bits);}),
+                        config::Offset::Two => self.adc_reg.ofr2().write(|w| unsafe {w.bits(bits);}),
+                        config::Offset::Three => self.adc_reg.ofr3().write(|w| unsafe {w.bits(bits);}),
+                        config::Offset::Four => self.adc_reg.ofr4().write(|w| unsafe {w.bits(bits);}),
+                    }
+                }
+
+                /// Disables an offset register
+                #[inline(always)]
+                pub fn disable_offset(&mut self, offset: config::Offset) {
+                    match offset {
+                        config::Offset::One => self.adc_reg.ofr1().reset(),
+                        config::Offset::Two => self.adc_reg.ofr2().reset(),
+                        config::Offset::Three => self.adc_reg.ofr3().reset(),
+                        config::Offset::Four => self.adc_reg.ofr4().reset(),
+                    }
+                }
+
+                /// Enables gain compensation, every conversion is multiplied by `coefficient / 4096`
+                ///
+                /// The coefficient is 14 bits wide, allowing gains from 0 up to almost 4.
+                /// NOTE: Only while no regular or injected conversion is ongoing
+                #[inline(always)]
+                pub fn enable_gain_compensation(&mut self, coefficient: u16) {
+                    assert!(coefficient < (1 << 14));
+                    self.adc_reg.gcomp().write(|w| unsafe {w.gcompcoeff().bits(coefficient);});
+                    self.adc_reg.cfgr2().modify(|_, w| {w.gcomp().set_bit();});
+                }
+
+                /// Disables gain compensation
+                #[inline(always)]
+                pub fn disable_gain_compensation(&mut self) {
+                    self.adc_reg.cfgr2().modify(|_, w| {w.gcomp().clear_bit();});
+                }
+
+                /// Enables and disables discontinuous mode of the injected group,
+                /// converting one injected channel per trigger.
+                /// Can not be combined with auto injection.
+                #[inline(always)]
+                pub fn set_injected_discontinuous(&mut self, discontinuous: bool) {
+                    self.adc_reg.cfgr().modify(|_, w| {w.jdiscen().bit(discontinuous);});
+                }
+
+                /// Left shift scaling a sample at the configured resolution to 12 bits
+                #[inline(always)]
+                fn resolution_shift(&self) -> u8 {
+                    match self.config.resolution {
+                        config::Resolution::Twelve => 0,
+                        config::Resolution::Ten => 2,
+                        config::Resolution::Eight => 4,
+                        config::Resolution::Six => 6,
+                    }
+                }
+
                 /// Synchronously convert a single sample
                 /// Note that it reconfigures the adc sequence and doesn't restore it
                 pub fn convert<PIN>(&mut self, pin: &PIN, sample_time: config::SampleTime) -> u16
@@ -2162,6 +3265,12 @@ macro_rules! adc {
                 pub fn sample_to_millivolts(&self, sample: u16) -> u16 {
                     self.adc.sample_to_millivolts(sample)
                 }
+
+                /// Converts millivolts to a sample value using calibrated VDDA and configured resolution
+                #[inline(always)]
+                pub fn millivolts_to_sample(&self, millivolts: u16) -> u16 {
+                    self.adc.millivolts_to_sample(millivolts)
+                }
             }
 
             impl Adc<stm32::$adc_type, PoweredDown> {
@@ -2238,6 +3347,11 @@ macro_rules! adc {
             impl Adc<stm32::$adc_type, Disabled> {
                 adc!(additionals: $adc_type => ($common_type));
                 adc!(additionals_checks: $adc_type => ($common_type));
+                adc!(injected_context: $adc_type);
+                adc!(injected_config: $adc_type => ($injected_trigger_type));
+                adc!(watchdog_config: $adc_type);
+                adc!(conversion_config: $adc_type);
+                adc!(watchdog_flags);
 
                 /// Enables the adc
                 #[inline(always)]
@@ -2326,15 +3440,16 @@ macro_rules! adc {
                     self.adc.set_auto_delay(delay)
                 }
 
-                /// Enables and disables continuous mode
+                /// Sets the behaviour of the queue of injected contexts
                 #[inline(always)]
-                pub fn set_continuous(&mut self, continuous: config::Continuous) {
-                    self.adc.set_continuous(continuous)
+                pub fn set_injected_queue(&mut self, queue: config::InjectedQueue) {
+                    self.adc.set_injected_queue(queue)
                 }
 
-                /// Set subgroup length, number of AD readings per trigger event (only relevant in Discontinuous mode)
-                pub fn set_subgroup_len(&mut self, subgroup_len: config::SubGroupLength) {
-                    self.adc.set_subgroup_len(subgroup_len);
+                /// Automatically converts the injected group after the regular group
+                #[inline(always)]
+                pub fn set_auto_injection(&mut self, auto: bool) {
+                    self.adc.set_auto_injection(auto)
                 }
 
                 /// Sets DMA to disabled, single or continuous
@@ -2424,6 +3539,12 @@ macro_rules! adc {
 
             impl Adc<stm32::$adc_type, Configured> {
                 adc!(additionals_checks: $adc_type => ($common_type));
+                adc!(injected_context: $adc_type);
+                adc!(injected_config: $adc_type => ($injected_trigger_type));
+                adc!(watchdog_config: $adc_type);
+                adc!(conversion_config: $adc_type);
+                adc!(watchdog_flags);
+                adc!(injected_run);
 
                 /// Disables the adc
                 #[inline(always)]
@@ -2496,6 +3617,10 @@ macro_rules! adc {
             }
 
             impl Adc<stm32::$adc_type, Active> {
+                adc!(injected_context: $adc_type);
+                adc!(injected_run);
+                adc!(watchdog_flags);
+
                 /// Block until the conversion is completed and return to configured
                 pub fn wait_for_conversion_sequence(mut self) -> Conversion<stm32::$adc_type> {
                     self.adc.wait_for_conversion_sequence();
@@ -2546,6 +3671,10 @@ macro_rules! adc {
             }
 
             impl Adc<stm32::$adc_type, DMA> {
+                adc!(injected_context: $adc_type);
+                adc!(injected_run);
+                adc!(watchdog_flags);
+
                 /// Starts conversion sequence. Waits for the hardware to indicate it's actually started.
                 #[inline(always)]
                 pub fn start_conversion(&mut self) {
@@ -2625,7 +3754,7 @@ macro_rules! adc {
     feature = "stm32g491",
     feature = "stm32g4a1",
 ))]
-adc!(ADC1 => (ExternalTrigger12, configure_clock_source12, DmaMuxResources::ADC1, (ADC12_COMMON) ));
+adc!(ADC1 => (ExternalTrigger12, InjectedExternalTrigger12, configure_clock_source12, DmaMuxResources::ADC1, (ADC12_COMMON) ));
 
 #[cfg(any(
     feature = "stm32g431",
@@ -2638,7 +3767,7 @@ adc!(ADC1 => (ExternalTrigger12, configure_clock_source12, DmaMuxResources::ADC1
     feature = "stm32g491",
     feature = "stm32g4a1",
 ))]
-adc!(ADC2 => (ExternalTrigger12, configure_clock_source12, DmaMuxResources::ADC2, (ADC12_COMMON) ));
+adc!(ADC2 => (ExternalTrigger12, InjectedExternalTrigger12, configure_clock_source12, DmaMuxResources::ADC2, (ADC12_COMMON) ));
 
 #[cfg(any(
     feature = "stm32g471",
@@ -2649,7 +3778,15 @@ adc!(ADC2 => (ExternalTrigger12, configure_clock_source12, DmaMuxResources::ADC2
     feature = "stm32g491",
     feature = "stm32g4a1",
 ))]
-adc!(ADC3 => (ExternalTrigger345, configure_clock_source345, DmaMuxResources::ADC3, (ADC345_COMMON) ));
+adc!(ADC3 => (ExternalTrigger345, InjectedExternalTrigger345, configure_clock_source345, DmaMuxResources::ADC3, (ADC345_COMMON) ));
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+))]
+adc!(ADC4 => (ExternalTrigger345, InjectedExternalTrigger345, configure_clock_source345, DmaMuxResources::ADC4, (ADC345_COMMON) ));
 
 #[cfg(any(
     feature = "stm32g473",
@@ -2657,7 +3794,272 @@ adc!(ADC3 => (ExternalTrigger345, configure_clock_source345, DmaMuxResources::AD
     feature = "stm32g483",
     feature = "stm32g484",
 ))]
-adc!(ADC4 => (ExternalTrigger345, configure_clock_source345, DmaMuxResources::ADC4, (ADC345_COMMON) ));
+adc!(ADC5 => (ExternalTrigger345, InjectedExternalTrigger345, configure_clock_source345, DmaMuxResources::ADC5, (ADC345_COMMON) ));
+
+macro_rules! dual_adc {
+    ($master:ident, $slave:ident => ($common_type:ident, $mux:expr)) => {
+        impl<STATUS> DualAdc<stm32::$master, stm32::$slave, STATUS> {
+            /// Starts the injected sequence of both ADCs
+            #[inline(always)]
+            pub fn start_injected_conversion(&mut self) {
+                self.master.start_injected_conversion();
+            }
+
+            /// Block until the injected sequences of both ADCs are completed
+            #[inline(always)]
+            pub fn wait_for_injected_sequence(&mut self) {
+                self.master.wait_for_injected_sequence();
+                self.slave.wait_for_injected_sequence();
+            }
+
+            /// Resets the injected end-of-sequence flags of both ADCs
+            #[inline(always)]
+            pub fn clear_injected_end_of_sequence_flag(&mut self) {
+                self.master.clear_injected_end_of_sequence_flag();
+                self.slave.clear_injected_end_of_sequence_flag();
+            }
+
+            /// Returns the `(master, slave)` samples of the given rank of the injected sequence
+            #[inline(always)]
+            pub fn injected_samples(&self, sequence: config::InjectedSequence) -> (u16, u16) {
+                (
+                    self.master.injected_sample(sequence),
+                    self.slave.injected_sample(sequence),
+                )
+            }
+
+            #[inline(always)]
+            fn set_mode(&mut self, mode: config::DualMode) {
+                unsafe {
+                    let common = &(*stm32::$common_type::ptr());
+                    common.ccr().modify(|_, w| {
+                        w.dual().bits(mode.into());
+                    });
+                }
+            }
+        }
+
+        impl DualAdc<stm32::$master, stm32::$slave, Configured> {
+            /// Pairs two enabled ADCs and switches them to the given dual mode
+            #[inline(always)]
+            pub fn new(
+                master: Adc<stm32::$master, Configured>,
+                slave: Adc<stm32::$slave, Configured>,
+                mode: config::DualMode,
+            ) -> Self {
+                let mut dual = DualAdc {
+                    master: master.adc,
+                    slave: slave.adc,
+                    _status: PhantomData,
+                };
+                dual.set_mode(mode);
+                dual
+            }
+
+            /// Changes the dual mode
+            #[inline(always)]
+            pub fn set_dual_mode(&mut self, mode: config::DualMode) {
+                self.set_mode(mode);
+            }
+
+            /// Sets the delay between the sampling phases of the two ADCs in interleaved mode
+            ///
+            /// `delay` is the raw 4 bit DELAY field, the resulting number of ADC clock cycles
+            /// depends on the resolution, see the reference manual.
+            #[inline(always)]
+            pub fn set_delay(&mut self, delay: u8) {
+                assert!(delay < 16);
+                unsafe {
+                    let common = &(*stm32::$common_type::ptr());
+                    common.ccr().modify(|_, w| {
+                        w.delay().bits(delay);
+                    });
+                }
+            }
+
+            /// Starts the conversion sequence of both ADCs
+            #[inline(always)]
+            pub fn start_conversion(mut self) -> DualAdc<stm32::$master, stm32::$slave, Active> {
+                self.master.clear_end_of_conversion_flag();
+                self.slave.clear_end_of_conversion_flag();
+                self.master.start_conversion();
+
+                DualAdc {
+                    master: self.master,
+                    slave: self.slave,
+                    _status: PhantomData,
+                }
+            }
+
+            /// Returns the `(master, slave)` samples stored in the ADC data registers
+            #[inline(always)]
+            pub fn current_samples(&self) -> (u16, u16) {
+                (self.master.current_sample(), self.slave.current_sample())
+            }
+
+            /// Enables DMA transfers of the packed common data register
+            /// panics if set to Dma::Disabled
+            #[inline(always)]
+            pub fn enable_dma(
+                mut self,
+                dma: config::Dma,
+            ) -> DualAdc<stm32::$master, stm32::$slave, DMA> {
+                let dds = match dma {
+                    config::Dma::Disabled => {
+                        panic!("Requesting Enabling DMA with DisableDma parameter")
+                    }
+                    config::Dma::Single => false,
+                    config::Dma::Continuous => true,
+                };
+
+                // The requests come from the common block instead of the individual ADCs
+                self.master.set_dma(config::Dma::Disabled);
+                self.slave.set_dma(config::Dma::Disabled);
+                unsafe {
+                    let common = &(*stm32::$common_type::ptr());
+                    common.ccr().modify(|_, w| {
+                        w.dmacfg().bit(dds).mdma().bits(0b10);
+                    });
+                }
+
+                DualAdc {
+                    master: self.master,
+                    slave: self.slave,
+                    _status: PhantomData,
+                }
+            }
+
+            /// Switches the ADCs back to independent mode and releases them
+            #[inline(always)]
+            pub fn release(
+                mut self,
+            ) -> (
+                Adc<stm32::$master, Configured>,
+                Adc<stm32::$slave, Configured>,
+            ) {
+                self.set_mode(config::DualMode::Independent);
+
+                (
+                    Adc {
+                        adc: self.master,
+                        _status: PhantomData,
+                    },
+                    Adc {
+                        adc: self.slave,
+                        _status: PhantomData,
+                    },
+                )
+            }
+        }
+
+        impl DualAdc<stm32::$master, stm32::$slave, Active> {
+            /// Block until the current conversion of both ADCs is completed
+            #[inline(always)]
+            pub fn wait_for_conversion_sequence(&mut self) {
+                self.master.wait_for_conversion_sequence();
+                self.slave.wait_for_conversion_sequence();
+            }
+
+            /// Returns if the conversion sequence has been completed
+            #[inline(always)]
+            pub fn is_conversion_done(&self) -> bool {
+                !self.master.is_conversion_active()
+            }
+
+            /// Returns the `(master, slave)` samples stored in the ADC data registers,
+            /// which also resets the end-of-conversion flags
+            #[inline(always)]
+            pub fn current_samples(&self) -> (u16, u16) {
+                (self.master.current_sample(), self.slave.current_sample())
+            }
+
+            /// Cancels an ongoing conversion
+            #[inline(always)]
+            pub fn cancel_conversion(
+                mut self,
+            ) -> DualAdc<stm32::$master, stm32::$slave, Configured> {
+                if self.master.is_conversion_active() {
+                    self.master.cancel_conversion();
+                }
+
+                DualAdc {
+                    master: self.master,
+                    slave: self.slave,
+                    _status: PhantomData,
+                }
+            }
+        }
+
+        impl DualAdc<stm32::$master, stm32::$slave, DMA> {
+            /// Starts the conversion sequence of both ADCs
+            #[inline(always)]
+            pub fn start_conversion(&mut self) {
+                self.master.start_conversion();
+            }
+
+            /// Cancels an ongoing conversion
+            #[inline(always)]
+            pub fn cancel_conversion(&mut self) {
+                self.master.cancel_conversion();
+            }
+
+            /// Read overrun flag of the master ADC
+            #[inline(always)]
+            pub fn get_overrun_flag(&self) -> bool {
+                self.master.get_overrun_flag()
+            }
+
+            /// Resets the overrun flag of the master ADC
+            #[inline(always)]
+            pub fn clear_overrun_flag(&mut self) {
+                self.master.clear_overrun_flag();
+            }
+
+            /// Disables DMA transfers of the common data register
+            #[inline(always)]
+            pub fn disable_dma(self) -> DualAdc<stm32::$master, stm32::$slave, Configured> {
+                unsafe {
+                    let common = &(*stm32::$common_type::ptr());
+                    common.ccr().modify(|_, w| {
+                        w.dmacfg().clear_bit().mdma().bits(0b00);
+                    });
+                }
+
+                DualAdc {
+                    master: self.master,
+                    slave: self.slave,
+                    _status: PhantomData,
+                }
+            }
+        }
+
+        unsafe impl TargetAddress<PeripheralToMemory>
+            for DualAdc<stm32::$master, stm32::$slave, DMA>
+        {
+            #[inline(always)]
+            fn address(&self) -> u32 {
+                unsafe { (*stm32::$common_type::ptr()).cdr() as *const _ as u32 }
+            }
+
+            type MemSize = u32;
+
+            const REQUEST_LINE: Option<u8> = Some($mux as u8);
+        }
+    };
+}
+
+#[cfg(any(
+    feature = "stm32g431",
+    feature = "stm32g441",
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1",
+))]
+dual_adc!(ADC1, ADC2 => (ADC12_COMMON, DmaMuxResources::ADC1));
 
 #[cfg(any(
     feature = "stm32g473",
@@ -2665,7 +4067,7 @@ adc!(ADC4 => (ExternalTrigger345, configure_clock_source345, DmaMuxResources::AD
     feature = "stm32g483",
     feature = "stm32g484",
 ))]
-adc!(ADC5 => (ExternalTrigger345, configure_clock_source345, DmaMuxResources::ADC5, (ADC345_COMMON) ));
+dual_adc!(ADC3, ADC4 => (ADC345_COMMON, DmaMuxResources::ADC3));
 
 #[cfg(any(feature = "stm32g431", feature = "stm32g441", feature = "stm32g471",))]
 adc_pins!(
@@ -0,0 +1,68 @@
+//! Async support
+//!
+//! With the `async` feature enabled, the SPI, I2C, serial, delay, GPIO and DMA drivers
+//! implement the `embedded-hal-async` and `embedded-io-async` traits. Instead of busy-waiting
+//! they enable the relevant peripheral interrupt and suspend until it fires.
+//!
+//! The HAL does not own the interrupt vectors, so the application has to forward each
+//! interrupt used by an async driver by calling [`OnInterrupt::on_interrupt`] from its handler:
+//!
+//! ```ignore
+//! use stm32g4xx_hal::{asynch::OnInterrupt, stm32::{self, interrupt}};
+//!
+//! #[interrupt]
+//! fn USART2() {
+//!     stm32::USART2::on_interrupt();
+//! }
+//!
+//! #[interrupt]
+//! fn DMA1_CH1() {
+//!     stm32g4xx_hal::dma::stream::Stream0::<stm32::DMA1>::on_interrupt();
+//! }
+//! ```
+//!
+//! The handlers only mask the interrupt sources that fired and wake the waiting task, the
+//! status flags are evaluated by the driver once the task is polled again.
+use core::cell::RefCell;
+use core::task::Waker;
+
+use cortex_m::interrupt::{self, Mutex};
+
+/// Interrupt handler of an async driver
+pub trait OnInterrupt {
+    /// Wakes the tasks waiting on this interrupt
+    ///
+    /// Call this from the interrupt handler of the peripheral.
+    fn on_interrupt();
+}
+
+/// Storage for the waker of the task waiting on an interrupt
+pub(crate) struct AtomicWaker {
+    waker: Mutex<RefCell<Option<Waker>>>,
+}
+
+impl AtomicWaker {
+    pub(crate) const fn new() -> Self {
+        Self {
+            waker: Mutex::new(RefCell::new(None)),
+        }
+    }
+
+    /// Registers `waker` to be woken on the next interrupt
+    pub(crate) fn register(&self, waker: &Waker) {
+        interrupt::free(|cs| {
+            let mut slot = self.waker.borrow(cs).borrow_mut();
+            match slot.as_ref() {
+                Some(w) if w.will_wake(waker) => {}
+                _ => *slot = Some(waker.clone()),
+            }
+        });
+    }
+
+    /// Wakes the registered waker, if any
+    pub(crate) fn wake(&self) {
+        if let Some(waker) = interrupt::free(|cs| self.waker.borrow(cs).borrow_mut().take()) {
+            waker.wake();
+        }
+    }
+}
@@ -0,0 +1,515 @@
+//! Input capture
+//!
+//! The counter of a timer runs at a fixed frequency and its channels latch the counter value on
+//! the edges of their input pins, which gives timestamps with the resolution of the counter
+//! frequency. The same pins as for PWM output are used, in alternate mode.
+//!
+//! ```ignore
+//! let pin: PA0<Alternate<AF1>> = gpioa.pa0.into_alternate();
+//!
+//! let (timer, (ch1, ch2, ch3, ch4)) = dp.TIM2.capture(1.MHz(), &mut rcc);
+//! let mut ch1 = ch1.capture(pin);
+//! ch1.set_edge(CaptureEdge::Rising);
+//! ch1.enable();
+//!
+//! let timestamp = nb::block!(ch1.read()).unwrap();
+//! ```
+//!
+//! Captures can be transferred into a buffer by DMA, see [`Capture::enable_dma`].
+//!
+//! # PWM input
+//!
+//! A PWM signal on the channel 1 pin is measured with two channels: channel 1 captures the
+//! period on the rising edges, which also reset the counter, and channel 2 captures the high
+//! time on the falling edges. See [`PwmInputExt`].
+use core::marker::PhantomData;
+
+use crate::dma::{mux::DmaMuxResources, traits::TargetAddress, PeripheralToMemory};
+use crate::pwm::{Pins, C1, C2, C3, C4};
+use crate::rcc::{Enable, GetBusFreq, Rcc, Reset};
+use crate::stm32::RCC;
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+use crate::stm32::TIM20;
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+use crate::stm32::TIM5;
+use crate::stm32::{TIM1, TIM15, TIM16, TIM17, TIM2, TIM3, TIM4, TIM8};
+use crate::time::Hertz;
+use crate::timer::InputFilter;
+
+/// Input capture errors
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Error {
+    /// A new capture happened before the previous one was read, the previous one is lost
+    Overcapture,
+}
+
+/// Edges of the input triggering a capture
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum CaptureEdge {
+    /// Rising edges
+    Rising,
+    /// Falling edges
+    Falling,
+    /// Both edges
+    Both,
+}
+
+/// Number of edges per capture
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum CapturePrescaler {
+    /// Capture on every edge
+    Div1 = 0b00,
+    /// Capture once every 2 edges
+    Div2 = 0b01,
+    /// Capture once every 4 edges
+    Div4 = 0b10,
+    /// Capture once every 8 edges
+    Div8 = 0b11,
+}
+
+/// Timer running at a fixed frequency, with channels capturing its counter
+pub struct CaptureTimer<TIM> {
+    tim: TIM,
+    clk: Hertz,
+}
+
+/// Input capture channel without a pin, each channel of a timer exists once
+pub struct CaptureChannel<TIM, CHANNEL> {
+    _tim: PhantomData<TIM>,
+    _channel: PhantomData<CHANNEL>,
+}
+
+/// Input capture channel capturing the edges of `PIN`
+pub struct Capture<TIM, CHANNEL, PIN> {
+    channel: CaptureChannel<TIM, CHANNEL>,
+    pin: PIN,
+}
+
+/// PWM input measurement on the channel 1 pin of a timer
+pub struct PwmInput<TIM, PIN> {
+    tim: TIM,
+    clk: Hertz,
+    pin: PIN,
+}
+
+/// Allows the capture() method to be added to the timer peripherals
+pub trait CaptureExt: Sized {
+    /// Tuple of the [`CaptureChannel`]s of the timer
+    type Channels;
+
+    /// Starts the counter at `frequency`, the resolution of the captures, and returns the
+    /// channels of the timer. The actual frequency is the closest one achievable by the
+    /// prescaler.
+    fn capture<T>(self, frequency: T, rcc: &mut Rcc) -> (CaptureTimer<Self>, Self::Channels)
+    where
+        T: Into<Hertz>;
+}
+
+/// Allows the pwm_input() method to be added to the timer peripherals with a slave mode controller
+pub trait PwmInputExt: Sized {
+    /// Measures the PWM signal on the channel 1 pin, counting at `frequency`.
+    ///
+    /// The period of the signal has to fit in the counter, longer periods are not detected.
+    fn pwm_input<PIN, COMP, T>(self, pin: PIN, frequency: T, rcc: &mut Rcc) -> PwmInput<Self, PIN>
+    where
+        PIN: Pins<Self, C1, COMP>,
+        T: Into<Hertz>;
+}
+
+/// Enables the timer and sets its prescaler, returns the counter frequency
+fn init<TIM, F>(set_psc: F, frequency: Hertz, rcc: &Rcc) -> Hertz
+where
+    TIM: Enable + Reset + GetBusFreq,
+    F: FnOnce(u16),
+{
+    unsafe {
+        let rcc_ptr = &(*RCC::ptr());
+        TIM::enable(rcc_ptr);
+        TIM::reset(rcc_ptr);
+    }
+
+    let clk = TIM::get_timer_frequency(&rcc.clocks);
+    let psc = (clk.raw() / frequency.raw()).max(1) - 1;
+    assert!(psc <= 0xFFFF);
+    set_psc(psc as u16);
+
+    Hertz::from_raw(clk.raw() / (psc + 1))
+}
+
+macro_rules! capture {
+    ($($TIMX:ident: ($typ:ty, [$(($CX:ident, $ccmr:ident, $ccs:ident, $icpsc:ident, $icf:ident, $cce:ident, $ccp:ident, $ccnp:ident, $ccr:ident, $ccif:ident, $ccof:ident, $ccie:ident, $ccde:ident, $mux:ident),)+]),)+) => {
+        $(
+            impl CaptureExt for $TIMX {
+                type Channels = ($(CaptureChannel<$TIMX, $CX>,)+);
+
+                fn capture<T>(self, frequency: T, rcc: &mut Rcc) -> (CaptureTimer<Self>, Self::Channels)
+                where
+                    T: Into<Hertz>,
+                {
+                    let clk = init::<$TIMX, _>(
+                        |psc| self.psc().write(|w| unsafe { w.psc().bits(psc); }),
+                        frequency.into(),
+                        rcc,
+                    );
+
+                    // Count over the full range of the counter
+                    self.arr().write(|w| unsafe { w.bits(<$typ>::MAX as u32); });
+                    // Load the prescaler
+                    self.egr().write(|w| { w.ug().set_bit(); });
+                    self.cr1().write(|w| { w.cen().set_bit(); });
+
+                    let channels = ($(
+                        CaptureChannel::<$TIMX, $CX> {
+                            _tim: PhantomData,
+                            _channel: PhantomData,
+                        },
+                    )+);
+                    (CaptureTimer { tim: self, clk }, channels)
+                }
+            }
+
+            impl CaptureTimer<$TIMX> {
+
+                /// Returns the current counter value
+                #[inline]
+                pub fn now(&self) -> $typ {
+                    self.tim.cnt().read().bits() as $typ
+                }
+
+                /// Returns the frequency of the counter
+                #[inline]
+                pub fn frequency(&self) -> Hertz {
+                    self.clk
+                }
+
+                /// Stops the counter and releases the timer, all of its channels have to be
+                /// handed back
+                pub fn release(self, _channels: <$TIMX as CaptureExt>::Channels) -> $TIMX {
+                    self.tim.cr1().modify(|_, w| { w.cen().clear_bit(); });
+                    self.tim
+                }
+            }
+
+            $(
+                impl CaptureChannel<$TIMX, $CX> {
+                    /// Captures the edges of `pin`
+                    pub fn capture<PIN, COMP>(self, pin: PIN) -> Capture<$TIMX, $CX, PIN>
+                    where
+                        PIN: Pins<$TIMX, $CX, COMP>,
+                    {
+                        Capture { channel: self, pin }
+                    }
+                }
+
+                impl<PIN> Capture<$TIMX, $CX, PIN> {
+                    /// Selects the edges triggering a capture
+                    #[inline]
+                    pub fn set_edge(&mut self, edge: CaptureEdge) {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        let (p, np) = match edge {
+                            CaptureEdge::Rising => (false, false),
+                            CaptureEdge::Falling => (true, false),
+                            CaptureEdge::Both => (true, true),
+                        };
+                        tim.ccer().modify(|_, w| { w.$ccp().bit(p).$ccnp().bit(np); });
+                    }
+
+                    /// Sets the number of edges per capture
+                    #[inline]
+                    pub fn set_prescaler(&mut self, prescaler: CapturePrescaler) {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        tim.$ccmr().modify(|_, w| unsafe { w.$icpsc().bits(prescaler as u8); });
+                    }
+
+                    /// Sets the digital filter of the input
+                    #[inline]
+                    pub fn set_filter(&mut self, filter: InputFilter) {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        tim.$ccmr().modify(|_, w| unsafe { w.$icf().bits(filter as u8); });
+                    }
+
+                    /// Enables the capture
+                    #[inline]
+                    pub fn enable(&mut self) {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        // Capture the input of this channel
+                        tim.$ccmr().modify(|_, w| unsafe { w.$ccs().bits(0b01); });
+                        tim.ccer().modify(|_, w| { w.$cce().set_bit(); });
+                    }
+
+                    /// Disables the capture
+                    #[inline]
+                    pub fn disable(&mut self) {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        tim.ccer().modify(|_, w| { w.$cce().clear_bit(); });
+                    }
+
+                    /// Reads the last capture
+                    #[inline]
+                    pub fn read(&mut self) -> nb::Result<$typ, Error> {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        let sr = tim.sr().read();
+                        if sr.$ccof().bit_is_set() {
+                            tim.sr().modify(|_, w| { w.$ccof().clear_bit(); });
+                            // Reading the capture register clears the capture flag
+                            tim.$ccr().read();
+                            Err(nb::Error::Other(Error::Overcapture))
+                        } else if sr.$ccif().bit_is_set() {
+                            Ok(tim.$ccr().read().bits() as $typ)
+                        } else {
+                            Err(nb::Error::WouldBlock)
+                        }
+                    }
+
+                    /// Starts listening for captures
+                    #[inline]
+                    pub fn listen(&mut self) {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        tim.dier().modify(|_, w| { w.$ccie().set_bit(); });
+                    }
+
+                    /// Stops listening for captures
+                    #[inline]
+                    pub fn unlisten(&mut self) {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        tim.dier().modify(|_, w| { w.$ccie().clear_bit(); });
+                    }
+
+                    /// Requests a DMA transfer for each capture, use the channel as the
+                    /// peripheral of a `dma::Transfer` to fill a buffer with captures
+                    #[inline]
+                    pub fn enable_dma(&mut self) {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        tim.dier().modify(|_, w| { w.$ccde().set_bit(); });
+                    }
+
+                    /// Stops requesting DMA transfers
+                    #[inline]
+                    pub fn disable_dma(&mut self) {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        tim.dier().modify(|_, w| { w.$ccde().clear_bit(); });
+                    }
+
+                    /// Disables the capture, its interrupt and DMA requests and releases the
+                    /// channel and the pin
+                    pub fn release(mut self) -> (CaptureChannel<$TIMX, $CX>, PIN) {
+                        self.disable();
+                        self.unlisten();
+                        self.disable_dma();
+                        (self.channel, self.pin)
+                    }
+                }
+
+                unsafe impl<PIN> TargetAddress<PeripheralToMemory> for Capture<$TIMX, $CX, PIN> {
+                    #[inline(always)]
+                    fn address(&self) -> u32 {
+                        let tim = unsafe { &*$TIMX::ptr() };
+                        tim.$ccr() as *const _ as u32
+                    }
+
+                    type MemSize = $typ;
+
+                    const REQUEST_LINE: Option<u8> = Some(DmaMuxResources::$mux as u8);
+                }
+            )+
+        )+
+    };
+}
+
+macro_rules! pwm_input {
+    ($($TIMX:ident: $typ:ty,)+) => {
+        $(
+            impl PwmInputExt for $TIMX {
+                fn pwm_input<PIN, COMP, T>(self, pin: PIN, frequency: T, rcc: &mut Rcc) -> PwmInput<Self, PIN>
+                where
+                    PIN: Pins<Self, C1, COMP>,
+                    T: Into<Hertz>,
+                {
+                    let clk = init::<$TIMX, _>(
+                        |psc| self.psc().write(|w| unsafe { w.psc().bits(psc); }),
+                        frequency.into(),
+                        rcc,
+                    );
+
+                    self.arr().write(|w| unsafe { w.bits(<$typ>::MAX as u32); });
+                    self.egr().write(|w| { w.ug().set_bit(); });
+
+                    // IC1 captures the rising edges of TI1, IC2 the falling edges of TI1
+                    self.ccmr1_input().write(|w| unsafe { w.cc1s().bits(0b01).cc2s().bits(0b10); });
+                    self.ccer().write(|w| { w
+                        .cc1p().clear_bit().cc1np().clear_bit()
+                        .cc2p().set_bit().cc2np().clear_bit()
+                        .cc1e().set_bit().cc2e().set_bit();
+                    });
+
+                    // The rising edges of TI1FP1 reset the counter
+                    self.smcr().write(|w| unsafe { w.ts().bits(0b101).sms().bits(0b100); });
+
+                    self.cr1().write(|w| { w.cen().set_bit(); });
+
+                    PwmInput { tim: self, clk, pin }
+                }
+            }
+
+            impl<PIN> PwmInput<$TIMX, PIN> {
+                /// Reads the `(period, high time)` of the last PWM period, in counter ticks
+                #[inline]
+                pub fn read(&mut self) -> nb::Result<($typ, $typ), Error> {
+                    let sr = self.tim.sr().read();
+                    if sr.cc1of().bit_is_set() || sr.cc2of().bit_is_set() {
+                        self.tim.sr().modify(|_, w| { w.cc1of().clear_bit().cc2of().clear_bit(); });
+                        self.tim.ccr1().read();
+                        self.tim.ccr2().read();
+                        Err(nb::Error::Other(Error::Overcapture))
+                    } else if sr.cc1if().bit_is_set() {
+                        let period = self.tim.ccr1().read().bits() as $typ;
+                        let high = self.tim.ccr2().read().bits() as $typ;
+                        Ok((period.wrapping_add(1), high.wrapping_add(1)))
+                    } else {
+                        Err(nb::Error::WouldBlock)
+                    }
+                }
+
+                /// Reads the frequency of the last PWM period
+                #[inline]
+                pub fn read_frequency(&mut self) -> nb::Result<Hertz, Error> {
+                    let (period, _) = self.read()?;
+                    Ok(Hertz::from_raw(self.clk.raw() / period.max(1) as u32))
+                }
+
+                /// Returns the frequency of the counter
+                #[inline]
+                pub fn frequency(&self) -> Hertz {
+                    self.clk
+                }
+
+                /// Stops the measurement and releases the timer and the pin
+                pub fn release(self) -> ($TIMX, PIN) {
+                    self.tim.cr1().modify(|_, w| { w.cen().clear_bit(); });
+                    (self.tim, self.pin)
+                }
+            }
+        )+
+    };
+}
+
+capture! {
+    TIM1: (u16, [
+        (C1, ccmr1_input, cc1s, ic1psc, ic1f, cc1e, cc1p, cc1np, ccr1, cc1if, cc1of, cc1ie, cc1de, TIM1_CH1),
+        (C2, ccmr1_input, cc2s, ic2psc, ic2f, cc2e, cc2p, cc2np, ccr2, cc2if, cc2of, cc2ie, cc2de, TIM1_CH2),
+        (C3, ccmr2_input, cc3s, ic3psc, ic3f, cc3e, cc3p, cc3np, ccr3, cc3if, cc3of, cc3ie, cc3de, TIM1_CH3),
+        (C4, ccmr2_input, cc4s, ic4psc, ic4f, cc4e, cc4p, cc4np, ccr4, cc4if, cc4of, cc4ie, cc4de, TIM1_CH4),
+    ]),
+    TIM2: (u32, [
+        (C1, ccmr1_input, cc1s, ic1psc, ic1f, cc1e, cc1p, cc1np, ccr1, cc1if, cc1of, cc1ie, cc1de, TIM2_CH1),
+        (C2, ccmr1_input, cc2s, ic2psc, ic2f, cc2e, cc2p, cc2np, ccr2, cc2if, cc2of, cc2ie, cc2de, TIM2_CH2),
+        (C3, ccmr2_input, cc3s, ic3psc, ic3f, cc3e, cc3p, cc3np, ccr3, cc3if, cc3of, cc3ie, cc3de, TIM2_CH3),
+        (C4, ccmr2_input, cc4s, ic4psc, ic4f, cc4e, cc4p, cc4np, ccr4, cc4if, cc4of, cc4ie, cc4de, TIM2_CH4),
+    ]),
+    TIM3: (u16, [
+        (C1, ccmr1_input, cc1s, ic1psc, ic1f, cc1e, cc1p, cc1np, ccr1, cc1if, cc1of, cc1ie, cc1de, TIM3_CH1),
+        (C2, ccmr1_input, cc2s, ic2psc, ic2f, cc2e, cc2p, cc2np, ccr2, cc2if, cc2of, cc2ie, cc2de, TIM3_CH2),
+        (C3, ccmr2_input, cc3s, ic3psc, ic3f, cc3e, cc3p, cc3np, ccr3, cc3if, cc3of, cc3ie, cc3de, TIM3_CH3),
+        (C4, ccmr2_input, cc4s, ic4psc, ic4f, cc4e, cc4p, cc4np, ccr4, cc4if, cc4of, cc4ie, cc4de, TIM3_CH4),
+    ]),
+    TIM4: (u16, [
+        (C1, ccmr1_input, cc1s, ic1psc, ic1f, cc1e, cc1p, cc1np, ccr1, cc1if, cc1of, cc1ie, cc1de, TIM4_CH1),
+        (C2, ccmr1_input, cc2s, ic2psc, ic2f, cc2e, cc2p, cc2np, ccr2, cc2if, cc2of, cc2ie, cc2de, TIM4_CH2),
+        (C3, ccmr2_input, cc3s, ic3psc, ic3f, cc3e, cc3p, cc3np, ccr3, cc3if, cc3of, cc3ie, cc3de, TIM4_CH3),
+        (C4, ccmr2_input, cc4s, ic4psc, ic4f, cc4e, cc4p, cc4np, ccr4, cc4if, cc4of, cc4ie, cc4de, TIM4_CH4),
+    ]),
+    TIM8: (u16, [
+        (C1, ccmr1_input, cc1s, ic1psc, ic1f, cc1e, cc1p, cc1np, ccr1, cc1if, cc1of, cc1ie, cc1de, TIM8_CH1),
+        (C2, ccmr1_input, cc2s, ic2psc, ic2f, cc2e, cc2p, cc2np, ccr2, cc2if, cc2of, cc2ie, cc2de, TIM8_CH2),
+        (C3, ccmr2_input, cc3s, ic3psc, ic3f, cc3e, cc3p, cc3np, ccr3, cc3if, cc3of, cc3ie, cc3de, TIM8_CH3),
+        (C4, ccmr2_input, cc4s, ic4psc, ic4f, cc4e, cc4p, cc4np, ccr4, cc4if, cc4of, cc4ie, cc4de, TIM8_CH4),
+    ]),
+    TIM15: (u16, [
+        (C1, ccmr1_input, cc1s, ic1psc, ic1f, cc1e, cc1p, cc1np, ccr1, cc1if, cc1of, cc1ie, cc1de, TIM15_CH1),
+        (C2, ccmr1_input, cc2s, ic2psc, ic2f, cc2e, cc2p, cc2np, ccr2, cc2if, cc2of, cc2ie, cc2de, TIM15_CH2),
+    ]),
+    TIM16: (u16, [
+        (C1, ccmr1_input, cc1s, ic1psc, ic1f, cc1e, cc1p, cc1np, ccr1, cc1if, cc1of, cc1ie, cc1de, TIM16_CH1),
+    ]),
+    TIM17: (u16, [
+        (C1, ccmr1_input, cc1s, ic1psc, ic1f, cc1e, cc1p, cc1np, ccr1, cc1if, cc1of, cc1ie, cc1de, TIM17_CH1),
+    ]),
+}
+
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+capture! {
+    TIM5: (u32, [
+        (C1, ccmr1_input, cc1s, ic1psc, ic1f, cc1e, cc1p, cc1np, ccr1, cc1if, cc1of, cc1ie, cc1de, TIM5_CH1),
+        (C2, ccmr1_input, cc2s, ic2psc, ic2f, cc2e, cc2p, cc2np, ccr2, cc2if, cc2of, cc2ie, cc2de, TIM5_CH2),
+        (C3, ccmr2_input, cc3s, ic3psc, ic3f, cc3e, cc3p, cc3np, ccr3, cc3if, cc3of, cc3ie, cc3de, TIM5_CH3),
+        (C4, ccmr2_input, cc4s, ic4psc, ic4f, cc4e, cc4p, cc4np, ccr4, cc4if, cc4of, cc4ie, cc4de, TIM5_CH4),
+    ]),
+}
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+capture! {
+    TIM20: (u16, [
+        (C1, ccmr1_input, cc1s, ic1psc, ic1f, cc1e, cc1p, cc1np, ccr1, cc1if, cc1of, cc1ie, cc1de, TIM20_CH1),
+        (C2, ccmr1_input, cc2s, ic2psc, ic2f, cc2e, cc2p, cc2np, ccr2, cc2if, cc2of, cc2ie, cc2de, TIM20_CH2),
+        (C3, ccmr2_input, cc3s, ic3psc, ic3f, cc3e, cc3p, cc3np, ccr3, cc3if, cc3of, cc3ie, cc3de, TIM20_CH3),
+        (C4, ccmr2_input, cc4s, ic4psc, ic4f, cc4e, cc4p, cc4np, ccr4, cc4if, cc4of, cc4ie, cc4de, TIM20_CH4),
+    ]),
+}
+
+pwm_input! {
+    TIM1: u16,
+    TIM2: u32,
+    TIM3: u16,
+    TIM4: u16,
+    TIM8: u16,
+    TIM15: u16,
+}
+
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+pwm_input! {
+    TIM5: u32,
+}
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+pwm_input! {
+    TIM20: u16,
+}
@@ -0,0 +1,581 @@
+//! Cyclic Redundancy Check
+//!
+//! The CRC unit computes checksums over 7, 8, 16 or 32-bit polynomials, with a programmable
+//! initial value and optional bit reversal of the input and output data. The data is written
+//! as bytes, half-words or words, and longer buffers can be fed by a memory-to-memory DMA
+//! transfer.
+//!
+//! The hardware has no final XOR, it is applied when reading the result.
+//!
+//! ```ignore
+//! let mut crc = dp.CRC.constrain(&mut rcc);
+//! crc.configure(&Config::crc32_ethernet());
+//! crc.feed_bytes(b"123456789");
+//! assert_eq!(crc.result(), 0xCBF4_3926);
+//! ```
+use core::marker::PhantomData;
+use core::ptr;
+
+use embedded_dma::StaticWriteBuffer;
+
+use crate::dma::{MemoryToMemory, TargetAddress};
+use crate::rcc::{Enable, Rcc, Reset};
+use crate::stm32::CRC;
+
+/// Generator polynomial, the leading coefficient is implicit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polynomial {
+    /// 7-bit polynomial
+    L7(u8),
+    /// 8-bit polynomial
+    L8(u8),
+    /// 16-bit polynomial
+    L16(u16),
+    /// 32-bit polynomial
+    L32(u32),
+}
+
+impl Polynomial {
+    /// Width of the CRC in bits
+    pub fn width(&self) -> u32 {
+        match self {
+            Polynomial::L7(_) => 7,
+            Polynomial::L8(_) => 8,
+            Polynomial::L16(_) => 16,
+            Polynomial::L32(_) => 32,
+        }
+    }
+
+    /// Coefficients of the polynomial
+    pub fn value(&self) -> u32 {
+        match *self {
+            Polynomial::L7(pol) => (pol & 0x7F) as u32,
+            Polynomial::L8(pol) => pol as u32,
+            Polynomial::L16(pol) => pol as u32,
+            Polynomial::L32(pol) => pol,
+        }
+    }
+
+    fn mask(&self) -> u32 {
+        u32::MAX >> (32 - self.width())
+    }
+
+    fn polysize(&self) -> u8 {
+        match self {
+            Polynomial::L32(_) => 0b00,
+            Polynomial::L16(_) => 0b01,
+            Polynomial::L8(_) => 0b10,
+            Polynomial::L7(_) => 0b11,
+        }
+    }
+}
+
+/// Bit reversal of the input data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitReversal {
+    /// The bits of each byte are reversed
+    ByByte = 0b01,
+    /// The bits of each half-word are reversed
+    ByHalfWord = 0b10,
+    /// The bits of each word are reversed
+    ByWord = 0b11,
+}
+
+/// CRC configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    polynomial: Polynomial,
+    initial_value: u32,
+    input_reversal: Option<BitReversal>,
+    output_reversal: bool,
+    output_xor: u32,
+}
+
+impl Config {
+    /// Non-reflected CRC over `polynomial`, starting from all ones
+    pub fn new(polynomial: Polynomial) -> Self {
+        Config {
+            polynomial,
+            initial_value: u32::MAX,
+            input_reversal: None,
+            output_reversal: false,
+            output_xor: 0,
+        }
+    }
+
+    /// CRC-32 used by Ethernet, zlib and PNG, the input is fed as bytes
+    pub fn crc32_ethernet() -> Self {
+        Config::new(Polynomial::L32(0x04C1_1DB7))
+            .input_reversal(Some(BitReversal::ByByte))
+            .output_reversal(true)
+            .output_xor(0xFFFF_FFFF)
+    }
+
+    /// CRC-16/CCITT-FALSE
+    pub fn crc16_ccitt() -> Self {
+        Config::new(Polynomial::L16(0x1021)).initial_value(0xFFFF)
+    }
+
+    /// CRC-8 with the `x^8 + x^2 + x + 1` polynomial, as used by SMBus
+    pub fn crc8() -> Self {
+        Config::new(Polynomial::L8(0x07)).initial_value(0)
+    }
+
+    /// Sets the value the CRC starts from, truncated to the polynomial width
+    pub fn initial_value(mut self, initial_value: u32) -> Self {
+        self.initial_value = initial_value;
+        self
+    }
+
+    /// Sets the bit reversal of the input data
+    pub fn input_reversal(mut self, reversal: Option<BitReversal>) -> Self {
+        self.input_reversal = reversal;
+        self
+    }
+
+    /// Reverses the bits of the result
+    pub fn output_reversal(mut self, reverse: bool) -> Self {
+        self.output_reversal = reverse;
+        self
+    }
+
+    /// Sets the value the result is XORed with
+    pub fn output_xor(mut self, output_xor: u32) -> Self {
+        self.output_xor = output_xor;
+        self
+    }
+
+    /// Polynomial of the configuration
+    pub fn polynomial(&self) -> Polynomial {
+        self.polynomial
+    }
+
+    fn finalize(&self, dr: u32) -> u32 {
+        (dr ^ self.output_xor) & self.polynomial.mask()
+    }
+
+    fn registers(&self) -> Registers {
+        let rev_in = self.input_reversal.map_or(0, |rev| rev as u32);
+        Registers {
+            pol: self.polynomial.value(),
+            init: self.initial_value & self.polynomial.mask(),
+            cr: (self.polynomial.polysize() as u32) << 3
+                | rev_in << 5
+                | (self.output_reversal as u32) << 7,
+        }
+    }
+}
+
+/// POL, INIT and CR values applying a configuration, RESET is also set when CR is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Registers {
+    pol: u32,
+    init: u32,
+    /// POLYSIZE, REV_IN and REV_OUT
+    cr: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::crc32_ethernet()
+    }
+}
+
+/// Extension trait to constrain the CRC peripheral
+pub trait CrcExt {
+    /// Enables the CRC unit, configured for [`Config::crc32_ethernet`]
+    fn constrain(self, rcc: &mut Rcc) -> Crc;
+}
+
+impl CrcExt for CRC {
+    fn constrain(self, rcc: &mut Rcc) -> Crc {
+        CRC::enable(&rcc.rb);
+        CRC::reset(&rcc.rb);
+
+        let mut crc = Crc {
+            crc: self,
+            config: Config::default(),
+        };
+        crc.configure(&Config::default());
+        crc
+    }
+}
+
+/// CRC unit
+pub struct Crc {
+    crc: CRC,
+    config: Config,
+}
+
+impl Crc {
+    /// Applies `config` and resets the CRC to its initial value
+    pub fn configure(&mut self, config: &Config) {
+        self.config = *config;
+        let registers = config.registers();
+        self.crc.pol().write(|w| unsafe {
+            w.bits(registers.pol);
+        });
+        self.crc.init().write(|w| unsafe {
+            w.bits(registers.init);
+        });
+        self.crc.cr().write(|w| unsafe {
+            w.bits(registers.cr).reset().set_bit();
+        });
+    }
+
+    /// Returns the current configuration
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Resets the CRC to its initial value
+    #[inline]
+    pub fn reset(&mut self) {
+        self.crc.cr().modify(|_, w| {
+            w.reset().set_bit();
+        });
+    }
+
+    /// Feeds a byte
+    #[inline]
+    pub fn feed_u8(&mut self, data: u8) {
+        unsafe { ptr::write_volatile(self.crc.dr() as *const _ as *mut u8, data) }
+    }
+
+    /// Feeds a half-word, most significant bit first unless the input is reversed
+    #[inline]
+    pub fn feed_u16(&mut self, data: u16) {
+        unsafe { ptr::write_volatile(self.crc.dr() as *const _ as *mut u16, data) }
+    }
+
+    /// Feeds a word, most significant bit first unless the input is reversed
+    #[inline]
+    pub fn feed_u32(&mut self, data: u32) {
+        self.crc.dr().write(|w| unsafe {
+            w.bits(data);
+        });
+    }
+
+    /// Feeds bytes
+    pub fn feed_bytes(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.feed_u8(byte);
+        }
+    }
+
+    /// Feeds half-words, a reflected CRC over little-endian data needs
+    /// [`BitReversal::ByHalfWord`] to give the same result as [`feed_bytes`](Self::feed_bytes)
+    pub fn feed_half_words(&mut self, data: &[u16]) {
+        for &half_word in data {
+            self.feed_u16(half_word);
+        }
+    }
+
+    /// Feeds words, a reflected CRC over little-endian data needs [`BitReversal::ByWord`] to
+    /// give the same result as [`feed_bytes`](Self::feed_bytes)
+    pub fn feed_words(&mut self, data: &[u32]) {
+        for &word in data {
+            self.feed_u32(word);
+        }
+    }
+
+    /// Returns the CRC of the data fed since the last reset, with the output XOR applied
+    #[inline]
+    pub fn result(&self) -> u32 {
+        self.config.finalize(self.crc.dr().read().bits())
+    }
+
+    /// Returns the CRC of the data fed since the last reset and resets it
+    pub fn result_and_reset(&mut self) -> u32 {
+        let result = self.result();
+        self.reset();
+        result
+    }
+
+    /// Prepares feeding `data` with a memory-to-memory DMA transfer.
+    ///
+    /// The returned source and destination are passed to
+    /// `into_memory_to_memory_transfer`, with `peripheral_increment(true)` and
+    /// `memory_increment(false)` in the DMA configuration since the data is read from the
+    /// source buffer and written to the CRC data register. The CRC is given back by
+    /// [`DmaInput::release`] once the transfer is freed.
+    pub fn into_dma_input<W>(self, data: &'static [W]) -> (DmaSource<W>, DmaInput<W>)
+    where
+        W: Into<u32>,
+    {
+        let len = data.len();
+        (
+            DmaSource { data },
+            DmaInput {
+                crc: self,
+                len,
+                _word: PhantomData,
+            },
+        )
+    }
+
+    /// Releases the CRC peripheral
+    pub fn release(self) -> CRC {
+        self.crc
+    }
+}
+
+/// Source buffer of a DMA transfer feeding the CRC
+pub struct DmaSource<W: 'static> {
+    data: &'static [W],
+}
+
+impl<W> DmaSource<W> {
+    /// Releases the source buffer
+    pub fn release(self) -> &'static [W] {
+        self.data
+    }
+}
+
+unsafe impl<W> TargetAddress<MemoryToMemory<W>> for DmaSource<W>
+where
+    W: Into<u32>,
+{
+    type MemSize = W;
+
+    fn address(&self) -> u32 {
+        self.data.as_ptr() as u32
+    }
+}
+
+/// CRC data register as the destination of a DMA transfer
+pub struct DmaInput<W> {
+    crc: Crc,
+    len: usize,
+    _word: PhantomData<W>,
+}
+
+impl<W> DmaInput<W> {
+    /// Releases the CRC, the result can then be read
+    pub fn release(self) -> Crc {
+        self.crc
+    }
+}
+
+unsafe impl<W> StaticWriteBuffer for DmaInput<W> {
+    type Word = W;
+
+    unsafe fn write_buffer(&mut self) -> (*mut W, usize) {
+        (self.crc.crc.dr() as *const _ as *mut W, self.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECK: &[u8] = b"123456789";
+
+    /// Bit-serial model of the CRC unit programmed with the register values of the driver,
+    /// the data goes through the input reversal and is then shifted in most significant bit
+    /// first
+    struct Model {
+        config: Config,
+        registers: Registers,
+        width: u32,
+        crc: u32,
+    }
+
+    impl Model {
+        fn new(config: Config) -> Self {
+            let registers = config.registers();
+            let width = match (registers.cr >> 3) & 0b11 {
+                0b00 => 32,
+                0b01 => 16,
+                0b10 => 8,
+                _ => 7,
+            };
+            Model {
+                config,
+                registers,
+                width,
+                crc: registers.init,
+            }
+        }
+
+        fn feed(&mut self, data: u32, bits: u32) {
+            let chunk = match (self.registers.cr >> 5) & 0b11 {
+                0b00 => 0,
+                0b01 => 8,
+                0b10 => 16,
+                _ => 32,
+            }
+            .min(bits);
+            let data = if chunk == 0 {
+                data
+            } else {
+                (0..bits / chunk).fold(0, |acc, i| {
+                    let part = (data >> (i * chunk)) & (u32::MAX >> (32 - chunk));
+                    acc | (part.reverse_bits() >> (32 - chunk)) << (i * chunk)
+                })
+            };
+
+            let mask = u32::MAX >> (32 - self.width);
+            for i in (0..bits).rev() {
+                let top = (self.crc >> (self.width - 1)) & 1;
+                self.crc = (self.crc << 1) & mask;
+                if top ^ ((data >> i) & 1) == 1 {
+                    self.crc ^= self.registers.pol;
+                }
+            }
+        }
+
+        /// Value read back from the data register
+        fn dr(&self) -> u32 {
+            if self.registers.cr & (1 << 7) != 0 {
+                self.crc.reverse_bits() >> (32 - self.width)
+            } else {
+                self.crc
+            }
+        }
+
+        fn result(&self) -> u32 {
+            self.config.finalize(self.dr())
+        }
+    }
+
+    fn model_bytes(config: Config, data: &[u8]) -> u32 {
+        let mut model = Model::new(config);
+        for &byte in data {
+            model.feed(byte as u32, 8);
+        }
+        model.result()
+    }
+
+    #[test]
+    fn preset_registers() {
+        assert_eq!(
+            Config::crc32_ethernet().registers(),
+            Registers {
+                pol: 0x04C1_1DB7,
+                init: 0xFFFF_FFFF,
+                // 32-bit, reversed by byte, reversed output
+                cr: 0b1_01_00_000,
+            }
+        );
+        assert_eq!(
+            Config::crc16_ccitt().registers(),
+            Registers {
+                pol: 0x1021,
+                init: 0xFFFF,
+                cr: 0b0_00_01_000,
+            }
+        );
+        assert_eq!(
+            Config::crc8().registers(),
+            Registers {
+                pol: 0x07,
+                init: 0,
+                cr: 0b0_00_10_000,
+            }
+        );
+    }
+
+    #[test]
+    fn registers_are_truncated_to_width() {
+        let config = Config::new(Polynomial::L7(0x89))
+            .input_reversal(Some(BitReversal::ByWord))
+            .output_reversal(true);
+        assert_eq!(
+            config.registers(),
+            Registers {
+                pol: 0x09,
+                init: 0x7F,
+                cr: 0b1_11_11_000,
+            }
+        );
+        let config = Config::new(Polynomial::L16(0x8005))
+            .initial_value(0x1234_5678)
+            .input_reversal(Some(BitReversal::ByHalfWord));
+        assert_eq!(
+            config.registers(),
+            Registers {
+                pol: 0x8005,
+                init: 0x5678,
+                cr: 0b0_10_01_000,
+            }
+        );
+    }
+
+    /// Check values of the CRC catalogue, for the data "123456789"
+    #[test]
+    fn preset_check_values() {
+        // CRC-32/ISO-HDLC
+        assert_eq!(model_bytes(Config::crc32_ethernet(), CHECK), 0xCBF4_3926);
+        // CRC-16/IBM-3740, formerly CRC-16/CCITT-FALSE
+        assert_eq!(model_bytes(Config::crc16_ccitt(), CHECK), 0x29B1);
+        // CRC-8/SMBUS
+        assert_eq!(model_bytes(Config::crc8(), CHECK), 0xF4);
+    }
+
+    #[test]
+    fn custom_check_values() {
+        // CRC-16/ARC
+        let arc = Config::new(Polynomial::L16(0x8005))
+            .initial_value(0)
+            .input_reversal(Some(BitReversal::ByByte))
+            .output_reversal(true);
+        assert_eq!(model_bytes(arc, CHECK), 0xBB3D);
+
+        // CRC-7/MMC
+        let mmc = Config::new(Polynomial::L7(0x09)).initial_value(0);
+        assert_eq!(model_bytes(mmc, CHECK), 0x75);
+
+        // CRC-8/MAXIM-DOW
+        let maxim = Config::new(Polynomial::L8(0x31))
+            .initial_value(0)
+            .input_reversal(Some(BitReversal::ByByte))
+            .output_reversal(true);
+        assert_eq!(model_bytes(maxim, CHECK), 0xA1);
+
+        // CRC-32/JAMCRC, the output XOR is applied by the driver
+        let jamcrc = Config::crc32_ethernet().output_xor(0);
+        assert_eq!(model_bytes(jamcrc, CHECK), 0x340B_C6D9);
+    }
+
+    #[test]
+    fn word_feeding_matches_byte_feeding() {
+        let data = b"12345678";
+
+        let bytes = model_bytes(Config::crc32_ethernet(), data);
+
+        let config = Config::crc32_ethernet().input_reversal(Some(BitReversal::ByWord));
+        let mut words = Model::new(config);
+        for chunk in data.chunks(4) {
+            words.feed(
+                u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]),
+                32,
+            );
+        }
+        assert_eq!(words.result(), bytes);
+
+        let config = Config::crc32_ethernet().input_reversal(Some(BitReversal::ByHalfWord));
+        let mut half_words = Model::new(config);
+        for chunk in data.chunks(2) {
+            half_words.feed(u16::from_le_bytes([chunk[0], chunk[1]]) as u32, 16);
+        }
+        assert_eq!(half_words.result(), bytes);
+
+        // Without reversal, words are shifted in most significant byte first
+        let config = Config::crc16_ccitt();
+        let mut words = Model::new(config);
+        for chunk in data.chunks(4) {
+            words.feed(
+                u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]),
+                32,
+            );
+        }
+        assert_eq!(words.result(), model_bytes(config, data));
+    }
+
+    #[test]
+    fn result_is_truncated_to_width() {
+        let config = Config::crc8().output_xor(0xFFFF_FFFF);
+        assert_eq!(config.finalize(0x1234_5600), 0xFF);
+    }
+}
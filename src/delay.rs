@@ -152,3 +152,126 @@ impl_delay_from_count_down_timer! {
     (DelayMs, delay_ms, 1_000),
     (DelayUs, delay_us, 1)
 }
+
+impl<T> embedded_hal_1::delay::DelayNs for DelayFromCountDownTimer<T>
+where
+    T: CountDown<Time = MicroSecond>,
+{
+    fn delay_ns(&mut self, ns: u32) {
+        // The timer resolution is 1us, round up so we never wait less than asked for
+        DelayUs::<u32>::delay_us(self, ns.div_ceil(1_000));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        DelayUs::<u32>::delay_us(self, us);
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        DelayMs::<u32>::delay_ms(self, ms);
+    }
+}
+
+#[cfg(feature = "async")]
+static SYST_WRAPPED: core::sync::atomic::AtomicBool = core::sync::atomic::AtomicBool::new(false);
+#[cfg(feature = "async")]
+static SYST_WAKER: crate::asynch::AtomicWaker = crate::asynch::AtomicWaker::new();
+
+/// Async delay provider using `SYST`
+///
+/// The `SysTick` exception has to be forwarded to
+/// [`OnInterrupt::on_interrupt`](crate::asynch::OnInterrupt::on_interrupt).
+#[cfg(feature = "async")]
+pub struct AsyncDelay {
+    syst: SYST,
+    ahb_clk: u32,
+}
+
+#[cfg(feature = "async")]
+impl AsyncDelay {
+    /// Creates an async delay provider from `SYST`
+    pub fn new(mut syst: SYST, clocks: &Clocks) -> Self {
+        syst.disable_counter();
+        syst.disable_interrupt();
+        syst.set_clock_source(cortex_m::peripheral::syst::SystClkSource::Core);
+        AsyncDelay {
+            syst,
+            ahb_clk: clocks.ahb_clk.raw(),
+        }
+    }
+
+    /// Releases `SYST`
+    pub fn free(mut self) -> SYST {
+        self.syst.disable_counter();
+        self.syst.disable_interrupt();
+        self.syst
+    }
+
+    async fn delay_ticks(&mut self, mut ticks: u64) {
+        use core::sync::atomic::Ordering;
+
+        // SYST is a 24 bit down counter, longer delays are split into several wraps
+        const MAX_TICKS: u64 = 1 << 24;
+
+        while ticks > 0 {
+            // The reload value has to be at least 1
+            let chunk = ticks.clamp(2, MAX_TICKS);
+            ticks = ticks.saturating_sub(chunk);
+
+            self.syst.set_reload((chunk - 1) as u32);
+            self.syst.clear_current();
+            SYST_WRAPPED.store(false, Ordering::Relaxed);
+            // Reading the control register clears a stale COUNTFLAG
+            let _ = self.syst.has_wrapped();
+            self.syst.enable_counter();
+
+            core::future::poll_fn(|cx| {
+                SYST_WAKER.register(cx.waker());
+                if SYST_WRAPPED.swap(false, Ordering::Relaxed) || self.syst.has_wrapped() {
+                    core::task::Poll::Ready(())
+                } else {
+                    self.syst.enable_interrupt();
+                    core::task::Poll::Pending
+                }
+            })
+            .await;
+
+            self.syst.disable_counter();
+            self.syst.disable_interrupt();
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::asynch::OnInterrupt for SYST {
+    fn on_interrupt() {
+        const COUNTFLAG: u32 = 1 << 16;
+        const TICKINT: u32 = 1 << 1;
+
+        // NOTE(unsafe) reading CSR clears COUNTFLAG, which is handed over to the task
+        let syst = unsafe { &*SYST::PTR };
+        let csr = syst.csr.read();
+        if csr & COUNTFLAG != 0 {
+            SYST_WRAPPED.store(true, core::sync::atomic::Ordering::Relaxed);
+        }
+        unsafe { syst.csr.write(csr & !TICKINT) };
+        SYST_WAKER.wake();
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for AsyncDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        let ticks = (ns as u64 * self.ahb_clk as u64).div_ceil(1_000_000_000);
+        self.delay_ticks(ticks).await
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        let ticks = us as u64 * self.ahb_clk as u64 / 1_000_000;
+        self.delay_ticks(ticks).await
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        let ticks = ms as u64 * self.ahb_clk as u64 / 1_000;
+        self.delay_ticks(ticks).await
+    }
+}
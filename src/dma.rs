@@ -35,6 +35,8 @@ pub enum DMAError {
     SmallBuffer,
     /// DMA started transfer on the inactive buffer while the user was processing it.
     Overflow,
+    /// The DMA stream signaled a transfer error.
+    TransferError,
 }
 
 /// Possible DMA's directions.
@@ -196,6 +196,7 @@ macro_rules! dma_stream {
             impl<I: Instance> Stream for $name<I> {
 
                 const NUMBER: usize = $number;
+                const IS_DMA1: bool = I::IS_DMA1;
                 type Config = DmaConfig;
                 type Interrupts = DmaInterrupts;
 
@@ -493,6 +494,22 @@ macro_rules! dma_stream {
                 }
             }
 
+            #[cfg(feature = "async")]
+            impl<I: Instance> crate::asynch::OnInterrupt for $name<I> {
+                fn on_interrupt() {
+                    //NOTE(unsafe) We only access the registers that belongs to the StreamX
+                    let dma = unsafe { &*I::ptr() };
+                    let isr = dma.isr().read();
+                    if isr.tcif($number).bit_is_set() || isr.teif($number).bit_is_set() {
+                        // The flags are left set for the waiting task
+                        dma.ch($number).cr().modify(|_, w| {
+                            w.tcie().clear_bit().teie().clear_bit();
+                        });
+                        asynch::waker::<Self>().wake();
+                    }
+                }
+            }
+
             impl<I: Instance> $name<I> {
                 #[inline(always)]
                 pub fn clear_half_transfer_interrupt(&mut self) {
@@ -524,6 +541,50 @@ macro_rules! dma_stream {
     };
 }
 
+#[cfg(feature = "async")]
+pub(crate) mod asynch {
+    use core::future::poll_fn;
+    use core::task::Poll;
+
+    use crate::asynch::AtomicWaker;
+    use crate::dma::{traits::Stream, DMAError};
+
+    const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+    static DMA1_WAKERS: [AtomicWaker; 8] = [NEW_WAKER; 8];
+    static DMA2_WAKERS: [AtomicWaker; 8] = [NEW_WAKER; 8];
+
+    pub(crate) fn waker<S: Stream>() -> &'static AtomicWaker {
+        if S::IS_DMA1 {
+            &DMA1_WAKERS[S::NUMBER]
+        } else {
+            &DMA2_WAKERS[S::NUMBER]
+        }
+    }
+
+    /// Waits for the transfer complete or transfer error flag of `stream`
+    ///
+    /// The transfer complete and error interrupts are enabled while waiting, the interrupt
+    /// of the stream has to be forwarded to
+    /// [`OnInterrupt::on_interrupt`](crate::asynch::OnInterrupt::on_interrupt).
+    pub(crate) async fn wait_for_transfer<S: Stream>(stream: &mut S) -> Result<(), DMAError> {
+        poll_fn(|cx| {
+            waker::<S>().register(cx.waker());
+            if S::get_transfer_error_flag() {
+                stream.clear_transfer_error_interrupt();
+                Poll::Ready(Err(DMAError::TransferError))
+            } else if S::get_transfer_complete_flag() {
+                stream.clear_transfer_complete_interrupt();
+                Poll::Ready(Ok(()))
+            } else {
+                stream.set_transfer_complete_interrupt_enable(true);
+                stream.set_transfer_error_interrupt_enable(true);
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
 // Cat 3 and 4 devices
 #[cfg(any(
     feature = "stm32g471",
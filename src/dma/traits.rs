@@ -20,6 +20,9 @@ pub trait Stream: Sealed {
     /// Number of the stream register
     const NUMBER: usize;
 
+    /// Whether the stream belongs to DMA1 or DMA2
+    const IS_DMA1: bool;
+
     /// Configuration structure for this stream.
     type Config;
 
@@ -195,3 +198,16 @@ pub unsafe trait TargetAddress<D: Direction> {
     /// An optional associated request line
     const REQUEST_LINE: Option<u8> = None;
 }
+
+/// A shared reference targets the same address, so that one target can be used
+/// by the transfers of both directions at once.
+unsafe impl<D: Direction, T: TargetAddress<D>> TargetAddress<D> for &T {
+    type MemSize = T::MemSize;
+
+    #[inline(always)]
+    fn address(&self) -> u32 {
+        (**self).address()
+    }
+
+    const REQUEST_LINE: Option<u8> = T::REQUEST_LINE;
+}
@@ -223,6 +223,21 @@ where
         fence(Ordering::SeqCst);
     }
 
+    /// Waits for the transfer to complete without blocking the CPU
+    ///
+    /// The interrupt of the stream has to be forwarded to
+    /// [`OnInterrupt::on_interrupt`](crate::asynch::OnInterrupt::on_interrupt).
+    #[cfg(feature = "async")]
+    pub async fn wait(&mut self) -> Result<(), crate::dma::DMAError> {
+        let result = crate::dma::stream::asynch::wait_for_transfer(&mut self.stream).await;
+
+        // Protect the instruction and bus sequence of the completed transfer and the
+        // subsequent buffer access.
+        fence(Ordering::SeqCst);
+
+        result
+    }
+
     /// Stops the stream and returns the underlying resources.
     pub fn free(mut self) -> (STREAM, PERIPHERAL, BUF) {
         self.stream.disable();
@@ -71,96 +71,311 @@ impl Event {
             _ => unreachable!(),
         }
     }
+
+    /// Returns `true` for the configurable lines, which have a trigger edge selection, a
+    /// pending bit and a software trigger. The other lines are direct lines, whose interrupt
+    /// is cleared in the peripheral raising it.
+    pub fn is_configurable(&self) -> bool {
+        matches!(*self as u8, 0..=17 | 19..=22 | 29..=33 | 40..=41)
+    }
+}
+
+/// Sets or clears the bit of `$line` in `$reg1` for lines 0 to 31, or in `$reg2` for lines 32
+/// and above
+macro_rules! modify_line {
+    ($exti:expr, $reg1:ident, $reg2:ident, $line:expr, $set:expr) => {
+        match $line {
+            line if line < 32 => {
+                $exti.$reg1().modify(|r, w| unsafe {
+                    w.bits(if $set {
+                        r.bits() | 1 << line
+                    } else {
+                        r.bits() & !(1 << line)
+                    });
+                });
+            }
+            line => {
+                $exti.$reg2().modify(|r, w| unsafe {
+                    w.bits(if $set {
+                        r.bits() | 1 << (line - 32)
+                    } else {
+                        r.bits() & !(1 << (line - 32))
+                    });
+                });
+            }
+        }
+    };
 }
 
 pub trait ExtiExt {
+    /// Unmasks the interrupt of the line, without changing its trigger edges
     fn wakeup(&self, ev: Event);
+    /// Selects the trigger edges of a configurable line and unmasks its interrupt
     fn listen(&self, ev: Event, edge: SignalEdge);
+    /// Masks the interrupt of the line and clears its trigger edges
     fn unlisten(&self, ev: Event);
+    /// Clears the pending bit of a configurable line
     fn unpend(&self, ev: Event);
+    /// Returns `true` if a configurable line is pending
     fn is_pending(&self, ev: Event) -> bool;
+    /// Unmasks the event of the line, which wakes the core up from `WFE`
+    fn wakeup_event(&self, ev: Event);
+    /// Selects the trigger edges of a configurable line and unmasks its event
+    fn listen_event(&self, ev: Event, edge: SignalEdge);
+    /// Masks the event of the line
+    fn unlisten_event(&self, ev: Event);
+    /// Triggers a configurable line from software, the line stays pending until unpended
+    fn trigger(&self, ev: Event);
 }
 
 impl ExtiExt for EXTI {
     fn listen(&self, ev: Event, edge: SignalEdge) {
+        set_edge(self, ev, edge);
+        self.wakeup(ev);
+    }
+
+    fn wakeup(&self, ev: Event) {
+        modify_line!(self, imr1, imr2, ev as u8, true);
+    }
+
+    fn unlisten(&self, ev: Event) {
+        self.unpend(ev);
         let line = ev as u8;
-        assert!(line <= 17);
-        let mask = 1 << line;
-        match edge {
-            SignalEdge::Rising => {
-                self.rtsr1().modify(|r, w| unsafe {
-                    w.bits(r.bits() | mask);
-                });
-            }
-            SignalEdge::Falling => {
-                self.ftsr1().modify(|r, w| unsafe {
-                    w.bits(r.bits() | mask);
+        modify_line!(self, imr1, imr2, line, false);
+        if ev.is_configurable() {
+            modify_line!(self, rtsr1, rtsr2, line, false);
+            modify_line!(self, ftsr1, ftsr2, line, false);
+        }
+    }
+
+    fn is_pending(&self, ev: Event) -> bool {
+        if !ev.is_configurable() {
+            return false;
+        }
+        match ev as u8 {
+            line if line < 32 => self.pr1().read().bits() & (1 << line) != 0,
+            line => self.pr2().read().bits() & (1 << (line - 32)) != 0,
+        }
+    }
+
+    fn unpend(&self, ev: Event) {
+        if !ev.is_configurable() {
+            return;
+        }
+        match ev as u8 {
+            line if line < 32 => {
+                self.pr1().write(|w| unsafe {
+                    w.bits(1 << line);
                 });
             }
-            SignalEdge::RisingFalling => {
-                self.rtsr1().modify(|r, w| unsafe {
-                    w.bits(r.bits() | mask);
-                });
-                self.ftsr1().modify(|r, w| unsafe {
-                    w.bits(r.bits() | mask);
+            line => {
+                self.pr2().write(|w| unsafe {
+                    w.bits(1 << (line - 32));
                 });
             }
         }
-        self.wakeup(ev);
     }
 
-    fn wakeup(&self, ev: Event) {
-        match ev as u8 {
-            line if line < 32 => self.imr1().modify(|r, w| unsafe {
-                w.bits(r.bits() | 1 << line);
-            }),
-            line => self.imr2().modify(|r, w| unsafe {
-                w.bits(r.bits() | 1 << (line - 32));
-            }),
-        }
+    fn wakeup_event(&self, ev: Event) {
+        modify_line!(self, emr1, emr2, ev as u8, true);
     }
 
-    fn unlisten(&self, ev: Event) {
-        self.unpend(ev);
+    fn listen_event(&self, ev: Event, edge: SignalEdge) {
+        set_edge(self, ev, edge);
+        self.wakeup_event(ev);
+    }
+
+    fn unlisten_event(&self, ev: Event) {
+        modify_line!(self, emr1, emr2, ev as u8, false);
+    }
+
+    fn trigger(&self, ev: Event) {
+        assert!(ev.is_configurable());
         match ev as u8 {
             line if line < 32 => {
-                let mask = !(1 << line);
-                self.imr1().modify(|r, w| unsafe {
-                    w.bits(r.bits() & mask);
+                self.swier1().write(|w| unsafe {
+                    w.bits(1 << line);
                 });
-                if line <= 18 {
-                    self.rtsr1().modify(|r, w| unsafe {
-                        w.bits(r.bits() & mask);
-                    });
-                    self.ftsr1().modify(|r, w| unsafe {
-                        w.bits(r.bits() & mask);
-                    });
-                }
             }
             line => {
-                let mask = !(1 << (line - 32));
-                self.imr2().modify(|r, w| unsafe {
-                    w.bits(r.bits() & mask);
-                })
+                self.swier2().write(|w| unsafe {
+                    w.bits(1 << (line - 32));
+                });
             }
         }
     }
+}
 
-    fn is_pending(&self, ev: Event) -> bool {
-        let line = ev as u8;
-        if line > 18 {
-            return false;
+/// Enables the trigger edges of a configurable line, the other edge is left enabled
+fn set_edge(exti: &EXTI, ev: Event, edge: SignalEdge) {
+    assert!(ev.is_configurable());
+    let line = ev as u8;
+    match edge {
+        SignalEdge::Rising => {
+            modify_line!(exti, rtsr1, rtsr2, line, true);
         }
+        SignalEdge::Falling => {
+            modify_line!(exti, ftsr1, ftsr2, line, true);
+        }
+        SignalEdge::RisingFalling => {
+            modify_line!(exti, rtsr1, rtsr2, line, true);
+            modify_line!(exti, ftsr1, ftsr2, line, true);
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub(crate) mod asynch {
+    use core::future::poll_fn;
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use core::task::Poll;
+
+    use crate::asynch::{AtomicWaker, OnInterrupt};
+    use crate::gpio::SignalEdge;
+    use crate::stm32::EXTI;
+
+    const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+    static WAKERS: [AtomicWaker; 16] = [NEW_WAKER; 16];
+    /// GPIO lines with a task waiting on them
+    static WAITING: AtomicU32 = AtomicU32::new(0);
+
+    /// Configures the trigger edge of GPIO line `line` and clears its pending bit
+    ///
+    /// The line is armed before the pin level is checked, so an edge in between is not lost.
+    pub(crate) fn arm(line: u8, edge: SignalEdge) {
+        let exti = unsafe { &*EXTI::ptr() };
         let mask = 1 << line;
-        self.pr1().read().bits() & mask != 0
+        let (rising, falling) = match edge {
+            SignalEdge::Rising => (true, false),
+            SignalEdge::Falling => (false, true),
+            SignalEdge::RisingFalling => (true, true),
+        };
+        cortex_m::interrupt::free(|_| {
+            exti.rtsr1().modify(|r, w| unsafe {
+                w.bits(if rising {
+                    r.bits() | mask
+                } else {
+                    r.bits() & !mask
+                });
+            });
+            exti.ftsr1().modify(|r, w| unsafe {
+                w.bits(if falling {
+                    r.bits() | mask
+                } else {
+                    r.bits() & !mask
+                });
+            });
+        });
+        exti.pr1().write(|w| unsafe {
+            w.bits(mask);
+        });
     }
 
-    fn unpend(&self, ev: Event) {
-        let line = ev as u8;
-        if line <= 18 {
-            self.pr1().modify(|_, w| unsafe {
-                w.bits(1 << line);
+    /// Waits for the edge `line` has been armed for
+    pub(crate) async fn wait(line: u8) {
+        let exti = unsafe { &*EXTI::ptr() };
+        let mask = 1 << line;
+        poll_fn(|cx| {
+            WAKERS[line as usize].register(cx.waker());
+            if exti.pr1().read().bits() & mask != 0 {
+                exti.pr1().write(|w| unsafe {
+                    w.bits(mask);
+                });
+                Poll::Ready(())
+            } else {
+                WAITING.fetch_or(mask, Ordering::AcqRel);
+                cortex_m::interrupt::free(|_| {
+                    exti.imr1().modify(|r, w| unsafe {
+                        w.bits(r.bits() | mask);
+                    });
+                });
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Handles GPIO lines 0 to 15, lines without a waiting task are left untouched
+    impl OnInterrupt for EXTI {
+        fn on_interrupt() {
+            let exti = unsafe { &*EXTI::ptr() };
+            let pending = exti.pr1().read().bits() & WAITING.load(Ordering::Acquire);
+            if pending == 0 {
+                return;
+            }
+
+            WAITING.fetch_and(!pending, Ordering::AcqRel);
+            // Mask the lines, the pending bits are consumed by the waiting tasks
+            exti.imr1().modify(|r, w| unsafe {
+                w.bits(r.bits() & !pending);
             });
+            for line in 0..16 {
+                if pending & (1 << line) != 0 {
+                    WAKERS[line].wake();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Event;
+
+    /// Line types of the EXTI line connections table of RM0440
+    #[test]
+    fn configurable_lines() {
+        let lines = [
+            (Event::GPIO0, true),
+            (Event::GPIO1, true),
+            (Event::GPIO2, true),
+            (Event::GPIO3, true),
+            (Event::GPIO4, true),
+            (Event::GPIO5, true),
+            (Event::GPIO6, true),
+            (Event::GPIO7, true),
+            (Event::GPIO8, true),
+            (Event::GPIO9, true),
+            (Event::GPIO10, true),
+            (Event::GPIO11, true),
+            (Event::GPIO12, true),
+            (Event::GPIO13, true),
+            (Event::GPIO14, true),
+            (Event::GPIO15, true),
+            (Event::PVD, true),
+            (Event::RTC_ALARM, true),
+            (Event::USB, false),
+            (Event::LCE, true),
+            (Event::RTC_WAKEUP, true),
+            (Event::COMP1, true),
+            (Event::COMP2, true),
+            (Event::I2C1, false),
+            (Event::I2C2, false),
+            (Event::USART1, false),
+            (Event::USART2, false),
+            (Event::I2C3, false),
+            (Event::USART3, false),
+            (Event::COMP3, true),
+            (Event::COMP4, true),
+            (Event::COMP5, true),
+            (Event::COMP6, true),
+            (Event::COMP7, true),
+            (Event::UART4, false),
+            (Event::UART5, false),
+            (Event::LPUART1, false),
+            (Event::LPTIM1, false),
+            (Event::PVM1, true),
+            (Event::PVM2, true),
+            (Event::I2C4, false),
+            (Event::UCPD1, false),
+        ];
+        for (event, configurable) in lines.iter() {
+            assert_eq!(
+                event.is_configurable(),
+                *configurable,
+                "line {}",
+                *event as u8
+            );
         }
     }
 }
@@ -105,7 +105,7 @@ impl ExtendedFilter {
 }
 
 /// Filter Type
-//#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+//#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[derive(Clone, Copy, Debug)]
 pub enum FilterType<ID, UNIT>
 where
@@ -151,7 +151,7 @@ where
 
 /// Filter Action
 #[derive(Clone, Copy, Debug)]
-#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Action {
     /// No Action
     Disable = 0b000,
@@ -184,7 +184,7 @@ impl From<Action> for super::message_ram::enums::FilterElementConfig {
 
 /// Filter
 #[derive(Clone, Copy, Debug)]
-//#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+//#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Filter<ID, UNIT>
 where
     ID: Copy + Clone + core::fmt::Debug,
@@ -198,7 +198,7 @@ where
 
 /// Standard Filter Slot
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum StandardFilterSlot {
     /// 0
     _0 = 0,
@@ -295,7 +295,7 @@ impl From<u8> for StandardFilterSlot {
 
 /// Extended Filter Slot
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ExtendedFilterSlot {
     /// 0
     _0 = 0,
@@ -332,7 +332,7 @@ impl From<u8> for ExtendedFilterSlot {
 
 /// Enum over both Standard and Extended Filter ID's
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FilterId {
     /// Standard Filter Slots
     Standard(StandardFilterSlot),
@@ -0,0 +1,158 @@
+use super::id::{Id, IdReg};
+
+use super::message_ram::enums::FrameFormat as PacFrameFormat;
+use super::message_ram::{RxFifoElementHeader, TxBufferElementHeader};
+
+use super::message_ram::enums::RemoteTransmissionRequest;
+use super::message_ram::enums::{DataLength, FilterFrameMatch};
+
+/// Type of Frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameFormat {
+    /// Frame used by Classic CAN
+    Standard = 0,
+    /// New frame format used by FdCan
+    Fdcan = 1,
+}
+impl From<FrameFormat> for PacFrameFormat {
+    fn from(ff: FrameFormat) -> Self {
+        match ff {
+            FrameFormat::Standard => PacFrameFormat::Standard,
+            FrameFormat::Fdcan => PacFrameFormat::Fdcan,
+        }
+    }
+}
+impl From<PacFrameFormat> for FrameFormat {
+    fn from(ff: PacFrameFormat) -> Self {
+        match ff {
+            PacFrameFormat::Standard => FrameFormat::Standard,
+            PacFrameFormat::Fdcan => FrameFormat::Fdcan,
+        }
+    }
+}
+
+/// Header of a transmit request
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TxFrameHeader {
+    /// Length of the data in bytes
+    ///
+    /// Classic CAN frames carry at most 8 bytes, FdCan frames at most 64. FdCan frames
+    /// with a length that is not directly encodable are padded up to the next valid length.
+    pub len: u8,
+    /// CAN or FDCAN Frame
+    pub frame_format: FrameFormat,
+    /// Message ID
+    pub id: Id,
+    /// Transmit the data phase of the frame at the data bit rate
+    ///
+    /// Only has an effect for FdCan frames, and if bit rate switching is allowed by
+    /// [`FrameTransmissionConfig::AllowFdCanAndBRS`](super::config::FrameTransmissionConfig).
+    pub bit_rate_switching: bool,
+    /// Store Tx events
+    pub marker: Option<u8>,
+}
+impl From<TxFrameHeader> for IdReg {
+    fn from(header: TxFrameHeader) -> IdReg {
+        let id: IdReg = header.id.into();
+        // Remote frames do not exist in the FdCan frame format
+        id.with_rtr(header.len == 0 && header.frame_format == FrameFormat::Standard)
+    }
+}
+
+pub(crate) trait MergeTxFrameHeader {
+    fn merge(&self, header: TxFrameHeader);
+}
+impl MergeTxFrameHeader for TxBufferElementHeader {
+    fn merge(&self, header: TxFrameHeader) {
+        let id: IdReg = header.into();
+        let len = DataLength::new(header.len, header.frame_format.into());
+        self.write(|w| {
+            unsafe { w.id().bits(id.as_raw_id()) }
+                .rtr()
+                .bit(id.rtr())
+                .xtd()
+                .set_id_type(header.id.into())
+                .set_len(len)
+                .set_event(header.marker.into())
+                .brs()
+                .set_brs(header.bit_rate_switching.into())
+        });
+    }
+}
+
+impl From<&TxBufferElementHeader> for TxFrameHeader {
+    fn from(reg: &TxBufferElementHeader) -> Self {
+        let reader = reg.read();
+        let id = reader.id().bits();
+        let rtr = reader.rtr().rtr();
+        let xtd = reader.xtd().id_type();
+        let len = reader.to_data_length();
+        let ff: PacFrameFormat = len.into();
+        TxFrameHeader {
+            len: len.len(),
+            frame_format: ff.into(),
+            id: IdReg::from_register(id, rtr, xtd).into(),
+            bit_rate_switching: reader.brs().is_with_brs(),
+            marker: reader.to_event().into(),
+        }
+    }
+}
+
+/// Information about a received frame
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RxFrameInfo {
+    /// Length in bytes
+    pub len: u8,
+    /// Frame Format
+    pub frame_format: FrameFormat,
+    /// Id
+    pub id: Id,
+    /// Is this an Remote Transmit Request
+    pub rtr: bool,
+    /// Did this message match any filters
+    pub filter_match: Option<u8>,
+    /// was this received with bit rate switching
+    pub bit_rate_switching: bool,
+    /// Time stamp counter
+    pub time_stamp: u16,
+}
+impl RxFrameInfo {
+    /// Transforms an RxFrameInfo into an TxFrameHeader
+    pub fn to_tx_header(self, marker: Option<u8>) -> TxFrameHeader {
+        TxFrameHeader {
+            len: self.len,
+            frame_format: self.frame_format,
+            id: self.id,
+            bit_rate_switching: self.bit_rate_switching,
+            marker,
+        }
+    }
+}
+impl From<&RxFifoElementHeader> for RxFrameInfo {
+    fn from(reg: &RxFifoElementHeader) -> Self {
+        let reader = reg.read();
+        let len = reader.to_data_length();
+        let ff: PacFrameFormat = len.into();
+        let id = reader.id().bits();
+        let rtr = reader.rtr().rtr();
+        let xtd = reader.xtd().id_type();
+        let id = IdReg::from_register(id, rtr, xtd).to_id();
+        let filter_match = match reader.to_filter_match() {
+            FilterFrameMatch::DidMatch(filter) => Some(filter),
+            FilterFrameMatch::DidNotMatch => None,
+        };
+
+        RxFrameInfo {
+            len: len.len(),
+            frame_format: ff.into(),
+            id,
+            rtr: rtr == RemoteTransmissionRequest::TransmitRemoteFrame,
+            filter_match,
+            bit_rate_switching: reader.brs().is_with_brs(),
+            time_stamp: reader.rxts().bits(),
+        }
+    }
+}
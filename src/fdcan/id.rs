@@ -6,7 +6,7 @@ use super::message_ram::enums::{IdType, RemoteTransmissionRequest};
 
 /// Standard 11-bit CAN Identifier (`0..=0x7FF`).
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct StandardId(u16);
 
 impl StandardId {
@@ -53,7 +53,7 @@ impl From<StandardId> for IdType {
 
 /// Extended 29-bit CAN Identifier (`0..=1FFF_FFFF`).
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ExtendedId(u32);
 
 impl ExtendedId {
@@ -107,7 +107,7 @@ impl From<ExtendedId> for IdType {
 
 /// A CAN Identifier (standard or extended).
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Id {
     /// Standard 11-bit Identifier (`0..=0x7FF`).
     Standard(StandardId),
@@ -151,12 +151,11 @@ impl From<Id> for IdType {
 /// have a higher priority than extended frames and data frames have a higher
 /// priority than remote frames.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub(crate) struct IdReg(u32);
 
 impl IdReg {
     const STANDARD_SHIFT: u32 = 18;
-    #[allow(dead_code)]
     const STANDARD_MASK: u32 = 0x1FFC0000;
 
     const EXTENDED_SHIFT: u32 = 0;
@@ -218,7 +217,7 @@ impl IdReg {
         } else {
             Id::Standard(unsafe {
                 StandardId::new_unchecked(
-                    ((self.0 >> Self::STANDARD_SHIFT) & Self::STANDARD_MASK) as u16,
+                    ((self.0 & Self::STANDARD_MASK) >> Self::STANDARD_SHIFT) as u16,
                 )
             })
         }
@@ -15,7 +15,7 @@ use crate::fdcan::{FdCan, Rx};
 ///
 /// The interrupts available on each line can be configured using the [`config::FdCanConfig`] struct.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[non_exhaustive]
 pub enum Interrupt {
     /// Rx FIFO 0 has a new message
@@ -83,7 +83,7 @@ pub enum Interrupt {
 
 bitflags::bitflags! {
     /// A set of FdCAN interrupts.
-    #[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
     pub struct Interrupts: u32 {
         /// Rx FIFO 0 has a new message
         const RX_FIFO_0_NEW_MESSAGE = 1 << 0;
@@ -175,7 +175,7 @@ impl ops::BitOrAssign<Interrupt> for Interrupts {
 /// The events linked to these can be configured
 /// see `[config::FdCanConfig]`
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-#[cfg_attr(feature = "unstable-defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum InterruptLine {
     /// Interrupt Line 0
     _0 = 0,
@@ -336,7 +336,7 @@ impl R {
     pub fn to_data_length(&self) -> DataLength {
         let dlc = self.dlc().bits();
         let ff = self.fdf().frame_format();
-        DataLength::new(dlc, ff)
+        DataLength::from_dlc(dlc, ff)
     }
     pub fn to_event(&self) -> Event {
         let mm = self.mm().bits();
@@ -0,0 +1,579 @@
+#![deny(missing_docs)]
+#![allow(private_bounds)] // part of the design philosophy
+
+//! Filter Math Accelerator (FMAC) access.
+//!
+//! The FMAC runs FIR and IIR filters on a stream of q1.15 samples. The filter
+//! coefficients and the X1 (inputs), X2 (coefficients) and Y (outputs) buffers
+//! live in the 256 word local memory of the FMAC, their layout is derived from
+//! the filter when it is started.
+//!
+//! Example:
+//!
+//! ```rust
+//! #[entry]
+//! fn main() -> ! {
+//!     let dp = stm32::Peripherals::take().expect("cannot take peripherals");
+//!     let pwr = dp.PWR.constrain().freeze();
+//!     let mut rcc = dp.RCC.freeze(Config::hsi(), pwr);
+//!
+//!     // 4 tap moving average
+//!     let coefficients = [I1F15::from_num(0.25); 4];
+//!
+//!     let mut fmac = dp
+//!         .FMAC
+//!         .constrain(&mut rcc)
+//!         .start(&Fir::new(&coefficients));
+//!
+//!     // polling operation
+//!
+//!     let y = fmac.process(I1F15::from_num(0.5)).unwrap();
+//!     println!("y: {}", y.to_num::<f32>());
+//!
+//!     // DMA operation, see `Fmac::into_dma`
+//!
+//!     let (input, output) = fmac.into_dma();
+//!
+//!     loop {}
+//! }
+//! ```
+
+use crate::{
+    dma::{mux::DmaMuxResources, traits::TargetAddress, MemoryToPeripheral, PeripheralToMemory},
+    rcc::Rcc,
+    stm32::FMAC,
+};
+use core::marker::PhantomData;
+
+pub use fixed::types::I1F15;
+
+/// Size of the local memory in words.
+const MEMORY_SIZE: usize = 256;
+
+/// Extension trait for constraining the FMAC peripheral.
+pub trait Ext {
+    /// Constrain the FMAC peripheral.
+    fn constrain(self, rcc: &mut Rcc) -> Fmac<Idle>;
+}
+
+impl Ext for FMAC {
+    #[inline]
+    fn constrain(self, rcc: &mut Rcc) -> Fmac<Idle> {
+        rcc.rb.ahb1enr().modify(|_, w| {
+            w.fmacen().set_bit();
+        });
+
+        Fmac {
+            rb: self,
+            _state: PhantomData,
+        }
+    }
+}
+
+/// FMAC errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// A sample was written while the X1 buffer was full.
+    Overflow,
+    /// A sample was read while the Y buffer was empty.
+    Underflow,
+}
+
+/// FMAC interrupt events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// An output sample is ready to be read.
+    OutputReady,
+    /// The X1 buffer can take an input sample.
+    InputReady,
+    /// X1 buffer overflow.
+    Overflow,
+    /// Y buffer underflow.
+    Underflow,
+    /// Saturation in the accumulator.
+    Saturation,
+}
+
+/// Gain applied to the filter output, as a left shift of the accumulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gain {
+    /// Gain of 1.
+    X1 = 0,
+    /// Gain of 2.
+    X2 = 1,
+    /// Gain of 4.
+    X4 = 2,
+    /// Gain of 8.
+    X8 = 3,
+    /// Gain of 16.
+    X16 = 4,
+    /// Gain of 32.
+    X32 = 5,
+    /// Gain of 64.
+    X64 = 6,
+    /// Gain of 128.
+    X128 = 7,
+}
+
+/// Traits and structures related to filter functions.
+pub mod filter {
+    use super::{Gain, I1F15};
+
+    pub(crate) mod sealed {
+        use super::{Gain, I1F15};
+
+        /// A filter function of the FMAC.
+        pub trait Filter {
+            /// Raw representation of the function.
+            const FUNC: u8;
+
+            /// Coefficients applied to the inputs, b0 first.
+            fn feedforward(&self) -> &[I1F15];
+            /// Coefficients applied to the previous outputs, a1 first.
+            fn feedback(&self) -> &[I1F15];
+            /// Gain applied to the output.
+            fn gain(&self) -> Gain;
+            /// Additional space in the X1 and Y buffers.
+            fn headroom(&self) -> u8;
+        }
+    }
+
+    /// Finite impulse response filter.
+    ///
+    /// `y[n] = gain * (b0 * x[n] + b1 * x[n - 1] + ... + bN-1 * x[n - N + 1])`
+    pub struct Fir<'a> {
+        coefficients: &'a [I1F15],
+        gain: Gain,
+        headroom: u8,
+    }
+
+    impl<'a> Fir<'a> {
+        /// Create a FIR filter from its coefficients `b0..bN-1`.
+        ///
+        /// Panics if there are less than 2 or more than 127 coefficients.
+        #[inline]
+        pub fn new(coefficients: &'a [I1F15]) -> Self {
+            assert!((2..=127).contains(&coefficients.len()));
+
+            Self {
+                coefficients,
+                gain: Gain::X1,
+                headroom: 0,
+            }
+        }
+
+        /// Set the gain applied to the output.
+        #[inline]
+        pub fn gain(mut self, gain: Gain) -> Self {
+            self.gain = gain;
+            self
+        }
+
+        /// Reserve additional space in the X1 and Y buffers, allowing inputs
+        /// to be written ahead and outputs to be read in bursts.
+        #[inline]
+        pub fn headroom(mut self, headroom: u8) -> Self {
+            self.headroom = headroom;
+            self
+        }
+    }
+
+    impl sealed::Filter for Fir<'_> {
+        const FUNC: u8 = 8;
+
+        #[inline]
+        fn feedforward(&self) -> &[I1F15] {
+            self.coefficients
+        }
+
+        #[inline]
+        fn feedback(&self) -> &[I1F15] {
+            &[]
+        }
+
+        #[inline]
+        fn gain(&self) -> Gain {
+            self.gain
+        }
+
+        #[inline]
+        fn headroom(&self) -> u8 {
+            self.headroom
+        }
+    }
+
+    /// Infinite impulse response filter, direct form 1.
+    ///
+    /// `y[n] = gain * (b0 * x[n] + ... + bN-1 * x[n - N + 1] + a1 * y[n - 1] + ... + aM * y[n - M])`
+    ///
+    /// *Note: the feedback coefficients are added, they are the negated
+    /// denominator coefficients of the usual transfer function notation.*
+    pub struct Iir<'a> {
+        feedforward: &'a [I1F15],
+        feedback: &'a [I1F15],
+        gain: Gain,
+        headroom: u8,
+    }
+
+    impl<'a> Iir<'a> {
+        /// Create an IIR filter from its feedforward coefficients `b0..bN-1`
+        /// and its feedback coefficients `a1..aM`.
+        ///
+        /// Panics unless there are 2 to 64 feedforward coefficients and
+        /// at least one but less feedback coefficients.
+        #[inline]
+        pub fn new(feedforward: &'a [I1F15], feedback: &'a [I1F15]) -> Self {
+            assert!((2..=64).contains(&feedforward.len()));
+            assert!(!feedback.is_empty() && feedback.len() < feedforward.len());
+
+            Self {
+                feedforward,
+                feedback,
+                gain: Gain::X1,
+                headroom: 0,
+            }
+        }
+
+        /// Set the gain applied to the output.
+        #[inline]
+        pub fn gain(mut self, gain: Gain) -> Self {
+            self.gain = gain;
+            self
+        }
+
+        /// Reserve additional space in the X1 and Y buffers, allowing inputs
+        /// to be written ahead and outputs to be read in bursts.
+        #[inline]
+        pub fn headroom(mut self, headroom: u8) -> Self {
+            self.headroom = headroom;
+            self
+        }
+    }
+
+    impl sealed::Filter for Iir<'_> {
+        const FUNC: u8 = 9;
+
+        #[inline]
+        fn feedforward(&self) -> &[I1F15] {
+            self.feedforward
+        }
+
+        #[inline]
+        fn feedback(&self) -> &[I1F15] {
+            self.feedback
+        }
+
+        #[inline]
+        fn gain(&self) -> Gain {
+            self.gain
+        }
+
+        #[inline]
+        fn headroom(&self) -> u8 {
+            self.headroom
+        }
+    }
+}
+
+pub use filter::{Fir, Iir};
+
+/// $RM0440 18.3.5
+mod func {
+    pub const LOAD_X1: u8 = 1;
+    pub const LOAD_X2: u8 = 2;
+    pub const LOAD_Y: u8 = 3;
+}
+
+/// Type-state of an FMAC without a running filter.
+pub struct Idle;
+/// Type-state of an FMAC running a filter.
+pub struct Running;
+
+/// FMAC co-processor interface.
+pub struct Fmac<State> {
+    rb: FMAC,
+    _state: PhantomData<State>,
+}
+
+impl Fmac<Idle> {
+    /// Load the filter into the local memory and start it.
+    ///
+    /// The buffers hold the coefficients first, followed by the inputs and
+    /// the outputs. The history of the filter is cleared, so each input
+    /// sample produces an output sample right away.
+    ///
+    /// Panics if the filter and its headroom do not fit in the local memory.
+    #[inline]
+    pub fn start<F>(self, filter: &F) -> Fmac<Running>
+    where
+        F: filter::sealed::Filter,
+    {
+        let feedforward = filter.feedforward();
+        let feedback = filter.feedback();
+        let (n, m, d) = (
+            feedforward.len(),
+            feedback.len(),
+            filter.headroom() as usize,
+        );
+
+        // $RM0440 18.3.4
+        let x2_size = n + m;
+        let x1_size = n + d;
+        let y_size = m.max(1) + d;
+        assert!(x2_size + x1_size + y_size <= MEMORY_SIZE);
+
+        let x2_base = 0;
+        let x1_base = x2_base + x2_size;
+        let y_base = x1_base + x1_size;
+
+        // SAFETY: all sizes and bases are within the local memory
+        self.rb.x2bufcfg().write(|w| unsafe {
+            w.x2_base().bits(x2_base as u8);
+            w.x2_buf_size().bits(x2_size as u8);
+        });
+        self.rb.x1bufcfg().write(|w| unsafe {
+            w.x1_base().bits(x1_base as u8);
+            w.x1_buf_size().bits(x1_size as u8);
+        });
+        self.rb.ybufcfg().write(|w| unsafe {
+            w.y_base().bits(y_base as u8);
+            w.y_buf_size().bits(y_size as u8);
+        });
+
+        // coefficients
+        self.load(
+            func::LOAD_X2,
+            n as u8,
+            m as u8,
+            feedforward.iter().chain(feedback).map(|c| c.to_bits()),
+        );
+
+        // cleared history
+        self.load(func::LOAD_X1, (n - 1) as u8, 0, (1..n).map(|_| 0));
+        if m > 0 {
+            self.load(func::LOAD_Y, m as u8, 0, (0..m).map(|_| 0));
+        }
+
+        // SAFETY: parameters checked when creating the filter
+        self.rb.param().write(|w| unsafe {
+            w.p().bits(n as u8);
+            w.q().bits(m as u8);
+            w.r().bits(filter.gain() as u8);
+            w.func().bits(F::FUNC);
+            w.start().set_bit();
+        });
+
+        Fmac {
+            rb: self.rb,
+            _state: PhantomData,
+        }
+    }
+
+    /// Release the FMAC resource binding after disabling its clock.
+    #[inline]
+    pub fn release(self, rcc: &mut Rcc) -> FMAC {
+        rcc.rb.ahb1enr().modify(|_, w| {
+            w.fmacen().clear_bit();
+        });
+
+        self.rb
+    }
+
+    /// Run a load function writing `p` + `q` values.
+    #[inline]
+    fn load(&self, func: u8, p: u8, q: u8, data: impl Iterator<Item = i16>) {
+        // SAFETY: all load functions are valid
+        self.rb.param().write(|w| unsafe {
+            w.p().bits(p);
+            w.q().bits(q);
+            w.func().bits(func);
+            w.start().set_bit();
+        });
+
+        data.for_each(|bits| self.write_raw(bits));
+
+        // $RM0440 18.3.5
+        // the function completes once all values have been written
+        while self.rb.param().read().start().bit_is_set() {}
+    }
+}
+
+impl Fmac<Running> {
+    /// Write an input sample.
+    #[inline]
+    pub fn write(&mut self, sample: I1F15) -> nb::Result<(), Error> {
+        if self.rb.sr().read().x1full().bit_is_set() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.write_raw(sample.to_bits());
+
+        Ok(())
+    }
+
+    /// Read an output sample.
+    #[inline]
+    pub fn read(&mut self) -> nb::Result<I1F15, Error> {
+        let sr = self.rb.sr().read();
+
+        if sr.ovfl().bit_is_set() {
+            Err(nb::Error::Other(Error::Overflow))
+        } else if sr.unfl().bit_is_set() {
+            Err(nb::Error::Other(Error::Underflow))
+        } else if sr.yempty().bit_is_set() {
+            Err(nb::Error::WouldBlock)
+        } else {
+            Ok(I1F15::from_bits(
+                self.rb.rdata().read().rdata().bits() as i16
+            ))
+        }
+    }
+
+    /// Filter a single sample.
+    ///
+    /// *Note: This employs the polling strategy.*
+    #[inline]
+    pub fn process(&mut self, sample: I1F15) -> Result<I1F15, Error> {
+        nb::block!(self.write(sample))?;
+        nb::block!(self.read())
+    }
+
+    /// Determine whether the accumulator saturated since the filter was started.
+    #[inline]
+    pub fn has_saturated(&self) -> bool {
+        self.rb.sr().read().sat().bit_is_set()
+    }
+
+    /// Stop the filter, clearing the buffers and the error flags.
+    #[inline]
+    pub fn stop(self) -> Fmac<Idle> {
+        self.rb.param().modify(|_, w| {
+            w.start().clear_bit();
+        });
+        self.rb.cr().modify(|_, w| {
+            w.reset().set_bit();
+        });
+        while self.rb.cr().read().reset().bit_is_set() {}
+
+        Fmac {
+            rb: self.rb,
+            _state: PhantomData,
+        }
+    }
+
+    /// Split the filter into its input and output, enabling their
+    /// DMA requests.
+    ///
+    /// Both can be used with the `dma::Transfer` API, the samples are the
+    /// raw bits of [`I1F15`] values.
+    #[inline]
+    pub fn into_dma(self) -> (Input, Output) {
+        self.rb.cr().modify(|_, w| {
+            w.dmawen().set_bit();
+            w.dmaren().set_bit();
+        });
+
+        (
+            Input { rb: self.rb },
+            Output {
+                _private: PhantomData,
+            },
+        )
+    }
+}
+
+// shared
+impl<State> Fmac<State> {
+    /// Enable or disable clipping of the output to the q1.15 range,
+    /// otherwise the output wraps around.
+    #[inline]
+    pub fn set_clipping(&mut self, clip: bool) {
+        self.rb.cr().modify(|_, w| {
+            w.clipen().bit(clip);
+        });
+    }
+
+    /// Enable an interrupt event.
+    #[inline]
+    pub fn listen(&mut self, event: Event) {
+        self.set_event(event, true);
+    }
+
+    /// Disable an interrupt event.
+    #[inline]
+    pub fn unlisten(&mut self, event: Event) {
+        self.set_event(event, false);
+    }
+
+    #[inline]
+    fn set_event(&mut self, event: Event, enable: bool) {
+        self.rb.cr().modify(|_, w| {
+            match event {
+                Event::OutputReady => w.rien().bit(enable),
+                Event::InputReady => w.wien().bit(enable),
+                Event::Overflow => w.ovflien().bit(enable),
+                Event::Underflow => w.unflien().bit(enable),
+                Event::Saturation => w.satien().bit(enable),
+            };
+        });
+    }
+
+    /// Write raw bits to the input register.
+    #[inline]
+    fn write_raw(&self, bits: i16) {
+        // SAFETY: all bits are valid
+        self.rb.wdata().write(|w| unsafe {
+            w.wdata().bits(bits as u16);
+        });
+    }
+}
+
+/// Input of a running filter fed by DMA.
+pub struct Input {
+    rb: FMAC,
+}
+
+/// Output of a running filter read by DMA.
+pub struct Output {
+    _private: PhantomData<FMAC>,
+}
+
+impl Input {
+    /// Join the input and output of the filter, disabling their DMA requests.
+    #[inline]
+    pub fn join(self, _output: Output) -> Fmac<Running> {
+        self.rb.cr().modify(|_, w| {
+            w.dmawen().clear_bit();
+            w.dmaren().clear_bit();
+        });
+
+        Fmac {
+            rb: self.rb,
+            _state: PhantomData,
+        }
+    }
+}
+
+unsafe impl TargetAddress<MemoryToPeripheral> for Input {
+    #[inline]
+    fn address(&self) -> u32 {
+        self.rb.wdata() as *const _ as u32
+    }
+
+    type MemSize = i16;
+
+    const REQUEST_LINE: Option<u8> = Some(DmaMuxResources::FMAC_Write as u8);
+}
+
+unsafe impl TargetAddress<PeripheralToMemory> for Output {
+    #[inline]
+    fn address(&self) -> u32 {
+        // SAFETY: only the address of the read-only output register is taken
+        unsafe { (*FMAC::ptr()).rdata() as *const _ as u32 }
+    }
+
+    type MemSize = i16;
+
+    const REQUEST_LINE: Option<u8> = Some(DmaMuxResources::FMAC_Read as u8);
+}
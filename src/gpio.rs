@@ -257,6 +257,74 @@ macro_rules! exti {
     };
 }
 
+#[cfg(feature = "async")]
+async fn wait_for_level<PIN>(pin: &mut PIN, line: u8, high: bool)
+where
+    PIN: hal_1::digital::InputPin<Error = core::convert::Infallible>,
+{
+    use crate::exti::asynch;
+
+    loop {
+        let edge = if high {
+            SignalEdge::Rising
+        } else {
+            SignalEdge::Falling
+        };
+        asynch::arm(line, edge);
+        if matches!(pin.is_high(), Ok(level) if level == high) {
+            return;
+        }
+        asynch::wait(line).await;
+    }
+}
+
+#[cfg(feature = "async")]
+async fn wait_for_edge(line: u8, edge: SignalEdge) {
+    use crate::exti::asynch;
+
+    asynch::arm(line, edge);
+    asynch::wait(line).await;
+}
+
+/// Implements `embedded_hal_async::digital::Wait` on top of the EXTI line of the pin
+///
+/// The pin has to be routed to its EXTI line with [`ExtiPin::make_interrupt_source`] first
+/// and the EXTI interrupt has to be forwarded to
+/// [`OnInterrupt::on_interrupt`](crate::asynch::OnInterrupt::on_interrupt).
+macro_rules! exti_wait {
+    ($PIN:ty, $self:ident => $line:expr) => {
+        #[cfg(feature = "async")]
+        impl<MODE> embedded_hal_async::digital::Wait for $PIN {
+            async fn wait_for_high(&mut $self) -> Result<(), Self::Error> {
+                let line = $line;
+                wait_for_level($self, line, true).await;
+                Ok(())
+            }
+
+            async fn wait_for_low(&mut $self) -> Result<(), Self::Error> {
+                let line = $line;
+                wait_for_level($self, line, false).await;
+                Ok(())
+            }
+
+            async fn wait_for_rising_edge(&mut $self) -> Result<(), Self::Error> {
+                wait_for_edge($line, SignalEdge::Rising).await;
+                Ok(())
+            }
+
+            async fn wait_for_falling_edge(&mut $self) -> Result<(), Self::Error> {
+                wait_for_edge($line, SignalEdge::Falling).await;
+                Ok(())
+            }
+
+            async fn wait_for_any_edge(&mut $self) -> Result<(), Self::Error> {
+                wait_for_edge($line, SignalEdge::RisingFalling).await;
+                Ok(())
+            }
+        }
+    };
+}
+
 macro_rules! gpio {
     ($GPIOX:ident, $gpiox:ident, $iopxenr:ident, $PXx:ident, $Pxn:expr, [
         $($PXi:ident: ($pxi:ident, $i:expr, $exticri:ident),)+
@@ -358,8 +426,70 @@ macro_rules! gpio {
                 }
             }
 
+            impl<MODE> hal_1::digital::ErrorType for $PXx<Output<MODE>> {
+                type Error = core::convert::Infallible;
+            }
+
+            impl<MODE> hal_1::digital::OutputPin for $PXx<Output<MODE>> {
+                fn set_high(&mut self) -> Result<(), Self::Error> {
+                    // NOTE(unsafe) atomic write to a stateless register
+                    unsafe { (*$GPIOX::ptr()).bsrr().write(|w| {w.bits(1 << self.i);}) };
+                    Ok(())
+                }
+
+                fn set_low(&mut self) -> Result<(), Self::Error> {
+                    // NOTE(unsafe) atomic write to a stateless register
+                    unsafe { (*$GPIOX::ptr()).bsrr().write(|w| {w.bits(1 << (self.i + 16));}) };
+                    Ok(())
+                }
+            }
+
+            impl<MODE> hal_1::digital::StatefulOutputPin for $PXx<Output<MODE>> {
+                fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+                    let is_set_high = !hal_1::digital::StatefulOutputPin::is_set_low(self)?;
+                    Ok(is_set_high)
+                }
+
+                fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+                    // NOTE(unsafe) atomic read with no side effects
+                    let is_set_low = unsafe { (*$GPIOX::ptr()).odr().read().bits() & (1 << self.i) == 0 };
+                    Ok(is_set_low)
+                }
+            }
+
+            impl<MODE> hal_1::digital::InputPin for $PXx<Output<MODE>> {
+                fn is_high(&mut self) -> Result<bool, Self::Error> {
+                    let is_high = !hal_1::digital::InputPin::is_low(self)?;
+                    Ok(is_high)
+                }
+
+                fn is_low(&mut self) -> Result<bool, Self::Error> {
+                    // NOTE(unsafe) atomic read with no side effects
+                    let is_low = unsafe { (*$GPIOX::ptr()).idr().read().bits() & (1 << self.i) == 0 };
+                    Ok(is_low)
+                }
+            }
+
+            impl<MODE> hal_1::digital::ErrorType for $PXx<Input<MODE>> {
+                type Error = core::convert::Infallible;
+            }
+
+            impl<MODE> hal_1::digital::InputPin for $PXx<Input<MODE>> {
+                fn is_high(&mut self) -> Result<bool, Self::Error> {
+                    let is_high = !hal_1::digital::InputPin::is_low(self)?;
+                    Ok(is_high)
+                }
+
+                fn is_low(&mut self) -> Result<bool, Self::Error> {
+                    // NOTE(unsafe) atomic read with no side effects
+                    let is_low = unsafe { (*$GPIOX::ptr()).idr().read().bits() & (1 << self.i) == 0 };
+                    Ok(is_low)
+                }
+            }
+
             exti_erased!($PXx<Output<MODE>>, $Pxn);
             exti_erased!($PXx<Input<MODE>>, $Pxn);
+            exti_wait!($PXx<Input<MODE>>, self => self.i);
 
             $(
                 pub struct $PXi<MODE> {
@@ -650,8 +780,70 @@ macro_rules! gpio {
                     }
                 }
 
+                impl<MODE> hal_1::digital::ErrorType for $PXi<Output<MODE>> {
+                    type Error = core::convert::Infallible;
+                }
+
+                impl<MODE> hal_1::digital::OutputPin for $PXi<Output<MODE>> {
+                    fn set_high(&mut self) -> Result<(), Self::Error> {
+                        // NOTE(unsafe) atomic write to a stateless register
+                        unsafe { (*$GPIOX::ptr()).bsrr().write(|w| {w.bits(1 << $i);}) };
+                        Ok(())
+                    }
+
+                    fn set_low(&mut self) -> Result<(), Self::Error> {
+                        // NOTE(unsafe) atomic write to a stateless register
+                        unsafe { (*$GPIOX::ptr()).bsrr().write(|w| {w.bits(1 << ($i + 16));}) };
+                        Ok(())
+                    }
+                }
+
+                impl<MODE> hal_1::digital::StatefulOutputPin for $PXi<Output<MODE>> {
+                    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+                        let is_set_high = !hal_1::digital::StatefulOutputPin::is_set_low(self)?;
+                        Ok(is_set_high)
+                    }
+
+                    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+                        // NOTE(unsafe) atomic read with no side effects
+                        let is_set_low = unsafe { (*$GPIOX::ptr()).odr().read().bits() & (1 << $i) == 0 };
+                        Ok(is_set_low)
+                    }
+                }
+
+                impl<MODE> hal_1::digital::InputPin for $PXi<Output<MODE>> {
+                    fn is_high(&mut self) -> Result<bool, Self::Error> {
+                        let is_high = !hal_1::digital::InputPin::is_low(self)?;
+                        Ok(is_high)
+                    }
+
+                    fn is_low(&mut self) -> Result<bool, Self::Error> {
+                        // NOTE(unsafe) atomic read with no side effects
+                        let is_low = unsafe { (*$GPIOX::ptr()).idr().read().bits() & (1 << $i) == 0 };
+                        Ok(is_low)
+                    }
+                }
+
+                impl<MODE> hal_1::digital::ErrorType for $PXi<Input<MODE>> {
+                    type Error = core::convert::Infallible;
+                }
+
+                impl<MODE> hal_1::digital::InputPin for $PXi<Input<MODE>> {
+                    fn is_high(&mut self) -> Result<bool, Self::Error> {
+                        let is_high = !hal_1::digital::InputPin::is_low(self)?;
+                        Ok(is_high)
+                    }
+
+                    fn is_low(&mut self) -> Result<bool, Self::Error> {
+                        // NOTE(unsafe) atomic read with no side effects
+                        let is_low = unsafe { (*$GPIOX::ptr()).idr().read().bits() & (1 << $i) == 0 };
+                        Ok(is_low)
+                    }
+                }
+
                 exti!($PXi<Output<MODE>>, $Pxn, $i, $exticri);
                 exti!($PXi<Input<MODE>>, $Pxn, $i, $exticri);
+                exti_wait!($PXi<Input<MODE>>, self => $i);
             )+
 
             impl<TYPE> $PXx<TYPE> {
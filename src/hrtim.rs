@@ -0,0 +1,640 @@
+//! High Resolution Timer (HRTIM)
+//!
+//! The HRTIM is available on the STM32G474 and STM32G484. It consists of a master timer and six
+//! timing units (`TIMA` to `TIMF`), each driving two outputs through a set/reset crossbar.
+//!
+//! ## Usage
+//!
+//! The HRTIM first has to be enabled and its DLL calibrated through the `HRTIM_COMMON`
+//! peripheral. This hands out the [HrPwmControl](struct.HrPwmControl.html) token, which is needed
+//! to start the timers, along with the fault and external event inputs.
+//!
+//! ```rust
+//! let (mut control, flt_inputs, eev_inputs) = dp
+//!     .HRTIM_COMMON
+//!     .hr_control(&mut rcc)
+//!     .wait_for_calibration();
+//!
+//! let pin_a = gpioa.pa8.into_alternate();
+//! let pin_b = gpioa.pa9.into_alternate();
+//!
+//! //        .               .               .
+//! //        .  30%          .               .
+//! //         ----           .               .----
+//! //pin_a   |    |          .               |    |
+//! //        |    |          .               |    |
+//! //   ------    ------------------------------    ------------
+//! //        .               .               .
+//! //        .               .  30%          .
+//! //        .               .----           .
+//! //pin_b   .               |    |          .
+//! //        .               |    |          .
+//! //   ---------------------    ------------------------------
+//! let HrParts {
+//!     mut timer,
+//!     mut cr1,
+//!     out: (mut out1, mut out2),
+//!     ..
+//! } = dp
+//!     .HRTIM_TIMA
+//!     .pwm_advanced((pin_a, pin_b), &mut rcc)
+//!     .prescaler(Pscl4)
+//!     .period(0xFFFF)
+//!     .push_pull_mode(true)
+//!     .finalize(&mut control);
+//!
+//! out1.enable_rst_event(&cr1); // Set low on compare match with cr1
+//! out2.enable_rst_event(&cr1);
+//!
+//! out1.enable_set_event(&timer); // Set high at new period
+//! out2.enable_set_event(&timer);
+//!
+//! cr1.set_duty(timer.get_period() / 3);
+//!
+//! out1.enable();
+//! out2.enable();
+//!
+//! timer.start(&mut control);
+//! ```
+//!
+//! ## Prescalers
+//!
+//! All timers count at a multiple of `f_HRTIM` (the APB2 timer clock). The prescaler type
+//! parameter selects the counting speed relative to the 32 times faster high resolution clock,
+//! i.e. [Pscl1](struct.Pscl1.html) counts at `32 * f_HRTIM` while [Pscl32](struct.Pscl32.html)
+//! counts at `f_HRTIM`. Each prescaler restricts the range of valid compare and period values,
+//! see [HrtimPrescaler](trait.HrtimPrescaler.html).
+
+pub mod adc_trigger;
+pub mod compare_register;
+pub mod deadtime;
+pub mod external_event;
+pub mod fault;
+pub mod output;
+
+use core::marker::PhantomData;
+
+use crate::pwm::Polarity;
+use crate::rcc::{Enable, Rcc, Reset};
+use crate::stm32::{
+    HRTIM_COMMON, HRTIM_MASTER, HRTIM_TIMA, HRTIM_TIMB, HRTIM_TIMC, HRTIM_TIMD, HRTIM_TIME,
+    HRTIM_TIMF, RCC,
+};
+
+use self::adc_trigger::{
+    AdcTrigger1, AdcTrigger10, AdcTrigger2, AdcTrigger3, AdcTrigger4, AdcTrigger5, AdcTrigger6,
+    AdcTrigger7, AdcTrigger8, AdcTrigger9,
+};
+use self::compare_register::{HrCr1, HrCr2, HrCr3, HrCr4};
+use self::deadtime::DeadtimeConfig;
+use self::external_event::ExternalEventInputs;
+use self::fault::{FaultAction, FaultInputs, FaultSource};
+use self::output::ToHrOut;
+
+/// Prescaler of a HRTIM timer, see the [module level documentation](index.html#prescalers)
+pub trait HrtimPrescaler: crate::Sealed {
+    /// Value of the `CKPSC` field
+    const BITS: u8;
+    /// Smallest valid compare and period value
+    const MIN_CR: u16;
+    /// Largest valid compare and period value
+    const MAX_CR: u16;
+}
+
+macro_rules! prescalers {
+    ($($NAME:ident: $bits:expr, $min:expr, $max:expr, $doc:expr;)+) => {
+        $(
+            #[doc = $doc]
+            #[derive(Copy, Clone, Debug)]
+            pub struct $NAME;
+
+            impl crate::Sealed for $NAME {}
+
+            impl HrtimPrescaler for $NAME {
+                const BITS: u8 = $bits;
+                const MIN_CR: u16 = $min;
+                const MAX_CR: u16 = $max;
+            }
+        )+
+    };
+}
+
+prescalers! {
+    Pscl1: 0b000, 0x0060, 0xFFDF, "Count at `32 * f_HRTIM`";
+    Pscl2: 0b001, 0x0030, 0xFFEF, "Count at `16 * f_HRTIM`";
+    Pscl4: 0b010, 0x0018, 0xFFF7, "Count at `8 * f_HRTIM`";
+    Pscl8: 0b011, 0x000C, 0xFFFB, "Count at `4 * f_HRTIM`";
+    Pscl16: 0b100, 0x0006, 0xFFFD, "Count at `2 * f_HRTIM`";
+    Pscl32: 0b101, 0x0003, 0xFFFD, "Count at `f_HRTIM`";
+    Pscl64: 0b110, 0x0003, 0xFFFD, "Count at `f_HRTIM / 2`";
+    Pscl128: 0b111, 0x0003, 0xFFFD, "Count at `f_HRTIM / 4`";
+}
+
+/// Timer events which may raise an interrupt
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HrTimerEvent {
+    /// Counter matched compare register 1
+    Compare1 = 1 << 0,
+    /// Counter matched compare register 2
+    Compare2 = 1 << 1,
+    /// Counter matched compare register 3
+    Compare3 = 1 << 2,
+    /// Counter matched compare register 4
+    Compare4 = 1 << 3,
+    /// The repetition counter underflowed
+    Repetition = 1 << 4,
+    /// The preloaded registers were transferred
+    Update = 1 << 6,
+}
+
+/// Proof that the HRTIM has been enabled and its DLL calibrated
+///
+/// This is required to start timers and holds the ADC trigger configuration which is shared by
+/// all timers.
+pub struct HrPwmControl {
+    _x: PhantomData<()>,
+
+    /// ADC trigger 1, usable by ADC1/ADC2 and ADC3/ADC4/ADC5
+    pub adc_trigger1: AdcTrigger1,
+    /// ADC trigger 2, usable by ADC3/ADC4/ADC5
+    pub adc_trigger2: AdcTrigger2,
+    /// ADC trigger 3, usable by ADC1/ADC2 and ADC3/ADC4/ADC5
+    pub adc_trigger3: AdcTrigger3,
+    /// ADC trigger 4, usable by ADC3/ADC4/ADC5
+    pub adc_trigger4: AdcTrigger4,
+    /// ADC trigger 5, usable by ADC1/ADC2 and ADC3/ADC4/ADC5
+    pub adc_trigger5: AdcTrigger5,
+    /// ADC trigger 6, usable by ADC1/ADC2 and ADC3/ADC4/ADC5
+    pub adc_trigger6: AdcTrigger6,
+    /// ADC trigger 7, usable by ADC1/ADC2 and ADC3/ADC4/ADC5
+    pub adc_trigger7: AdcTrigger7,
+    /// ADC trigger 8, usable by ADC1/ADC2 and ADC3/ADC4/ADC5
+    pub adc_trigger8: AdcTrigger8,
+    /// ADC trigger 9, usable by ADC1/ADC2 and ADC3/ADC4/ADC5
+    pub adc_trigger9: AdcTrigger9,
+    /// ADC trigger 10, usable by ADC1/ADC2 and ADC3/ADC4/ADC5
+    pub adc_trigger10: AdcTrigger10,
+}
+
+/// The HRTIM has been enabled, the DLL calibration is ongoing
+pub struct HrTimOngoingCalibration {
+    _x: PhantomData<()>,
+}
+
+/// Extension trait to take control of the HRTIM
+pub trait HrControlExt {
+    /// Enable and reset the HRTIM and start the DLL calibration
+    fn hr_control(self, rcc: &mut Rcc) -> HrTimOngoingCalibration;
+}
+
+impl HrControlExt for HRTIM_COMMON {
+    fn hr_control(self, _rcc: &mut Rcc) -> HrTimOngoingCalibration {
+        // Enable and reset HRTIM
+        unsafe {
+            let rcc_ptr = &(*RCC::ptr());
+            HRTIM_COMMON::enable(rcc_ptr);
+            HRTIM_COMMON::reset(rcc_ptr);
+        }
+
+        // Start calibration procedure
+        self.dllcr().write(|w| {
+            w.cal().set_bit();
+        });
+
+        HrTimOngoingCalibration { _x: PhantomData }
+    }
+}
+
+impl HrTimOngoingCalibration {
+    /// Set the sampling clock division of the fault inputs, `f_FLTS = f_HRTIM / 2^div`
+    ///
+    /// NOTE: Only valid values are 0 to 3
+    pub fn set_fault_sampling_division(self, div: u8) -> Self {
+        assert!(div <= 0b11);
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        common.fltinr2().modify(|_, w| unsafe {
+            w.fltsd().bits(div);
+        });
+        self
+    }
+
+    /// Set the sampling clock division of the external event inputs, `f_EEVS = f_HRTIM / 2^div`
+    ///
+    /// NOTE: Only valid values are 0 to 3
+    pub fn set_eev_sampling_division(self, div: u8) -> Self {
+        assert!(div <= 0b11);
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        common.eecr3().modify(|_, w| unsafe {
+            w.eevsd().bits(div);
+        });
+        self
+    }
+
+    /// Block until the DLL calibration is finished
+    pub fn wait_for_calibration(self) -> (HrPwmControl, FaultInputs, ExternalEventInputs) {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        while common.isr().read().dllrdy().bit_is_clear() {}
+
+        // Keep the DLL calibrated, compensating for voltage and temperature drift
+        common.dllcr().modify(|_, w| unsafe {
+            w.calen().set_bit().calrte().bits(0b00);
+        });
+
+        (
+            HrPwmControl {
+                _x: PhantomData,
+                adc_trigger1: AdcTrigger1 { _x: PhantomData },
+                adc_trigger2: AdcTrigger2 { _x: PhantomData },
+                adc_trigger3: AdcTrigger3 { _x: PhantomData },
+                adc_trigger4: AdcTrigger4 { _x: PhantomData },
+                adc_trigger5: AdcTrigger5 { _x: PhantomData },
+                adc_trigger6: AdcTrigger6 { _x: PhantomData },
+                adc_trigger7: AdcTrigger7 { _x: PhantomData },
+                adc_trigger8: AdcTrigger8 { _x: PhantomData },
+                adc_trigger9: AdcTrigger9 { _x: PhantomData },
+                adc_trigger10: AdcTrigger10 { _x: PhantomData },
+            },
+            FaultInputs::new(),
+            ExternalEventInputs::new(),
+        )
+    }
+}
+
+/// A HRTIM timer, either the master timer or one of the timing units
+pub struct HrTim<TIM, PSCL> {
+    _tim: PhantomData<TIM>,
+    _prescaler: PhantomData<PSCL>,
+}
+
+/// The parts making up a configured HRTIM timer
+pub struct HrParts<TIM, PSCL, OUT> {
+    /// The timer itself, controls the period and may be used as an event source
+    pub timer: HrTim<TIM, PSCL>,
+
+    /// Compare register 1
+    pub cr1: HrCr1<TIM, PSCL>,
+    /// Compare register 2
+    pub cr2: HrCr2<TIM, PSCL>,
+    /// Compare register 3
+    pub cr3: HrCr3<TIM, PSCL>,
+    /// Compare register 4
+    pub cr4: HrCr4<TIM, PSCL>,
+
+    /// The outputs connected to pins
+    pub out: OUT,
+}
+
+/// HrPwmBuilder is used to configure the HRTIM timers
+pub struct HrPwmBuilder<TIM, PSCL, PINS> {
+    _tim: PhantomData<TIM>,
+    _prescaler: PhantomData<PSCL>,
+    _pins: PhantomData<PINS>,
+    period: u16,
+    repetition_counter: u8,
+    preload: bool,
+    push_pull: bool,
+    deadtime: Option<DeadtimeConfig>,
+    out1_polarity: Polarity,
+    out2_polarity: Polarity,
+    fault_enable_bits: u8,
+    fault1_action: FaultAction,
+    fault2_action: FaultAction,
+}
+
+/// Extension trait to configure a HRTIM timing unit for PWM generation
+pub trait HrPwmExt: Sized {
+    /// Start configuring the timer, connecting it to `pins`
+    fn pwm_advanced<PINS>(self, pins: PINS, rcc: &mut Rcc) -> HrPwmBuilder<Self, Pscl128, PINS>
+    where
+        PINS: ToHrOut<Self>;
+}
+
+/// Extension trait to configure the HRTIM master timer
+pub trait HrMasterExt: Sized {
+    /// Start configuring the master timer
+    fn master(self, rcc: &mut Rcc) -> HrPwmBuilder<Self, Pscl128, ()>;
+}
+
+impl<TIM, PSCL, PINS> HrPwmBuilder<TIM, PSCL, PINS> {
+    fn new() -> Self {
+        HrPwmBuilder {
+            _tim: PhantomData,
+            _prescaler: PhantomData,
+            _pins: PhantomData,
+            period: 0xFFFD,
+            repetition_counter: 0,
+            preload: true,
+            push_pull: false,
+            deadtime: None,
+            out1_polarity: Polarity::ActiveHigh,
+            out2_polarity: Polarity::ActiveHigh,
+            fault_enable_bits: 0,
+            fault1_action: FaultAction::ForceInactive,
+            fault2_action: FaultAction::ForceInactive,
+        }
+    }
+
+    /// Set the prescaler, see the [module level documentation](index.html#prescalers)
+    pub fn prescaler<P: HrtimPrescaler>(self, _prescaler: P) -> HrPwmBuilder<TIM, P, PINS> {
+        HrPwmBuilder {
+            _tim: PhantomData,
+            _prescaler: PhantomData,
+            _pins: PhantomData,
+            period: self.period,
+            repetition_counter: self.repetition_counter,
+            preload: self.preload,
+            push_pull: self.push_pull,
+            deadtime: self.deadtime,
+            out1_polarity: self.out1_polarity,
+            out2_polarity: self.out2_polarity,
+            fault_enable_bits: self.fault_enable_bits,
+            fault1_action: self.fault1_action,
+            fault2_action: self.fault2_action,
+        }
+    }
+
+    /// Set the period, this is clamped to the range valid for the prescaler when finalizing
+    pub fn period(mut self, period: u16) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Set the repetition counter, the repetition event fires every `repetition_counter + 1`
+    /// periods
+    pub fn repetition_counter(mut self, repetition_counter: u8) -> Self {
+        self.repetition_counter = repetition_counter;
+        self
+    }
+
+    /// Enable or disable the preload of the period and compare registers
+    ///
+    /// When enabled (the default) new values take effect on the repetition event.
+    pub fn preload(mut self, preload: bool) -> Self {
+        self.preload = preload;
+        self
+    }
+}
+
+impl<TIM, PSCL, PINS> HrPwmBuilder<TIM, PSCL, PINS>
+where
+    TIM: output::HrOutputTimer,
+{
+    /// Enable push-pull mode, the outputs are alternately activated every other period
+    pub fn push_pull_mode(mut self, enable: bool) -> Self {
+        self.push_pull = enable;
+        self
+    }
+
+    /// Enable the deadtime generator, output 2 becomes the complement of output 1
+    pub fn deadtime(mut self, deadtime: DeadtimeConfig) -> Self {
+        self.deadtime = Some(deadtime);
+        self
+    }
+
+    /// Set the polarity of output 1
+    pub fn out1_polarity(mut self, polarity: Polarity) -> Self {
+        self.out1_polarity = polarity;
+        self
+    }
+
+    /// Set the polarity of output 2
+    pub fn out2_polarity(mut self, polarity: Polarity) -> Self {
+        self.out2_polarity = polarity;
+        self
+    }
+
+    /// Have the outputs react to `fault`
+    pub fn with_fault_source<const N: u8>(mut self, _fault: &FaultSource<N>) -> Self {
+        self.fault_enable_bits |= 1 << (N - 1);
+        self
+    }
+
+    /// Set the state of output 1 while a fault is active
+    pub fn fault_action1(mut self, action: FaultAction) -> Self {
+        self.fault1_action = action;
+        self
+    }
+
+    /// Set the state of output 2 while a fault is active
+    pub fn fault_action2(mut self, action: FaultAction) -> Self {
+        self.fault2_action = action;
+        self
+    }
+}
+
+macro_rules! hrtim_master {
+    ($TIMX:ident: ($cr:ident, $ck_psc:ident, $cont:ident, $repu:ident, $per:ident, $perf:ident,
+        $rep:ident, $repf:ident, $cnt:ident, $cntf:ident, $dier:ident, $isr:ident, $icr:ident, $en_bit:expr, $swu_bit:expr)) => {
+        impl HrMasterExt for $TIMX {
+            fn master(self, _rcc: &mut Rcc) -> HrPwmBuilder<Self, Pscl128, ()> {
+                HrPwmBuilder::new()
+            }
+        }
+
+        hrtim_timer!($TIMX: ($cr, $ck_psc, $cont, $repu, $per, $perf, $rep, $repf, $cnt, $cntf,
+            $dier, $isr, $icr, $en_bit, $swu_bit));
+
+        impl<PSCL: HrtimPrescaler> HrPwmBuilder<$TIMX, PSCL, ()> {
+            /// Configure the master timer
+            pub fn finalize(self, _control: &mut HrPwmControl) -> HrParts<$TIMX, PSCL, ()> {
+                let tim = unsafe { &*$TIMX::ptr() };
+                self.configure_timer(tim);
+
+                HrParts {
+                    timer: HrTim { _tim: PhantomData, _prescaler: PhantomData },
+                    cr1: HrCr1::new(),
+                    cr2: HrCr2::new(),
+                    cr3: HrCr3::new(),
+                    cr4: HrCr4::new(),
+                    out: (),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! hrtim_timer {
+    ($TIMX:ident: ($cr:ident, $ck_psc:ident, $cont:ident, $repu:ident, $per:ident, $perf:ident,
+        $rep:ident, $repf:ident, $cnt:ident, $cntf:ident, $dier:ident, $isr:ident, $icr:ident, $en_bit:expr, $swu_bit:expr)) => {
+        impl<PSCL: HrtimPrescaler, PINS> HrPwmBuilder<$TIMX, PSCL, PINS> {
+            fn configure_timer(&self, tim: &<$TIMX as core::ops::Deref>::Target) {
+                let period = self.period.clamp(PSCL::MIN_CR, PSCL::MAX_CR);
+
+                tim.$cr().modify(|_, w| unsafe {
+                    w.$ck_psc().bits(PSCL::BITS)
+                        // Continuous mode
+                        .$cont().set_bit()
+                        // Transfer the preloaded registers on the repetition event
+                        .preen().bit(self.preload)
+                        .$repu().bit(self.preload);
+                });
+                tim.$per().write(|w| unsafe { w.$perf().bits(period); });
+                tim.$rep().write(|w| unsafe { w.$repf().bits(self.repetition_counter); });
+            }
+        }
+
+        impl<PSCL: HrtimPrescaler> HrTim<$TIMX, PSCL> {
+            /// Start the timer
+            pub fn start(&mut self, _control: &mut HrPwmControl) {
+                let master = unsafe { &*HRTIM_MASTER::ptr() };
+                master.mcr().modify(|r, w| unsafe { w.bits(r.bits() | (1 << $en_bit)); });
+            }
+
+            /// Stop the timer
+            pub fn stop(&mut self, _control: &mut HrPwmControl) {
+                let master = unsafe { &*HRTIM_MASTER::ptr() };
+                master.mcr().modify(|r, w| unsafe { w.bits(r.bits() & !(1 << $en_bit)); });
+            }
+
+            /// Returns the period
+            pub fn get_period(&self) -> u16 {
+                let tim = unsafe { &*$TIMX::ptr() };
+                tim.$per().read().$perf().bits()
+            }
+
+            /// Set the period, clamped to the range valid for the prescaler
+            pub fn set_period(&mut self, period: u16) {
+                let tim = unsafe { &*$TIMX::ptr() };
+                let period = period.clamp(PSCL::MIN_CR, PSCL::MAX_CR);
+                tim.$per().write(|w| unsafe { w.$perf().bits(period); });
+            }
+
+            /// Returns the current counter value
+            pub fn get_counter_value(&self) -> u16 {
+                let tim = unsafe { &*$TIMX::ptr() };
+                tim.$cnt().read().$cntf().bits()
+            }
+
+            /// Force a transfer of the preloaded registers
+            pub fn software_update(&mut self) {
+                let common = unsafe { &*HRTIM_COMMON::ptr() };
+                common.cr2().write(|w| unsafe { w.bits(1 << $swu_bit); });
+            }
+
+            /// Start listening for `event`
+            pub fn listen(&mut self, event: HrTimerEvent) {
+                let tim = unsafe { &*$TIMX::ptr() };
+                tim.$dier().modify(|r, w| unsafe { w.bits(r.bits() | event as u32); });
+            }
+
+            /// Stop listening for `event`
+            pub fn unlisten(&mut self, event: HrTimerEvent) {
+                let tim = unsafe { &*$TIMX::ptr() };
+                tim.$dier().modify(|r, w| unsafe { w.bits(r.bits() & !(event as u32)); });
+            }
+
+            /// Returns true if `event` has occurred
+            pub fn is_pending(&self, event: HrTimerEvent) -> bool {
+                let tim = unsafe { &*$TIMX::ptr() };
+                tim.$isr().read().bits() & event as u32 != 0
+            }
+
+            /// Clear the interrupt flag of `event`
+            pub fn clear_interrupt(&mut self, event: HrTimerEvent) {
+                let tim = unsafe { &*$TIMX::ptr() };
+                tim.$icr().write(|w| unsafe { w.bits(event as u32); });
+            }
+        }
+    };
+}
+
+macro_rules! hrtim_unit {
+    ($($TIMX:ident: ($cr:ident, $per:ident, $rep:ident, $cnt:ident, $dier:ident, $isr:ident, $icr:ident,
+        $out:ident, $dt:ident, $flt:ident, $en_bit:expr, $swu_bit:expr),)+) => {
+        $(
+            impl HrPwmExt for $TIMX {
+                fn pwm_advanced<PINS>(self, _pins: PINS, _rcc: &mut Rcc) -> HrPwmBuilder<Self, Pscl128, PINS>
+                where
+                    PINS: ToHrOut<Self>,
+                {
+                    HrPwmBuilder::new()
+                }
+            }
+
+            hrtim_timer!($TIMX: ($cr, ck_pscx, contx, trepu, $per, perx, $rep, repx, $cnt, cntx,
+                $dier, $isr, $icr, $en_bit, $swu_bit));
+
+            impl<PSCL: HrtimPrescaler, PINS: ToHrOut<$TIMX>> HrPwmBuilder<$TIMX, PSCL, PINS> {
+                /// Configure the timer and its outputs
+                ///
+                /// The outputs are left disabled, see [HrOut1::enable](output/struct.HrOut1.html#method.enable).
+                pub fn finalize(self, _control: &mut HrPwmControl) -> HrParts<$TIMX, PSCL, PINS::Out<PSCL>> {
+                    let tim = unsafe { &*$TIMX::ptr() };
+                    self.configure_timer(tim);
+
+                    tim.$cr().modify(|_, w| {
+                        w.pshpll().bit(self.push_pull);
+                    });
+
+                    if let Some(deadtime) = self.deadtime {
+                        tim.$dt().write(|w| unsafe {
+                            w.dtprsc().bits(deadtime.prescaler as u8)
+                                .dtrx().bits(deadtime.deadtime_rising_value)
+                                .sdtrx().bit(deadtime.deadtime_rising_sign)
+                                .dtfx().bits(deadtime.deadtime_falling_value)
+                                .sdtfx().bit(deadtime.deadtime_falling_sign);
+                        });
+                    }
+
+                    tim.$out().modify(|_, w| unsafe {
+                        w.pol1().bit(matches!(self.out1_polarity, Polarity::ActiveLow))
+                            .pol2().bit(matches!(self.out2_polarity, Polarity::ActiveLow))
+                            .dten().bit(self.deadtime.is_some())
+                            .fault1().bits(self.fault1_action as u8)
+                            .fault2().bits(self.fault2_action as u8);
+                    });
+
+                    tim.$flt().write(|w| unsafe { w.bits(self.fault_enable_bits as u32); });
+
+                    HrParts {
+                        timer: HrTim { _tim: PhantomData, _prescaler: PhantomData },
+                        cr1: HrCr1::new(),
+                        cr2: HrCr2::new(),
+                        cr3: HrCr3::new(),
+                        cr4: HrCr4::new(),
+                        out: <PINS as ToHrOut<$TIMX>>::out::<PSCL>(),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+hrtim_master!(HRTIM_MASTER: (mcr, ck_psc, cont, mrepu, mper, mper, mrep, mrep, mcntr, mcnt,
+    mdier, misr, micr, 16, 0));
+
+hrtim_unit! {
+    HRTIM_TIMA: (timacr, perar, repar, cntar, timadier, timaisr, timaicr, outar, dtar, fltar, 17, 1),
+    HRTIM_TIMB: (timbcr, perbr, repbr, cntbr, timbdier, timbisr, timbicr, outbr, dtbr, fltbr, 18, 2),
+    HRTIM_TIMC: (timccr, percr, repcr, cntcr, timcdier, timcisr, timcicr, outcr, dtcr, fltcr, 19, 3),
+    HRTIM_TIMD: (timdcr, perdr, repdr, cntdr, timddier, timdisr, timdicr, outdr, dtdr, fltdr, 20, 4),
+    HRTIM_TIME: (timecr, perer, reper, cnter, timedier, timeisr, timeicr, outer, dter, flter, 21, 5),
+    HRTIM_TIMF: (timfcr, perfr, repfr, cntfr, timfdier, timfisr, timficr, outfr, dtfr, fltfr, 22, 6),
+}
+
+/// An event which can set or reset an output of `DST`, or otherwise be routed to `DST`
+pub trait EventSource<DST>: crate::Sealed {
+    /// Bits of this event in the `SETxyR`/`RSTxyR` registers
+    const BITS: u32;
+}
+
+// The timer period of a timing unit, may only affect its own outputs
+macro_rules! period_event {
+    ($($TIMX:ident),+) => {
+        $(
+            impl<PSCL> crate::Sealed for HrTim<$TIMX, PSCL> {}
+            impl<PSCL> EventSource<$TIMX> for HrTim<$TIMX, PSCL> {
+                const BITS: u32 = 1 << 2;
+            }
+        )+
+    };
+}
+
+period_event!(HRTIM_TIMA, HRTIM_TIMB, HRTIM_TIMC, HRTIM_TIMD, HRTIM_TIME, HRTIM_TIMF);
+
+impl<PSCL> crate::Sealed for HrTim<HRTIM_MASTER, PSCL> {}
+
+// The master period may affect all outputs
+impl<DST: output::HrOutputTimer, PSCL> EventSource<DST> for HrTim<HRTIM_MASTER, PSCL> {
+    const BITS: u32 = 1 << 7;
+}
@@ -0,0 +1,219 @@
+//! HRTIM ADC triggers
+//!
+//! The HRTIM provides ten ADC trigger outputs, `hrtim_adc_trg1` to `hrtim_adc_trg10`. Triggers
+//! 1 to 4 are the OR of any number of sources selected with [AdcTrigger1::enable_source] etc.,
+//! triggers 5 to 10 have a single source selected with [AdcTrigger5::set_source] etc.
+//! Use the `ADC12`/`ADC345` constants to select the trigger as the external trigger of an ADC.
+
+use core::marker::PhantomData;
+
+use super::compare_register::{HrCr1, HrCr2, HrCr3, HrCr4};
+use super::HrTim;
+use crate::adc::config::{ExternalTrigger12, ExternalTrigger345};
+use crate::stm32::{
+    HRTIM_COMMON, HRTIM_MASTER, HRTIM_TIMA, HRTIM_TIMB, HRTIM_TIMC, HRTIM_TIMD, HRTIM_TIME,
+    HRTIM_TIMF,
+};
+
+/// A source which can be routed to the ADC trigger `TRIG`
+pub trait AdcTriggerSource<TRIG>: crate::Sealed {
+    /// Bits of this source in the `ADCxR` register of triggers 1 to 4, or its number in the
+    /// `ADCER` register for triggers 5 to 10
+    const BITS: u32;
+}
+
+/// Timer whose update event transfers the preloaded sources of an ADC trigger
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AdcTriggerUpdate {
+    /// Master timer
+    Master = 0b000,
+    /// Timer A
+    TimerA = 0b001,
+    /// Timer B
+    TimerB = 0b010,
+    /// Timer C
+    TimerC = 0b011,
+    /// Timer D
+    TimerD = 0b100,
+    /// Timer E
+    TimerE = 0b101,
+    /// Timer F
+    TimerF = 0b110,
+}
+
+macro_rules! adc_trigger_common {
+    ($TRIG:ident, $doc:expr, [$($ADC:ident = $ET:ident::$variant:ident),+], $usrcr:ident, $usrc:expr) => {
+        #[doc = $doc]
+        pub struct $TRIG {
+            pub(super) _x: PhantomData<()>,
+        }
+
+        impl $TRIG {
+            $(
+                #[doc = concat!("This trigger as the external trigger of an ", stringify!($ADC))]
+                pub const $ADC: $ET = $ET::$variant;
+            )+
+
+            /// Select the timer whose update event transfers the sources of this trigger when
+            /// the preload of that timer is enabled
+            pub fn set_update_source(&mut self, source: AdcTriggerUpdate) {
+                let common = unsafe { &*HRTIM_COMMON::ptr() };
+                common.$usrcr().modify(|r, w| unsafe {
+                    w.bits(r.bits() & !(0b111 << $usrc) | (source as u32) << $usrc);
+                });
+            }
+        }
+    };
+}
+
+macro_rules! adc_trigger {
+    ($($TRIG:ident: $adcr:ident, $doc:expr, [$($ADC:ident = $ET:ident::$variant:ident),+], $usrc:expr,)+) => {
+        $(
+            adc_trigger_common!($TRIG, $doc, [$($ADC = $ET::$variant),+], cr1, $usrc);
+
+            impl $TRIG {
+                /// Have `source` generate this trigger, in addition to the other enabled sources
+                pub fn enable_source<S: AdcTriggerSource<$TRIG>>(&mut self, _source: &S) {
+                    let common = unsafe { &*HRTIM_COMMON::ptr() };
+                    common.$adcr().modify(|r, w| unsafe { w.bits(r.bits() | S::BITS); });
+                }
+
+                /// Stop `source` from generating this trigger
+                pub fn disable_source<S: AdcTriggerSource<$TRIG>>(&mut self, _source: &S) {
+                    let common = unsafe { &*HRTIM_COMMON::ptr() };
+                    common.$adcr().modify(|r, w| unsafe { w.bits(r.bits() & !S::BITS); });
+                }
+
+                /// Disable all sources of this trigger
+                pub fn clear_sources(&mut self) {
+                    let common = unsafe { &*HRTIM_COMMON::ptr() };
+                    common.$adcr().reset();
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! adc_trigger_extended {
+    ($($TRIG:ident: $trg:expr, $doc:expr, [$($ADC:ident = $ET:ident::$variant:ident),+], $usrc:expr,)+) => {
+        $(
+            adc_trigger_common!($TRIG, $doc, [$($ADC = $ET::$variant),+], adcur, $usrc);
+
+            impl $TRIG {
+                /// Have `source` generate this trigger, replacing the previous source
+                pub fn set_source<S: AdcTriggerSource<$TRIG>>(&mut self, _source: &S) {
+                    let common = unsafe { &*HRTIM_COMMON::ptr() };
+                    common.adcer().modify(|r, w| unsafe {
+                        w.bits(r.bits() & !(0b11111 << $trg) | S::BITS << $trg);
+                    });
+                }
+            }
+        )+
+    };
+}
+
+adc_trigger! {
+    AdcTrigger1: adc1r, "ADC trigger 1", [
+        ADC12 = ExternalTrigger12::Hrtim_adc_trg_1,
+        ADC345 = ExternalTrigger345::Hrtim_adc_trg_1
+    ], 16,
+    AdcTrigger2: adc2r, "ADC trigger 2", [
+        ADC345 = ExternalTrigger345::Hrtim_adc_trg_2
+    ], 19,
+    AdcTrigger3: adc3r, "ADC trigger 3", [
+        ADC12 = ExternalTrigger12::Hrtim_adc_trg_3,
+        ADC345 = ExternalTrigger345::Hrtim_adc_trg_3
+    ], 22,
+    AdcTrigger4: adc4r, "ADC trigger 4", [
+        ADC345 = ExternalTrigger345::Hrtim_adc_trg_4
+    ], 25,
+}
+
+adc_trigger_extended! {
+    AdcTrigger5: 0, "ADC trigger 5", [
+        ADC12 = ExternalTrigger12::Hrtim_adc_trg_5,
+        ADC345 = ExternalTrigger345::Hrtim_adc_trg_5
+    ], 0,
+    AdcTrigger6: 5, "ADC trigger 6", [
+        ADC12 = ExternalTrigger12::Hrtim_adc_trg_6,
+        ADC345 = ExternalTrigger345::Hrtim_adc_trg_6
+    ], 4,
+    AdcTrigger7: 10, "ADC trigger 7", [
+        ADC12 = ExternalTrigger12::Hrtim_adc_trg_7,
+        ADC345 = ExternalTrigger345::Hrtim_adc_trg_7
+    ], 8,
+    AdcTrigger8: 16, "ADC trigger 8", [
+        ADC12 = ExternalTrigger12::Hrtim_adc_trg_8,
+        ADC345 = ExternalTrigger345::Hrtim_adc_trg_8
+    ], 12,
+    AdcTrigger9: 21, "ADC trigger 9", [
+        ADC12 = ExternalTrigger12::Hrtim_adc_trg_9,
+        ADC345 = ExternalTrigger345::Hrtim_adc_trg_9
+    ], 16,
+    AdcTrigger10: 26, "ADC trigger 10", [
+        ADC12 = ExternalTrigger12::Hrtim_adc_trg_10,
+        ADC345 = ExternalTrigger345::Hrtim_adc_trg_10
+    ], 20,
+}
+
+// Each row is the bit of the source in ADC1R/ADC3R and in ADC2R/ADC4R, followed by its number
+// in the ADCER fields of triggers 5/7/9 and of triggers 6/8/10. `-` marks the triggers the
+// source can not be routed to.
+macro_rules! adc_trigger_sources {
+    ($($SRC:ident<$TIMX:ident>: $adc13:tt, $adc24:tt, $adc579:tt, $adc6810:tt;)+) => {
+        $(
+            adc_trigger_sources!(@source $SRC<$TIMX>, [AdcTrigger1, AdcTrigger3], $adc13, 1 <<);
+            adc_trigger_sources!(@source $SRC<$TIMX>, [AdcTrigger2, AdcTrigger4], $adc24, 1 <<);
+            adc_trigger_sources!(@source $SRC<$TIMX>, [AdcTrigger5, AdcTrigger7, AdcTrigger9], $adc579,);
+            adc_trigger_sources!(@source $SRC<$TIMX>, [AdcTrigger6, AdcTrigger8, AdcTrigger10], $adc6810,);
+        )+
+    };
+    (@source $SRC:ident<$TIMX:ident>, [$($TRIG:ident),+], -, $($shift:tt)*) => {};
+    (@source $SRC:ident<$TIMX:ident>, [$($TRIG:ident),+], $value:tt, $($shift:tt)*) => {
+        $(
+            impl<PSCL> AdcTriggerSource<$TRIG> for $SRC<$TIMX, PSCL> {
+                const BITS: u32 = $($shift)* $value;
+            }
+        )+
+    };
+}
+
+// The `HrTim` rows are the timer periods, the timer resets are not available as sources.
+// External events are routed in `external_event`.
+adc_trigger_sources! {
+    HrCr1<HRTIM_MASTER>: 0, 0, 0, 0;
+    HrCr2<HRTIM_MASTER>: 1, 1, 1, 1;
+    HrCr3<HRTIM_MASTER>: 2, 2, 2, 2;
+    HrCr4<HRTIM_MASTER>: 3, 3, 3, 3;
+    HrTim<HRTIM_MASTER>: 4, 4, 4, 4;
+
+    HrCr2<HRTIM_TIMA>: -, 10, -, 10;
+    HrCr3<HRTIM_TIMA>: 11, -, 10, -;
+    HrCr4<HRTIM_TIMA>: 12, 12, 11, 11;
+    HrTim<HRTIM_TIMA>: 13, 13, 12, 12;
+
+    HrCr2<HRTIM_TIMB>: -, 14, -, 13;
+    HrCr3<HRTIM_TIMB>: 16, -, 14, -;
+    HrCr4<HRTIM_TIMB>: 17, 16, 15, 14;
+    HrTim<HRTIM_TIMB>: 18, 17, 16, 15;
+
+    HrCr2<HRTIM_TIMC>: -, 18, -, 16;
+    HrCr3<HRTIM_TIMC>: 21, -, 18, -;
+    HrCr4<HRTIM_TIMC>: 22, 20, 19, 17;
+    HrTim<HRTIM_TIMC>: 23, 21, 20, 18;
+
+    HrCr2<HRTIM_TIMD>: -, 23, -, 20;
+    HrCr3<HRTIM_TIMD>: 25, -, 21, -;
+    HrCr4<HRTIM_TIMD>: 26, 25, 22, 21;
+    HrTim<HRTIM_TIMD>: 27, 26, 23, 22;
+
+    HrCr2<HRTIM_TIME>: -, 28, -, 24;
+    HrCr3<HRTIM_TIME>: 29, 29, 24, 25;
+    HrCr4<HRTIM_TIME>: 30, 30, 25, 26;
+    HrTim<HRTIM_TIME>: 31, -, 26, -;
+
+    HrCr2<HRTIM_TIMF>: 10, 11, 27, 28;
+    HrCr3<HRTIM_TIMF>: 15, 15, 28, 29;
+    HrCr4<HRTIM_TIMF>: 20, 19, 29, 30;
+    HrTim<HRTIM_TIMF>: 24, 24, 30, 31;
+}
@@ -0,0 +1,134 @@
+//! HRTIM compare registers
+
+use core::marker::PhantomData;
+
+use super::{output::HrOutputTimer, EventSource, HrtimPrescaler};
+use crate::stm32::{
+    HRTIM_MASTER, HRTIM_TIMA, HRTIM_TIMB, HRTIM_TIMC, HRTIM_TIMD, HRTIM_TIME, HRTIM_TIMF,
+};
+
+/// Access to a compare register of a HRTIM timer
+pub trait HrCompareRegister {
+    /// Returns the compare value
+    fn get_duty(&self) -> u16;
+
+    /// Set the compare value, clamped to the range valid for the timer's prescaler
+    fn set_duty(&mut self, duty: u16);
+}
+
+/// Compare register 1
+pub struct HrCr1<TIM, PSCL>(PhantomData<(TIM, PSCL)>);
+/// Compare register 2
+pub struct HrCr2<TIM, PSCL>(PhantomData<(TIM, PSCL)>);
+/// Compare register 3
+pub struct HrCr3<TIM, PSCL>(PhantomData<(TIM, PSCL)>);
+/// Compare register 4
+pub struct HrCr4<TIM, PSCL>(PhantomData<(TIM, PSCL)>);
+
+macro_rules! hrtim_cr {
+    ($($TIMX:ident: [$(($CR:ident, $cmpr:ident, $cmp:ident),)+],)+) => {
+        $($(
+            impl<PSCL> $CR<$TIMX, PSCL> {
+                pub(crate) fn new() -> Self {
+                    $CR(PhantomData)
+                }
+            }
+
+            impl<PSCL> crate::Sealed for $CR<$TIMX, PSCL> {}
+
+            impl<PSCL: HrtimPrescaler> HrCompareRegister for $CR<$TIMX, PSCL> {
+                fn get_duty(&self) -> u16 {
+                    let tim = unsafe { &*$TIMX::ptr() };
+                    tim.$cmpr().read().$cmp().bits()
+                }
+
+                fn set_duty(&mut self, duty: u16) {
+                    let tim = unsafe { &*$TIMX::ptr() };
+                    let duty = duty.clamp(PSCL::MIN_CR, PSCL::MAX_CR);
+                    tim.$cmpr().write(|w| unsafe { w.$cmp().bits(duty); });
+                }
+            }
+        )+)+
+    };
+}
+
+hrtim_cr! {
+    HRTIM_MASTER: [
+        (HrCr1, mcmp1r, mcmp1),
+        (HrCr2, mcmp2r, mcmp2),
+        (HrCr3, mcmp3r, mcmp3),
+        (HrCr4, mcmp4r, mcmp4),
+    ],
+    HRTIM_TIMA: [
+        (HrCr1, cmp1ar, cmp1x),
+        (HrCr2, cmp2ar, cmp2x),
+        (HrCr3, cmp3ar, cmp3x),
+        (HrCr4, cmp4ar, cmp4x),
+    ],
+    HRTIM_TIMB: [
+        (HrCr1, cmp1br, cmp1x),
+        (HrCr2, cmp2br, cmp2x),
+        (HrCr3, cmp3br, cmp3x),
+        (HrCr4, cmp4br, cmp4x),
+    ],
+    HRTIM_TIMC: [
+        (HrCr1, cmp1cr, cmp1x),
+        (HrCr2, cmp2cr, cmp2x),
+        (HrCr3, cmp3cr, cmp3x),
+        (HrCr4, cmp4cr, cmp4x),
+    ],
+    HRTIM_TIMD: [
+        (HrCr1, cmp1dr, cmp1x),
+        (HrCr2, cmp2dr, cmp2x),
+        (HrCr3, cmp3dr, cmp3x),
+        (HrCr4, cmp4dr, cmp4x),
+    ],
+    HRTIM_TIME: [
+        (HrCr1, cmp1er, cmp1x),
+        (HrCr2, cmp2er, cmp2x),
+        (HrCr3, cmp3er, cmp3x),
+        (HrCr4, cmp4er, cmp4x),
+    ],
+    HRTIM_TIMF: [
+        (HrCr1, cmp1fr, cmp1x),
+        (HrCr2, cmp2fr, cmp2x),
+        (HrCr3, cmp3fr, cmp3x),
+        (HrCr4, cmp4fr, cmp4x),
+    ],
+}
+
+// A compare match of a timing unit may only affect the outputs of that unit
+macro_rules! cr_event {
+    ($($TIMX:ident),+) => {
+        $(
+            impl<PSCL> EventSource<$TIMX> for HrCr1<$TIMX, PSCL> {
+                const BITS: u32 = 1 << 3;
+            }
+            impl<PSCL> EventSource<$TIMX> for HrCr2<$TIMX, PSCL> {
+                const BITS: u32 = 1 << 4;
+            }
+            impl<PSCL> EventSource<$TIMX> for HrCr3<$TIMX, PSCL> {
+                const BITS: u32 = 1 << 5;
+            }
+            impl<PSCL> EventSource<$TIMX> for HrCr4<$TIMX, PSCL> {
+                const BITS: u32 = 1 << 6;
+            }
+        )+
+    };
+}
+
+cr_event!(HRTIM_TIMA, HRTIM_TIMB, HRTIM_TIMC, HRTIM_TIMD, HRTIM_TIME, HRTIM_TIMF);
+
+// The master compare matches may affect all outputs
+impl<DST: HrOutputTimer, PSCL> EventSource<DST> for HrCr1<HRTIM_MASTER, PSCL> {
+    const BITS: u32 = 1 << 8;
+}
+impl<DST: HrOutputTimer, PSCL> EventSource<DST> for HrCr2<HRTIM_MASTER, PSCL> {
+    const BITS: u32 = 1 << 9;
+}
+impl<DST: HrOutputTimer, PSCL> EventSource<DST> for HrCr3<HRTIM_MASTER, PSCL> {
+    const BITS: u32 = 1 << 10;
+}
+impl<DST: HrOutputTimer, PSCL> EventSource<DST> for HrCr4<HRTIM_MASTER, PSCL> {
+    const BITS: u32 = 1 << 11;
+}
@@ -0,0 +1,88 @@
+//! HRTIM deadtime generator
+
+/// Deadtime configuration of a timing unit
+///
+/// When enabled, output 2 is the complement of output 1 with the rising edges of both outputs
+/// delayed. The deadtimes are given in steps of `t_DTG`, see [DeadtimePrescaler].
+#[derive(Copy, Clone, Debug)]
+pub struct DeadtimeConfig {
+    pub(super) prescaler: DeadtimePrescaler,
+    pub(super) deadtime_rising_value: u16,
+    pub(super) deadtime_rising_sign: bool,
+    pub(super) deadtime_falling_value: u16,
+    pub(super) deadtime_falling_sign: bool,
+}
+
+impl DeadtimeConfig {
+    /// See RM0440 Table 221 'Deadtime resolution and max absolute values'
+    pub fn prescaler(mut self, value: DeadtimePrescaler) -> Self {
+        self.prescaler = value;
+        self
+    }
+
+    /// Panic if value can not fit in 9 bits
+    pub fn deadtime_rising_value(mut self, value: u16) -> Self {
+        // 9 bits
+        assert!(value < (1 << 9));
+
+        self.deadtime_rising_value = value;
+
+        self
+    }
+
+    /// Negate the rising deadtime, overlapping the output edges instead of separating them
+    pub fn deadtime_rising_sign(mut self, is_negative: bool) -> Self {
+        self.deadtime_rising_sign = is_negative;
+        self
+    }
+
+    /// Panic if value can not fit in 9 bits
+    pub fn deadtime_falling_value(mut self, value: u16) -> Self {
+        // 9 bits
+        assert!(value < (1 << 9));
+
+        self.deadtime_falling_value = value;
+
+        self
+    }
+
+    /// Negate the falling deadtime, overlapping the output edges instead of separating them
+    pub fn deadtime_falling_sign(mut self, is_negative: bool) -> Self {
+        self.deadtime_falling_sign = is_negative;
+        self
+    }
+}
+
+impl Default for DeadtimeConfig {
+    /// About 1us of deadtime at `f_HRTIM = 170MHz`
+    fn default() -> Self {
+        Self {
+            prescaler: DeadtimePrescaler::Thrtim,
+            deadtime_rising_value: 170,
+            deadtime_rising_sign: false,
+            deadtime_falling_value: 170,
+            deadtime_falling_sign: false,
+        }
+    }
+}
+
+/// Deadtime generator clock period `t_DTG` relative to `t_HRTIM = 1 / f_HRTIM`
+#[derive(Copy, Clone, Debug)]
+pub enum DeadtimePrescaler {
+    /// `t_DTG = t_HRTIM / 8`
+    ThrtimDiv8 = 0b000,
+    /// `t_DTG = t_HRTIM / 4`
+    ThrtimDiv4 = 0b001,
+    /// `t_DTG = t_HRTIM / 2`
+    ThrtimDiv2 = 0b010,
+    /// `t_DTG = t_HRTIM`
+    Thrtim = 0b011,
+    /// `t_DTG = t_HRTIM * 2`
+    ThrtimMul2 = 0b100,
+    /// `t_DTG = t_HRTIM * 4`
+    ThrtimMul4 = 0b101,
+    /// `t_DTG = t_HRTIM * 8`
+    ThrtimMul8 = 0b110,
+    /// `t_DTG = t_HRTIM * 16`
+    ThrtimMul16 = 0b111,
+}
@@ -0,0 +1,286 @@
+//! HRTIM external events
+//!
+//! External events can set or reset outputs through the crossbar and trigger the ADC, see
+//! [ExternalEventSource].
+
+use core::marker::PhantomData;
+
+use super::adc_trigger::{
+    AdcTrigger1, AdcTrigger10, AdcTrigger2, AdcTrigger3, AdcTrigger4, AdcTrigger5, AdcTrigger6,
+    AdcTrigger7, AdcTrigger8, AdcTrigger9, AdcTriggerSource,
+};
+use super::{output::HrOutputTimer, EventSource, HrPwmControl};
+use crate::gpio::gpiob::{PB3, PB4, PB5, PB6, PB7, PB8, PB9};
+use crate::gpio::gpioc::{PC11, PC12, PC5, PC6};
+use crate::gpio::{Alternate, SignalEdge, AF13, AF3};
+use crate::pwm::Polarity;
+use crate::stm32::HRTIM_COMMON;
+
+/// What an external event reacts to
+pub enum EdgeOrPolarity {
+    /// The event is active for as long as the input is at the active level
+    Polarity(Polarity),
+    /// The event fires on an edge of the input
+    Edge(SignalEdge),
+}
+
+/// Digital filter of external events 6 to 10, `N` consecutive equal samples are needed to
+/// validate a level change
+#[derive(Copy, Clone, Debug)]
+pub enum EevSamplingFilter {
+    /// No filtering
+    None = 0b0000,
+    /// f_SAMPLING = f_HRTIM, N = 2
+    HrtimN2 = 0b0001,
+    /// f_SAMPLING = f_HRTIM, N = 4
+    HrtimN4 = 0b0010,
+    /// f_SAMPLING = f_HRTIM, N = 8
+    HrtimN8 = 0b0011,
+    /// f_SAMPLING = f_EEVS / 2, N = 6
+    EevsDiv2N6 = 0b0100,
+    /// f_SAMPLING = f_EEVS / 2, N = 8
+    EevsDiv2N8 = 0b0101,
+    /// f_SAMPLING = f_EEVS / 4, N = 6
+    EevsDiv4N6 = 0b0110,
+    /// f_SAMPLING = f_EEVS / 4, N = 8
+    EevsDiv4N8 = 0b0111,
+    /// f_SAMPLING = f_EEVS / 8, N = 6
+    EevsDiv8N6 = 0b1000,
+    /// f_SAMPLING = f_EEVS / 8, N = 8
+    EevsDiv8N8 = 0b1001,
+    /// f_SAMPLING = f_EEVS / 16, N = 5
+    EevsDiv16N5 = 0b1010,
+    /// f_SAMPLING = f_EEVS / 16, N = 6
+    EevsDiv16N6 = 0b1011,
+    /// f_SAMPLING = f_EEVS / 16, N = 8
+    EevsDiv16N8 = 0b1100,
+    /// f_SAMPLING = f_EEVS / 32, N = 5
+    EevsDiv32N5 = 0b1101,
+    /// f_SAMPLING = f_EEVS / 32, N = 6
+    EevsDiv32N6 = 0b1110,
+    /// f_SAMPLING = f_EEVS / 32, N = 8
+    EevsDiv32N8 = 0b1111,
+}
+
+/// EevPin is a trait that marks which GPIO pins may be used as HRTIM external event `N`; it should not be directly used.
+/// See the device datasheet 'Pin descriptions' chapter for which pins can be used (or look at Implementors)
+pub trait EevPin<const N: u8> {}
+
+macro_rules! eev_pins {
+    ($($N:literal => $PIN:ident<$AF:ident>,)+) => {
+        $(
+            impl EevPin<$N> for $PIN<Alternate<$AF>> {}
+        )+
+    };
+}
+
+eev_pins! {
+    1 => PC12<AF3>,
+    2 => PC11<AF3>,
+    3 => PB7<AF13>,
+    4 => PB6<AF13>,
+    5 => PB9<AF13>,
+    6 => PB5<AF13>,
+    7 => PB4<AF13>,
+    8 => PB8<AF13>,
+    9 => PB3<AF13>,
+    10 => PC5<AF13>,
+    10 => PC6<AF3>,
+}
+
+/// The ten external event inputs, handed out once the HRTIM is calibrated
+pub struct ExternalEventInputs {
+    /// External event 1
+    pub eev_input1: EevInput<1>,
+    /// External event 2
+    pub eev_input2: EevInput<2>,
+    /// External event 3
+    pub eev_input3: EevInput<3>,
+    /// External event 4
+    pub eev_input4: EevInput<4>,
+    /// External event 5
+    pub eev_input5: EevInput<5>,
+    /// External event 6
+    pub eev_input6: EevInput<6>,
+    /// External event 7
+    pub eev_input7: EevInput<7>,
+    /// External event 8
+    pub eev_input8: EevInput<8>,
+    /// External event 9
+    pub eev_input9: EevInput<9>,
+    /// External event 10
+    pub eev_input10: EevInput<10>,
+}
+
+impl ExternalEventInputs {
+    pub(super) fn new() -> Self {
+        ExternalEventInputs {
+            eev_input1: EevInput { _x: PhantomData },
+            eev_input2: EevInput { _x: PhantomData },
+            eev_input3: EevInput { _x: PhantomData },
+            eev_input4: EevInput { _x: PhantomData },
+            eev_input5: EevInput { _x: PhantomData },
+            eev_input6: EevInput { _x: PhantomData },
+            eev_input7: EevInput { _x: PhantomData },
+            eev_input8: EevInput { _x: PhantomData },
+            eev_input9: EevInput { _x: PhantomData },
+            eev_input10: EevInput { _x: PhantomData },
+        }
+    }
+}
+
+/// An unconfigured external event input
+pub struct EevInput<const N: u8> {
+    _x: PhantomData<()>,
+}
+
+impl<const N: u8> EevInput<N> {
+    /// Use `pin` as the source of this external event
+    pub fn bind_pin<PIN: EevPin<N>>(self, _pin: PIN) -> SourceBuilder<N> {
+        SourceBuilder::new(0b00)
+    }
+
+    /// Use one of the internal sources `1..=3` of this external event
+    ///
+    /// See RM0440 Table 222 'External event mapping and associated features' for the sources
+    /// of each event, e.g. source 1 of external event 1 is `COMP2`.
+    pub fn bind_internal(self, src: u8) -> SourceBuilder<N> {
+        assert!((1..=3).contains(&src));
+        SourceBuilder::new(src)
+    }
+}
+
+/// Builder for the configuration of an external event
+pub struct SourceBuilder<const N: u8> {
+    src: u8,
+    edge_or_polarity: EdgeOrPolarity,
+    fast: bool,
+    filter: EevSamplingFilter,
+}
+
+impl<const N: u8> SourceBuilder<N> {
+    fn new(src: u8) -> Self {
+        SourceBuilder {
+            src,
+            edge_or_polarity: EdgeOrPolarity::Polarity(Polarity::ActiveHigh),
+            fast: false,
+            filter: EevSamplingFilter::None,
+        }
+    }
+
+    /// Set whether the event is level or edge sensitive
+    pub fn edge_or_polarity(mut self, edge_or_polarity: EdgeOrPolarity) -> Self {
+        self.edge_or_polarity = edge_or_polarity;
+        self
+    }
+
+    /// Finalize the configuration of the external event
+    pub fn finalize(self, _control: &mut HrPwmControl) -> ExternalEventSource<N> {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+
+        let (pol, sns) = match self.edge_or_polarity {
+            EdgeOrPolarity::Polarity(Polarity::ActiveHigh) => (0, 0b00),
+            EdgeOrPolarity::Polarity(Polarity::ActiveLow) => (1, 0b00),
+            EdgeOrPolarity::Edge(SignalEdge::Rising) => (0, 0b01),
+            EdgeOrPolarity::Edge(SignalEdge::Falling) => (0, 0b10),
+            EdgeOrPolarity::Edge(SignalEdge::RisingFalling) => (0, 0b11),
+        };
+
+        // EECR1 holds events 1 to 5 and EECR2 events 6 to 10, six bits each:
+        // bits 0..=1 EExSRC, bit 2 EExPOL, bits 3..=4 EExSNS, bit 5 EExFAST
+        let shift = 6 * ((N as u32 - 1) % 5);
+        let config = (self.src as u32 | pol << 2 | sns << 3 | (self.fast as u32) << 5) << shift;
+
+        if N <= 5 {
+            common.eecr1().modify(|r, w| unsafe { w.bits(r.bits() & !(0x3F << shift) | config); });
+        } else {
+            // EECR3 holds the filters of events 6 to 10, six bits apart
+            common.eecr3().modify(|r, w| unsafe {
+                w.bits(r.bits() & !(0xF << shift) | (self.filter as u32) << shift);
+            });
+            common.eecr2().modify(|r, w| unsafe { w.bits(r.bits() & !(0x3F << shift) | config); });
+        }
+
+        ExternalEventSource { _x: PhantomData }
+    }
+}
+
+macro_rules! fast_events {
+    ($($N:literal),+) => {
+        $(
+            impl SourceBuilder<$N> {
+                /// Enable the low latency mode, bypassing the resynchronization stage
+                ///
+                /// Only available for external events 1 to 5.
+                pub fn fast(mut self) -> Self {
+                    self.fast = true;
+                    self
+                }
+            }
+        )+
+    };
+}
+
+fast_events!(1, 2, 3, 4, 5);
+
+macro_rules! filtered_events {
+    ($($N:literal),+) => {
+        $(
+            impl SourceBuilder<$N> {
+                /// Set the digital filter
+                ///
+                /// Only available for external events 6 to 10.
+                pub fn filter(mut self, filter: EevSamplingFilter) -> Self {
+                    self.filter = filter;
+                    self
+                }
+            }
+        )+
+    };
+}
+
+filtered_events!(6, 7, 8, 9, 10);
+
+/// A configured external event
+pub struct ExternalEventSource<const N: u8> {
+    _x: PhantomData<()>,
+}
+
+impl<const N: u8> crate::Sealed for ExternalEventSource<N> {}
+
+// External events may set or reset all outputs
+impl<DST: HrOutputTimer, const N: u8> EventSource<DST> for ExternalEventSource<N> {
+    const BITS: u32 = 1 << (20 + N as u32);
+}
+
+// ADC triggers 1, 3, 5, 7 and 9 may use events 1 to 5, the other triggers events 6 to 10. The
+// bit of an event in the ADCxR registers is also its number in the ADCER fields.
+macro_rules! eev_adc_trigger {
+    ($($N:literal: $bit:expr => [$($TRIG:ident),+], [$($TRIGX:ident),+],)+) => {
+        $(
+            $(
+                impl AdcTriggerSource<$TRIG> for ExternalEventSource<$N> {
+                    const BITS: u32 = 1 << $bit;
+                }
+            )+
+            $(
+                impl AdcTriggerSource<$TRIGX> for ExternalEventSource<$N> {
+                    const BITS: u32 = $bit;
+                }
+            )+
+        )+
+    };
+}
+
+eev_adc_trigger! {
+    1: 5 => [AdcTrigger1, AdcTrigger3], [AdcTrigger5, AdcTrigger7, AdcTrigger9],
+    2: 6 => [AdcTrigger1, AdcTrigger3], [AdcTrigger5, AdcTrigger7, AdcTrigger9],
+    3: 7 => [AdcTrigger1, AdcTrigger3], [AdcTrigger5, AdcTrigger7, AdcTrigger9],
+    4: 8 => [AdcTrigger1, AdcTrigger3], [AdcTrigger5, AdcTrigger7, AdcTrigger9],
+    5: 9 => [AdcTrigger1, AdcTrigger3], [AdcTrigger5, AdcTrigger7, AdcTrigger9],
+    6: 5 => [AdcTrigger2, AdcTrigger4], [AdcTrigger6, AdcTrigger8, AdcTrigger10],
+    7: 6 => [AdcTrigger2, AdcTrigger4], [AdcTrigger6, AdcTrigger8, AdcTrigger10],
+    8: 7 => [AdcTrigger2, AdcTrigger4], [AdcTrigger6, AdcTrigger8, AdcTrigger10],
+    9: 8 => [AdcTrigger2, AdcTrigger4], [AdcTrigger6, AdcTrigger8, AdcTrigger10],
+    10: 9 => [AdcTrigger2, AdcTrigger4], [AdcTrigger6, AdcTrigger8, AdcTrigger10],
+}
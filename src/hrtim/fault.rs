@@ -0,0 +1,230 @@
+//! HRTIM fault inputs
+//!
+//! A fault input which is enabled for a timing unit immediately puts its outputs in their fault
+//! state, see [FaultAction]. The outputs stay there until they are re-enabled, which only
+//! succeeds once the fault condition is gone.
+
+use core::marker::PhantomData;
+
+use super::HrPwmControl;
+use crate::gpio::gpioa::{PA12, PA15};
+use crate::gpio::gpiob::{PB0, PB10, PB11};
+use crate::gpio::gpioc::{PC10, PC7};
+use crate::gpio::{Alternate, AF13, AF3};
+use crate::pwm::Polarity;
+use crate::stm32::HRTIM_COMMON;
+
+/// State of an output while a fault is active
+#[derive(Copy, Clone, Debug)]
+pub enum FaultAction {
+    /// The output is not affected by the fault
+    NoAction = 0b00,
+    /// The output is forced to its active level
+    ForceActive = 0b01,
+    /// The output is forced to its inactive level
+    ForceInactive = 0b10,
+    /// The output is put in high impedance
+    HighZ = 0b11,
+}
+
+/// Digital filter of a fault input, `N` consecutive equal samples are needed to validate a
+/// level change
+#[derive(Copy, Clone, Debug)]
+pub enum FaultSamplingFilter {
+    /// No filtering, fault acts asynchronously
+    None = 0b0000,
+    /// f_SAMPLING = f_HRTIM, N = 2
+    HrtimN2 = 0b0001,
+    /// f_SAMPLING = f_HRTIM, N = 4
+    HrtimN4 = 0b0010,
+    /// f_SAMPLING = f_HRTIM, N = 8
+    HrtimN8 = 0b0011,
+    /// f_SAMPLING = f_FLTS / 2, N = 6
+    FltsDiv2N6 = 0b0100,
+    /// f_SAMPLING = f_FLTS / 2, N = 8
+    FltsDiv2N8 = 0b0101,
+    /// f_SAMPLING = f_FLTS / 4, N = 6
+    FltsDiv4N6 = 0b0110,
+    /// f_SAMPLING = f_FLTS / 4, N = 8
+    FltsDiv4N8 = 0b0111,
+    /// f_SAMPLING = f_FLTS / 8, N = 6
+    FltsDiv8N6 = 0b1000,
+    /// f_SAMPLING = f_FLTS / 8, N = 8
+    FltsDiv8N8 = 0b1001,
+    /// f_SAMPLING = f_FLTS / 16, N = 5
+    FltsDiv16N5 = 0b1010,
+    /// f_SAMPLING = f_FLTS / 16, N = 6
+    FltsDiv16N6 = 0b1011,
+    /// f_SAMPLING = f_FLTS / 16, N = 8
+    FltsDiv16N8 = 0b1100,
+    /// f_SAMPLING = f_FLTS / 32, N = 5
+    FltsDiv32N5 = 0b1101,
+    /// f_SAMPLING = f_FLTS / 32, N = 6
+    FltsDiv32N6 = 0b1110,
+    /// f_SAMPLING = f_FLTS / 32, N = 8
+    FltsDiv32N8 = 0b1111,
+}
+
+/// FaultPin is a trait that marks which GPIO pins may be used as HRTIM fault input `N`; it should not be directly used.
+/// See the device datasheet 'Pin descriptions' chapter for which pins can be used (or look at Implementors)
+pub trait FaultPin<const N: u8> {}
+
+macro_rules! fault_pins {
+    ($($N:literal => $PIN:ident<$AF:ident>,)+) => {
+        $(
+            impl FaultPin<$N> for $PIN<Alternate<$AF>> {}
+        )+
+    };
+}
+
+fault_pins! {
+    1 => PA12<AF13>,
+    2 => PA15<AF13>,
+    3 => PB10<AF13>,
+    4 => PB11<AF13>,
+    5 => PB0<AF13>,
+    5 => PC7<AF3>,
+    6 => PC10<AF13>,
+}
+
+/// The six fault inputs, handed out once the HRTIM is calibrated
+pub struct FaultInputs {
+    /// Fault input 1, pin FLT1 or COMP2
+    pub fault_input1: FaultInput<1>,
+    /// Fault input 2, pin FLT2 or COMP4
+    pub fault_input2: FaultInput<2>,
+    /// Fault input 3, pin FLT3 or COMP6
+    pub fault_input3: FaultInput<3>,
+    /// Fault input 4, pin FLT4 or COMP1
+    pub fault_input4: FaultInput<4>,
+    /// Fault input 5, pin FLT5 or COMP3
+    pub fault_input5: FaultInput<5>,
+    /// Fault input 6, pin FLT6 or COMP5
+    pub fault_input6: FaultInput<6>,
+}
+
+impl FaultInputs {
+    pub(super) fn new() -> Self {
+        FaultInputs {
+            fault_input1: FaultInput { _x: PhantomData },
+            fault_input2: FaultInput { _x: PhantomData },
+            fault_input3: FaultInput { _x: PhantomData },
+            fault_input4: FaultInput { _x: PhantomData },
+            fault_input5: FaultInput { _x: PhantomData },
+            fault_input6: FaultInput { _x: PhantomData },
+        }
+    }
+}
+
+/// An unconfigured fault input
+pub struct FaultInput<const N: u8> {
+    _x: PhantomData<()>,
+}
+
+impl<const N: u8> FaultInput<N> {
+    /// Use `pin` as the source of this fault input
+    pub fn bind_pin<PIN: FaultPin<N>>(self, _pin: PIN) -> SourceBuilder<N> {
+        SourceBuilder::new(0b00)
+    }
+
+    /// Use the internal comparator as the source of this fault input
+    ///
+    /// See the documentation of [FaultInputs] for which comparator is connected to which input.
+    pub fn bind_comp(self) -> SourceBuilder<N> {
+        SourceBuilder::new(0b01)
+    }
+}
+
+/// Builder for the configuration of a fault input
+pub struct SourceBuilder<const N: u8> {
+    src: u8,
+    polarity: Polarity,
+    filter: FaultSamplingFilter,
+}
+
+impl<const N: u8> SourceBuilder<N> {
+    fn new(src: u8) -> Self {
+        SourceBuilder {
+            src,
+            polarity: Polarity::ActiveHigh,
+            filter: FaultSamplingFilter::None,
+        }
+    }
+
+    /// Set the level at which the fault is active
+    pub fn polarity(mut self, polarity: Polarity) -> Self {
+        self.polarity = polarity;
+        self
+    }
+
+    /// Set the digital filter
+    pub fn filter(mut self, filter: FaultSamplingFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Configure and enable the fault input
+    pub fn finalize(self, _control: &mut HrPwmControl) -> FaultSource<N> {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+
+        // FLTINR1 holds inputs 1 to 4 and FLTINR2 inputs 5 and 6, one byte each:
+        // bit 0 FLTxE, bit 1 FLTxP, bit 2 FLTxSRC[0], bits 3..=6 FLTxF, bit 7 FLTxLCK.
+        // FLTINR2 bits 16..=21 hold FLTxSRC[1] of all inputs.
+        let shift = 8 * ((N as u32 - 1) % 4);
+        let config = ((matches!(self.polarity, Polarity::ActiveHigh) as u32) << 1
+            | ((self.src as u32) & 1) << 2
+            | (self.filter as u32) << 3)
+            << shift;
+
+        let src_hi = ((self.src as u32) >> 1) << (16 + N as u32 - 1);
+        common
+            .fltinr2()
+            .modify(|r, w| unsafe { w.bits(r.bits() & !(1 << (16 + N as u32 - 1)) | src_hi); });
+
+        // The configuration may only be changed while the input is disabled
+        if N <= 4 {
+            common.fltinr1().modify(|r, w| unsafe { w.bits(r.bits() & !(0xFF << shift) | config); });
+            common.fltinr1().modify(|r, w| unsafe { w.bits(r.bits() | (1 << shift)); });
+        } else {
+            common.fltinr2().modify(|r, w| unsafe { w.bits(r.bits() & !(0xFF << shift) | config); });
+            common.fltinr2().modify(|r, w| unsafe { w.bits(r.bits() | (1 << shift)); });
+        }
+
+        FaultSource { _x: PhantomData }
+    }
+}
+
+/// A configured fault input, see [HrPwmBuilder::with_fault_source](super::HrPwmBuilder::with_fault_source)
+pub struct FaultSource<const N: u8> {
+    _x: PhantomData<()>,
+}
+
+impl<const N: u8> FaultSource<N> {
+    // FLT1..FLT5 occupy bits 0..=4 of ISR/ICR, bit 5 is the system fault
+    const FLAG: u32 = if N == 6 { 1 << 6 } else { 1 << (N - 1) };
+
+    /// Returns true if the fault has occurred since the flag was last cleared
+    pub fn is_fault_active(&self) -> bool {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        common.isr().read().bits() & Self::FLAG != 0
+    }
+
+    /// Clear the fault flag
+    ///
+    /// The affected outputs must be re-enabled separately.
+    pub fn clear_fault(&mut self) {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        common.icr().write(|w| unsafe { w.bits(Self::FLAG); });
+    }
+
+    /// Lock the configuration of this fault input until the next reset
+    pub fn lock(&mut self) {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        let bit = 1 << (8 * ((N as u32 - 1) % 4) + 7);
+        if N <= 4 {
+            common.fltinr1().modify(|r, w| unsafe { w.bits(r.bits() | bit); });
+        } else {
+            common.fltinr2().modify(|r, w| unsafe { w.bits(r.bits() | bit); });
+        }
+    }
+}
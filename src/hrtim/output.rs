@@ -0,0 +1,190 @@
+//! HRTIM outputs and the set/reset crossbar
+
+use core::marker::PhantomData;
+
+use super::EventSource;
+use crate::gpio::gpioa::{PA10, PA11, PA8, PA9};
+use crate::gpio::gpiob::{PB12, PB13, PB14, PB15};
+use crate::gpio::gpioc::{PC6, PC7, PC8, PC9};
+use crate::gpio::{Alternate, AF13, AF3};
+use crate::stm32::{
+    HRTIM_COMMON, HRTIM_TIMA, HRTIM_TIMB, HRTIM_TIMC, HRTIM_TIMD, HRTIM_TIME, HRTIM_TIMF,
+};
+
+/// Marks the HRTIM timing units, which have outputs
+pub trait HrOutputTimer: crate::Sealed {}
+
+/// State of an output
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum State {
+    /// The output is disabled and in its idle state
+    Idle,
+    /// The output is enabled and driven by the crossbar
+    Running,
+    /// The output was disabled by a fault and is in its fault state
+    Fault,
+}
+
+/// Output 1 of a timing unit
+pub struct HrOut1<TIM, PSCL> {
+    _tim: PhantomData<TIM>,
+    _prescaler: PhantomData<PSCL>,
+}
+
+/// Output 2 of a timing unit
+pub struct HrOut2<TIM, PSCL> {
+    _tim: PhantomData<TIM>,
+    _prescaler: PhantomData<PSCL>,
+}
+
+/// ToHrOut is a trait that marks which GPIO pins may be used as HRTIM outputs; it should not be directly used.
+/// See the device datasheet 'Pin descriptions' chapter for which pins can be used with which timer outputs (or look at Implementors)
+pub trait ToHrOut<TIM> {
+    /// The outputs handed out for these pins
+    type Out<PSCL>;
+
+    #[doc(hidden)]
+    fn out<PSCL>() -> Self::Out<PSCL>;
+}
+
+impl<TIM, PA, PB> ToHrOut<TIM> for (PA, PB)
+where
+    PA: ToHrOut<TIM>,
+    PB: ToHrOut<TIM>,
+{
+    type Out<PSCL> = (PA::Out<PSCL>, PB::Out<PSCL>);
+
+    fn out<PSCL>() -> Self::Out<PSCL> {
+        (PA::out(), PB::out())
+    }
+}
+
+macro_rules! hrtim_out {
+    ($($TIMX:ident: $OUT:ident, $setr:ident, $rstr:ident, $oen_bit:expr,)+) => {
+        $(
+            impl<PSCL> $OUT<$TIMX, PSCL> {
+                /// Set the output active on `event`
+                pub fn enable_set_event<ES: EventSource<$TIMX>>(&mut self, _event: &ES) {
+                    let tim = unsafe { &*$TIMX::ptr() };
+                    tim.$setr().modify(|r, w| unsafe { w.bits(r.bits() | ES::BITS); });
+                }
+
+                /// Stop setting the output active on `event`
+                pub fn disable_set_event<ES: EventSource<$TIMX>>(&mut self, _event: &ES) {
+                    let tim = unsafe { &*$TIMX::ptr() };
+                    tim.$setr().modify(|r, w| unsafe { w.bits(r.bits() & !ES::BITS); });
+                }
+
+                /// Set the output inactive on `event`
+                pub fn enable_rst_event<ES: EventSource<$TIMX>>(&mut self, _event: &ES) {
+                    let tim = unsafe { &*$TIMX::ptr() };
+                    tim.$rstr().modify(|r, w| unsafe { w.bits(r.bits() | ES::BITS); });
+                }
+
+                /// Stop setting the output inactive on `event`
+                pub fn disable_rst_event<ES: EventSource<$TIMX>>(&mut self, _event: &ES) {
+                    let tim = unsafe { &*$TIMX::ptr() };
+                    tim.$rstr().modify(|r, w| unsafe { w.bits(r.bits() & !ES::BITS); });
+                }
+
+                /// Enable the output, this also resumes an output stopped by a fault
+                pub fn enable(&mut self) {
+                    let common = unsafe { &*HRTIM_COMMON::ptr() };
+                    common.oenr().write(|w| unsafe { w.bits(1 << $oen_bit); });
+                }
+
+                /// Disable the output, putting it in its idle state
+                pub fn disable(&mut self) {
+                    let common = unsafe { &*HRTIM_COMMON::ptr() };
+                    common.odisr().write(|w| unsafe { w.bits(1 << $oen_bit); });
+                }
+
+                /// Returns the state of the output
+                pub fn get_state(&self) -> State {
+                    let common = unsafe { &*HRTIM_COMMON::ptr() };
+                    let enabled = common.oenr().read().bits() & (1 << $oen_bit) != 0;
+                    let disabled_in_fault = common.odsr().read().bits() & (1 << $oen_bit) != 0;
+
+                    match (enabled, disabled_in_fault) {
+                        (true, _) => State::Running,
+                        (false, false) => State::Idle,
+                        (false, true) => State::Fault,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+hrtim_out! {
+    HRTIM_TIMA: HrOut1, seta1r, rsta1r, 0,
+    HRTIM_TIMA: HrOut2, seta2r, rsta2r, 1,
+
+    HRTIM_TIMB: HrOut1, setb1r, rstb1r, 2,
+    HRTIM_TIMB: HrOut2, setb2r, rstb2r, 3,
+
+    HRTIM_TIMC: HrOut1, setc1r, rstc1r, 4,
+    HRTIM_TIMC: HrOut2, setc2r, rstc2r, 5,
+
+    HRTIM_TIMD: HrOut1, setd1r, rstd1r, 6,
+    HRTIM_TIMD: HrOut2, setd2r, rstd2r, 7,
+
+    HRTIM_TIME: HrOut1, sete1r, rste1r, 8,
+    HRTIM_TIME: HrOut2, sete2r, rste2r, 9,
+
+    HRTIM_TIMF: HrOut1, setf1r, rstf1r, 10,
+    HRTIM_TIMF: HrOut2, setf2r, rstf2r, 11,
+}
+
+macro_rules! hrtim_timers {
+    ($($TIMX:ident),+) => {
+        $(
+            impl HrOutputTimer for $TIMX {}
+
+            impl ToHrOut<$TIMX> for () {
+                type Out<PSCL> = ();
+
+                fn out<PSCL>() -> Self::Out<PSCL> {}
+            }
+        )+
+    };
+}
+
+hrtim_timers!(HRTIM_TIMA, HRTIM_TIMB, HRTIM_TIMC, HRTIM_TIMD, HRTIM_TIME, HRTIM_TIMF);
+
+macro_rules! pins {
+    ($($TIMX:ident: $OUT:ident => $PIN:ident<$AF:ident>,)+) => {
+        $(
+            impl ToHrOut<$TIMX> for $PIN<Alternate<$AF>> {
+                type Out<PSCL> = $OUT<$TIMX, PSCL>;
+
+                fn out<PSCL>() -> Self::Out<PSCL> {
+                    $OUT {
+                        _tim: PhantomData,
+                        _prescaler: PhantomData,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+pins! {
+    HRTIM_TIMA: HrOut1 => PA8<AF13>,
+    HRTIM_TIMA: HrOut2 => PA9<AF13>,
+
+    HRTIM_TIMB: HrOut1 => PA10<AF13>,
+    HRTIM_TIMB: HrOut2 => PA11<AF13>,
+
+    HRTIM_TIMC: HrOut1 => PB12<AF13>,
+    HRTIM_TIMC: HrOut2 => PB13<AF13>,
+
+    HRTIM_TIMD: HrOut1 => PB14<AF13>,
+    HRTIM_TIMD: HrOut2 => PB15<AF13>,
+
+    HRTIM_TIME: HrOut1 => PC8<AF3>,
+    HRTIM_TIME: HrOut2 => PC9<AF3>,
+
+    HRTIM_TIMF: HrOut1 => PC6<AF13>,
+    HRTIM_TIMF: HrOut2 => PC7<AF13>,
+}
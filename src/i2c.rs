@@ -1,6 +1,8 @@
 //! I2C
 use hal_02::blocking::i2c::{Read, Write, WriteRead};
 
+use crate::dma::{mux::DmaMuxResources, traits::TargetAddress};
+use crate::dma::{MemoryToPeripheral, PeripheralToMemory};
 use crate::gpio::{gpioa::*, gpiob::*, gpioc::*, gpiof::*};
 #[cfg(any(
     feature = "stm32g471",
@@ -9,9 +11,10 @@ use crate::gpio::{gpioa::*, gpiob::*, gpioc::*, gpiof::*};
     feature = "stm32g483",
     feature = "stm32g484"
 ))]
-use crate::gpio::{gpiog::*, AF3};
+use crate::gpio::{gpiod::*, gpiog::*, AF3};
 use crate::gpio::{AlternateOD, AF2, AF4, AF8};
 use crate::rcc::{Enable, GetBusFreq, Rcc, RccBus, Reset};
+use crate::stm32::i2c1::RegisterBlock;
 #[cfg(any(
     feature = "stm32g471",
     feature = "stm32g473",
@@ -21,8 +24,17 @@ use crate::rcc::{Enable, GetBusFreq, Rcc, RccBus, Reset};
 ))]
 use crate::stm32::I2C4;
 use crate::stm32::{I2C1, I2C2, I2C3, RCC};
-use crate::time::Hertz;
+use crate::time::{ExtU32, Hertz, NanoSecond};
 use core::cmp;
+use core::marker::PhantomData;
+
+pub mod pmbus;
+pub mod target;
+pub mod timing;
+
+pub use timing::TimingError;
+
+use smbus::{Smbus, SmbusConfig, TimeoutError};
 
 /// I2C bus configuration.
 pub struct Config {
@@ -30,6 +42,8 @@ pub struct Config {
     timing: Option<u32>,
     analog_filter: bool,
     digital_filter: u8,
+    rise_time: NanoSecond,
+    fall_time: NanoSecond,
 }
 
 impl Config {
@@ -43,6 +57,8 @@ impl Config {
             timing: None,
             analog_filter: true,
             digital_filter: 0,
+            rise_time: 100.nanos(),
+            fall_time: 10.nanos(),
         }
     }
 
@@ -62,6 +78,8 @@ impl Config {
             speed: None,
             analog_filter: true,
             digital_filter: 0,
+            rise_time: 100.nanos(),
+            fall_time: 10.nanos(),
         }
     }
 
@@ -78,27 +96,58 @@ impl Config {
         self
     }
 
-    fn timing_bits(&self, i2c_clk: Hertz) -> u32 {
+    /// Sets the rise time of SCL and SDA, 100 ns by default.
+    ///
+    /// It depends on the pull-up resistors and the bus capacitance, and delays the data and
+    /// the clock edges.
+    pub fn rise_time(mut self, rise_time: NanoSecond) -> Self {
+        self.rise_time = rise_time;
+        self
+    }
+
+    /// Sets the fall time of SCL and SDA, 10 ns by default.
+    pub fn fall_time(mut self, fall_time: NanoSecond) -> Self {
+        self.fall_time = fall_time;
+        self
+    }
+
+    /// Returns the value of the `I2C_TIMINGR` register for the kernel clock `i2c_clk`, see
+    /// [`timing::solve`].
+    pub fn timing(&self, i2c_clk: Hertz) -> Result<u32, TimingError> {
         if let Some(bits) = self.timing {
-            return bits;
+            return Ok(bits);
+        }
+        timing::solve(
+            i2c_clk,
+            self.speed.unwrap(),
+            self.rise_time,
+            self.fall_time,
+            self.analog_filter,
+            self.digital_filter,
+        )
+    }
+}
+
+/// Address of an I2C device
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Address {
+    /// 7-bit address
+    SevenBit(u8),
+    /// 10-bit address
+    TenBit(u16),
+}
+
+impl Address {
+    /// Value of the `SADD` field of `CR2`
+    fn sadd(self) -> u16 {
+        match self {
+            Address::SevenBit(address) => (address as u16) << 1,
+            Address::TenBit(address) => address & 0x3FF,
         }
-        let speed = self.speed.unwrap();
-        let (psc, scll, sclh, sdadel, scldel) = if speed.raw() <= 100_000 {
-            let psc = 3;
-            let scll = cmp::min((((i2c_clk.raw() >> 1) / (psc + 1)) / speed.raw()) - 1, 255);
-            let sclh = scll - 4;
-            let sdadel = 2;
-            let scldel = 4;
-            (psc, scll, sclh, sdadel, scldel)
-        } else {
-            let psc = 1;
-            let scll = cmp::min((((i2c_clk.raw() >> 1) / (psc + 1)) / speed.raw()) - 1, 255);
-            let sclh = scll - 6;
-            let sdadel = 1;
-            let scldel = 3;
-            (psc, scll, sclh, sdadel, scldel)
-        };
-        psc << 28 | scldel << 20 | sdadel << 16 | sclh << 8 | scll
+    }
+
+    fn is_ten_bit(self) -> bool {
+        matches!(self, Address::TenBit(_))
     }
 }
 
@@ -109,12 +158,27 @@ pub struct I2c<I2C, SDA, SCL> {
     scl: SCL,
 }
 
+/// Controller transfer fed by the DMA.
+///
+/// The [`I2c`] is owned by the DMA transfer in the meantime, this handle sends the START
+/// condition and reloads the byte count of transfers longer than 255 bytes.
+pub struct DmaControl<I2C> {
+    address: Address,
+    read: bool,
+    len: usize,
+    remaining: usize,
+    _i2c: PhantomData<I2C>,
+}
+
 /// I2C SDA pin
 pub trait SDAPin<I2C> {}
 
 /// I2C SCL pin
 pub trait SCLPin<I2C> {}
 
+/// SMBus alert pin
+pub trait SMBAPin<I2C> {}
+
 /// I2C error
 #[derive(Debug)]
 pub enum Error {
@@ -123,13 +187,46 @@ pub enum Error {
     PECError,
     BusError,
     ArbitrationLost,
+    Timeout,
+}
+
+impl hal_1::i2c::Error for Error {
+    fn kind(&self) -> hal_1::i2c::ErrorKind {
+        match self {
+            Error::Overrun => hal_1::i2c::ErrorKind::Overrun,
+            Error::Nack => {
+                hal_1::i2c::ErrorKind::NoAcknowledge(hal_1::i2c::NoAcknowledgeSource::Unknown)
+            }
+            Error::PECError => hal_1::i2c::ErrorKind::Other,
+            Error::BusError => hal_1::i2c::ErrorKind::Bus,
+            Error::ArbitrationLost => hal_1::i2c::ErrorKind::ArbitrationLoss,
+            Error::Timeout => hal_1::i2c::ErrorKind::Other,
+        }
+    }
 }
 
 pub trait I2cExt<I2C> {
+    /// Initializes the I2C peripheral, see [`I2cExt::try_i2c`].
+    ///
+    /// Panics if the bus speed can not be met with the kernel clock, in particular below about
+    /// 1/8192 of the kernel clock, for example 21 kHz at 170 MHz.
     fn i2c<SDA, SCL>(self, sda: SDA, scl: SCL, config: Config, rcc: &mut Rcc) -> I2c<I2C, SDA, SCL>
     where
         SDA: SDAPin<I2C>,
         SCL: SCLPin<I2C>;
+
+    /// Initializes the I2C peripheral, or returns why the bus speed can not be met with the
+    /// kernel clock.
+    fn try_i2c<SDA, SCL>(
+        self,
+        sda: SDA,
+        scl: SCL,
+        config: Config,
+        rcc: &mut Rcc,
+    ) -> Result<I2c<I2C, SDA, SCL>, TimingError>
+    where
+        SDA: SDAPin<I2C>,
+        SCL: SCLPin<I2C>;
 }
 
 /// Sequence to flush the TXDR register. This resets the TXIS and TXE flags
@@ -168,6 +265,11 @@ macro_rules! busy_wait {
                     w.arlocf().set_bit();
                 });
                 return Err(Error::ArbitrationLost);
+            } else if isr.timeout().bit_is_set() {
+                $i2c.icr().write(|w| {
+                    w.timoutcf().set_bit();
+                });
+                return Err(Error::Timeout);
             } else if isr.nackf().bit_is_set() {
                 $i2c.icr().write(|w| {
                     w.stopcf().set_bit().nackcf().set_bit();
@@ -181,10 +283,271 @@ macro_rules! busy_wait {
     };
 }
 
+/// Sends a START or repeated START condition for the first chunk of a transfer of `total`
+/// bytes, returns the length of the chunk and the bytes remaining after it. RELOAD is also set
+/// when `more` bytes will be added to the transfer. With `pec`, the last byte of the transfer is
+/// the SMBus PEC, sent or checked by the peripheral.
+pub(crate) fn start_transfer(
+    i2c: &RegisterBlock,
+    address: Address,
+    read: bool,
+    total: usize,
+    more: bool,
+    pec: bool,
+) -> (usize, usize) {
+    let chunk = cmp::min(total, 255);
+    let remaining = total - chunk;
+    i2c.cr2().write(|w| {
+        w.start().set_bit().pecbyte().bit(pec);
+        unsafe {
+            w.nbytes().bits(chunk as u8).sadd().bits(address.sadd());
+        }
+        w.add10()
+            .bit(address.is_ten_bit())
+            .rd_wrn()
+            .bit(read)
+            // More than 255 bytes are sent in chunks
+            .reload()
+            .bit(remaining > 0 || more)
+            // Software end mode
+            .autoend()
+            .clear_bit();
+    });
+    (chunk, remaining)
+}
+
+/// Loads the next chunk of at most 255 bytes once the current one has been transferred,
+/// keeping RELOAD set while more bytes follow.
+pub(crate) fn reload_chunk(
+    i2c: &RegisterBlock,
+    chunk: &mut usize,
+    remaining: &mut usize,
+) -> Result<(), Error> {
+    if *chunk == 0 {
+        busy_wait!(i2c, tcr, bit_is_set);
+
+        *chunk = cmp::min(*remaining, 255);
+        *remaining -= *chunk;
+        i2c.cr2().modify(|_, w| {
+            unsafe {
+                w.nbytes().bits(*chunk as u8);
+            }
+            w.reload().bit(*remaining > 0);
+        });
+    }
+    *chunk -= 1;
+
+    Ok(())
+}
+
+/// Sends a byte once the previous one went through, or the address has been ACKed
+pub(crate) fn write_byte(i2c: &RegisterBlock, byte: u8) -> Result<(), Error> {
+    busy_wait!(i2c, txis, bit_is_set);
+    i2c.txdr().write(|w| unsafe {
+        w.txdata().bits(byte);
+    });
+    Ok(())
+}
+
+/// Waits for a byte to be received
+pub(crate) fn read_byte(i2c: &RegisterBlock) -> Result<u8, Error> {
+    busy_wait!(i2c, rxne, bit_is_set);
+    Ok(i2c.rxdr().read().rxdata().bits())
+}
+
+// Declared after the macros it uses
+pub mod smbus;
+
+#[cfg(feature = "async")]
+mod asynch {
+    use core::cmp;
+    use core::future::poll_fn;
+    use core::task::Poll;
+
+    use super::{Address, Error};
+    use crate::asynch::AtomicWaker;
+    use crate::stm32::i2c1::RegisterBlock;
+
+    /// I2C peripheral with a waker for its event and error interrupts
+    pub(super) trait Instance {
+        fn waker() -> &'static AtomicWaker;
+    }
+
+    #[derive(Clone, Copy)]
+    enum Flag {
+        Txis,
+        Rxne,
+        Tc,
+        Tcr,
+    }
+
+    /// Masks the interrupt sources enabled by [`wait_flag`] and wakes the waiting task
+    pub(super) fn on_interrupt(i2c: &RegisterBlock, waker: &AtomicWaker) {
+        i2c.cr1().modify(|_, w| {
+            w.txie()
+                .clear_bit()
+                .rxie()
+                .clear_bit()
+                .tcie()
+                .clear_bit()
+                .nackie()
+                .clear_bit()
+                .errie()
+                .clear_bit();
+        });
+        waker.wake();
+    }
+
+    /// Async counterpart of `busy_wait!`
+    async fn wait_flag(i2c: &RegisterBlock, waker: &AtomicWaker, flag: Flag) -> Result<(), Error> {
+        poll_fn(|cx| {
+            waker.register(cx.waker());
+
+            let isr = i2c.isr().read();
+            let set = match flag {
+                Flag::Txis => isr.txis().bit_is_set(),
+                Flag::Rxne => isr.rxne().bit_is_set(),
+                Flag::Tc => isr.tc().bit_is_set(),
+                Flag::Tcr => isr.tcr().bit_is_set(),
+            };
+
+            if set {
+                Poll::Ready(Ok(()))
+            } else if isr.berr().bit_is_set() {
+                i2c.icr().write(|w| {
+                    w.berrcf().set_bit();
+                });
+                Poll::Ready(Err(Error::BusError))
+            } else if isr.arlo().bit_is_set() {
+                i2c.icr().write(|w| {
+                    w.arlocf().set_bit();
+                });
+                Poll::Ready(Err(Error::ArbitrationLost))
+            } else if isr.timeout().bit_is_set() {
+                i2c.icr().write(|w| {
+                    w.timoutcf().set_bit();
+                });
+                Poll::Ready(Err(Error::Timeout))
+            } else if isr.nackf().bit_is_set() {
+                i2c.icr().write(|w| {
+                    w.stopcf().set_bit().nackcf().set_bit();
+                });
+                flush_txdr!(i2c);
+                Poll::Ready(Err(Error::Nack))
+            } else {
+                i2c.cr1().modify(|_, w| {
+                    match flag {
+                        Flag::Txis => w.txie().set_bit(),
+                        Flag::Rxne => w.rxie().set_bit(),
+                        Flag::Tc | Flag::Tcr => w.tcie().set_bit(),
+                    };
+                    w.nackie().set_bit().errie().set_bit();
+                });
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Async counterpart of `reload_chunk`
+    async fn reload_chunk(
+        i2c: &RegisterBlock,
+        waker: &AtomicWaker,
+        chunk: &mut usize,
+        remaining: &mut usize,
+    ) -> Result<(), Error> {
+        if *chunk == 0 {
+            wait_flag(i2c, waker, Flag::Tcr).await?;
+
+            *chunk = cmp::min(*remaining, 255);
+            *remaining -= *chunk;
+            i2c.cr2().modify(|_, w| {
+                unsafe {
+                    w.nbytes().bits(*chunk as u8);
+                }
+                w.reload().bit(*remaining > 0);
+            });
+        }
+        *chunk -= 1;
+
+        Ok(())
+    }
+
+    /// Async counterpart of the blocking `I2c::transaction_addr`
+    pub(super) async fn transaction(
+        i2c: &RegisterBlock,
+        waker: &AtomicWaker,
+        address: Address,
+        operations: &mut [hal_1::i2c::Operation<'_>],
+    ) -> Result<(), Error> {
+        use hal_1::i2c::Operation;
+
+        if operations.is_empty() {
+            return Ok(());
+        }
+
+        // Wait for any previous address sequence to end automatically.
+        // This could be up to 50% of a bus cycle (ie. up to 0.5/freq)
+        while i2c.cr2().read().start().bit_is_set() {}
+
+        let mut first = 0;
+        while first < operations.len() {
+            let read = matches!(operations[first], Operation::Read(_));
+            let mut last = first + 1;
+            while last < operations.len() && matches!(operations[last], Operation::Read(_)) == read
+            {
+                last += 1;
+            }
+
+            let total: usize = operations[first..last]
+                .iter()
+                .map(|op| match op {
+                    Operation::Read(buffer) => buffer.len(),
+                    Operation::Write(bytes) => bytes.len(),
+                })
+                .sum();
+            let (mut chunk, mut remaining) =
+                super::start_transfer(i2c, address, read, total, false, false);
+
+            for op in operations[first..last].iter_mut() {
+                match op {
+                    Operation::Read(buffer) => {
+                        for byte in buffer.iter_mut() {
+                            reload_chunk(i2c, waker, &mut chunk, &mut remaining).await?;
+                            wait_flag(i2c, waker, Flag::Rxne).await?;
+                            *byte = i2c.rxdr().read().rxdata().bits();
+                        }
+                    }
+                    Operation::Write(bytes) => {
+                        for byte in bytes.iter() {
+                            reload_chunk(i2c, waker, &mut chunk, &mut remaining).await?;
+                            wait_flag(i2c, waker, Flag::Txis).await?;
+                            i2c.txdr().write(|w| unsafe {
+                                w.txdata().bits(*byte);
+                            });
+                        }
+                    }
+                }
+            }
+
+            wait_flag(i2c, waker, Flag::Tc).await?;
+
+            first = last;
+        }
+
+        i2c.cr2().modify(|_, w| {
+            w.stop().set_bit();
+        });
+
+        Ok(())
+    }
+}
+
 macro_rules! i2c {
-    ($I2CX:ident, $i2cx:ident,
+    ($I2CX:ident, $i2cx:ident, $rx:ident, $tx:ident,
         sda: [ $($( #[ $pmetasda:meta ] )* $PSDA:ty,)+ ],
         scl: [ $($( #[ $pmetascl:meta ] )* $PSCL:ty,)+ ],
+        smba: [ $($( #[ $pmetasmba:meta ] )* $PSMBA:ty,)+ ],
     ) => {
         $(
             $( #[ $pmetasda ] )*
@@ -196,223 +559,431 @@ macro_rules! i2c {
             impl SCLPin<$I2CX> for $PSCL {}
         )+
 
-        impl I2cExt<$I2CX> for $I2CX {
-            fn i2c<SDA, SCL>(
-                self,
-                sda: SDA,
-                scl: SCL,
-                config: Config,
-                rcc: &mut Rcc,
-            ) -> I2c<$I2CX, SDA, SCL>
-            where
+        $(
+            $( #[ $pmetasmba ] )*
+            impl SMBAPin<$I2CX> for $PSMBA {}
+        )+
+
+        paste::paste! {
+            impl I2cExt<$I2CX> for $I2CX {
+                fn i2c<SDA, SCL>(
+                    self,
+                    sda: SDA,
+                    scl: SCL,
+                    config: Config,
+                    rcc: &mut Rcc,
+                ) -> I2c<$I2CX, SDA, SCL>
+                where
+                    SDA: SDAPin<$I2CX>,
+                    SCL: SCLPin<$I2CX>,
+                {
+                    I2c::$i2cx(self, sda, scl, config, rcc)
+                }
+
+                fn try_i2c<SDA, SCL>(
+                    self,
+                    sda: SDA,
+                    scl: SCL,
+                    config: Config,
+                    rcc: &mut Rcc,
+                ) -> Result<I2c<$I2CX, SDA, SCL>, TimingError>
+                where
+                    SDA: SDAPin<$I2CX>,
+                    SCL: SCLPin<$I2CX>,
+                {
+                    I2c::[<try_ $i2cx>](self, sda, scl, config, rcc)
+                }
+            }
+
+            impl<SDA, SCL> I2c<$I2CX, SDA, SCL> where
                 SDA: SDAPin<$I2CX>,
-                SCL: SCLPin<$I2CX>,
+                SCL: SCLPin<$I2CX>
             {
-                I2c::$i2cx(self, sda, scl, config, rcc)
+                /// Initializes the I2C peripheral.
+                ///
+                /// Panics if the bus speed can not be met with the kernel clock.
+                pub fn $i2cx(i2c: $I2CX, sda: SDA, scl: SCL, config: Config, rcc: &mut Rcc) -> Self
+                where
+                    SDA: SDAPin<$I2CX>,
+                    SCL: SCLPin<$I2CX>,
+                {
+                    Self::[<try_ $i2cx>](i2c, sda, scl, config, rcc)
+                        .expect("the I2C bus speed can not be met with this kernel clock")
+                }
+
+                /// Initializes the I2C peripheral, or returns why the bus speed can not be met with
+                /// the kernel clock.
+                pub fn [<try_ $i2cx>](
+                    i2c: $I2CX,
+                    sda: SDA,
+                    scl: SCL,
+                    config: Config,
+                    rcc: &mut Rcc,
+                ) -> Result<Self, TimingError>
+                where
+                    SDA: SDAPin<$I2CX>,
+                    SCL: SCLPin<$I2CX>,
+                {
+                    let timing_bits = config.timing(<$I2CX as RccBus>::Bus::get_frequency(&rcc.clocks))?;
+
+                    // Enable and reset I2C
+                    unsafe {
+                        let rcc_ptr = &(*RCC::ptr());
+                        $I2CX::enable(rcc_ptr);
+                        $I2CX::reset(rcc_ptr);
+                    }
+
+                    // Make sure the I2C unit is disabled so we can configure it
+                    i2c.cr1().modify(|_, w| {w.pe().clear_bit();});
+
+                    // Setup protocol timings
+                    i2c.timingr().write(|w| unsafe { w.bits(timing_bits); });
+
+                    // Enable the I2C processing
+                    i2c.cr1().modify(|_, w| {
+                        w.pe()
+                            .set_bit();
+                            unsafe{w.dnf()
+                            .bits(config.digital_filter);}
+                            w.anfoff()
+                            .bit(!config.analog_filter);
+                    });
+
+                    Ok(I2c { i2c, sda, scl })
+                }
+
+                /// Disables I2C and releases the peripheral as well as the pins.
+                pub fn release(self) -> ($I2CX, SDA, SCL) {
+                    // Disable I2C.
+                    unsafe {
+                        let rcc_ptr = &(*RCC::ptr());
+                        $I2CX::reset(rcc_ptr);
+                        $I2CX::disable(rcc_ptr);
+                    }
+
+                    (self.i2c, self.sda, self.scl)
+                }
             }
         }
 
-        impl<SDA, SCL> I2c<$I2CX, SDA, SCL> where
-            SDA: SDAPin<$I2CX>,
-            SCL: SCLPin<$I2CX>
-        {
-            /// Initializes the I2C peripheral.
-            pub fn $i2cx(i2c: $I2CX, sda: SDA, scl: SCL, config: Config, rcc: &mut Rcc) -> Self
-            where
-                SDA: SDAPin<$I2CX>,
-                SCL: SCLPin<$I2CX>,
-            {
-                // Enable and reset I2C
-                unsafe {
-                    let rcc_ptr = &(*RCC::ptr());
-                    $I2CX::enable(rcc_ptr);
-                    $I2CX::reset(rcc_ptr);
+        impl<SDA, SCL> I2c<$I2CX, SDA, SCL> {
+            /// Runs the SMBus protocols on this bus, see [`smbus`]. Fails if a timeout of `config`
+            /// can not be counted with the kernel clock.
+            pub fn smbus(self, config: SmbusConfig, rcc: &Rcc) -> Result<Smbus<$I2CX, SDA, SCL>, TimeoutError> {
+                let i2c_clk = <$I2CX as RccBus>::Bus::get_frequency(&rcc.clocks);
+                Smbus::init(self, config, i2c_clk)
+            }
+        }
+
+        impl<SDA, SCL> I2c<$I2CX, SDA, SCL> {
+            /// Prepares a write of `len` bytes to `address`, fed by a memory to peripheral DMA
+            /// transfer on this `I2c`.
+            ///
+            /// ```ignore
+            /// let mut control = i2c.dma_write(Address::SevenBit(0x50), page.len());
+            /// let mut transfer = stream.into_memory_to_peripheral_transfer(i2c, page, config);
+            /// transfer.start(|_| {});
+            /// control.start();
+            /// block!(control.poll())?;
+            /// ```
+            pub fn dma_write(&mut self, address: Address, len: usize) -> DmaControl<$I2CX> {
+                self.i2c.cr1().modify(|_, w| { w.txdmaen().set_bit(); });
+                DmaControl {
+                    address,
+                    read: false,
+                    len,
+                    remaining: 0,
+                    _i2c: PhantomData,
+                }
+            }
+
+            /// Prepares a read of `len` bytes from `address`, stored by a peripheral to memory
+            /// DMA transfer on this `I2c`.
+            pub fn dma_read(&mut self, address: Address, len: usize) -> DmaControl<$I2CX> {
+                self.i2c.cr1().modify(|_, w| { w.rxdmaen().set_bit(); });
+                DmaControl {
+                    address,
+                    read: true,
+                    len,
+                    remaining: 0,
+                    _i2c: PhantomData,
                 }
+            }
+        }
 
-                // Make sure the I2C unit is disabled so we can configure it
-                i2c.cr1().modify(|_, w| {w.pe().clear_bit();});
+        impl DmaControl<$I2CX> {
+            /// Sends the START condition, once the DMA transfer has been started
+            pub fn start(&mut self) {
+                // NOTE(unsafe) the DMA transfer owning the `I2c` does not access its registers
+                let i2c = unsafe { &*$I2CX::ptr() };
 
-                // Setup protocol timings
-                let timing_bits = config.timing_bits(<$I2CX as RccBus>::Bus::get_frequency(&rcc.clocks));
-                i2c.timingr().write(|w| unsafe { w.bits(timing_bits); });
+                // Wait for any previous address sequence to end automatically.
+                while i2c.cr2().read().start().bit_is_set() {};
 
-                // Enable the I2C processing
-                i2c.cr1().modify(|_, w| {
-                    w.pe()
+                let chunk = cmp::min(self.len, 255);
+                self.remaining = self.len - chunk;
+                i2c.cr2().write(|w| {
+                    w.start().set_bit();
+                    unsafe {
+                        w.nbytes().bits(chunk as u8).sadd().bits(self.address.sadd());
+                    }
+                    w.add10()
+                        .bit(self.address.is_ten_bit())
+                        .rd_wrn()
+                        .bit(self.read)
+                        .reload()
+                        .bit(self.remaining > 0)
+                        // The STOP condition is sent after the last chunk
+                        .autoend()
                         .set_bit();
-                        unsafe{w.dnf()
-                        .bits(config.digital_filter);}
-                        w.anfoff()
-                        .bit(!config.analog_filter);
                 });
-
-                I2c { i2c, sda, scl }
             }
 
-            /// Disables I2C and releases the peripheral as well as the pins.
-            pub fn release(self) -> ($I2CX, SDA, SCL) {
-                // Disable I2C.
-                unsafe {
-                    let rcc_ptr = &(*RCC::ptr());
-                    $I2CX::reset(rcc_ptr);
-                    $I2CX::disable(rcc_ptr);
+            /// Reloads the byte count of the next chunk, returns `Ok` once the STOP condition
+            /// has been sent
+            pub fn poll(&mut self) -> nb::Result<(), Error> {
+                // NOTE(unsafe) the DMA transfer owning the `I2c` does not access its registers
+                let i2c = unsafe { &*$I2CX::ptr() };
+                let isr = i2c.isr().read();
+
+                let error = if isr.berr().bit_is_set() {
+                    i2c.icr().write(|w| { w.berrcf().set_bit(); });
+                    Some(Error::BusError)
+                } else if isr.arlo().bit_is_set() {
+                    i2c.icr().write(|w| { w.arlocf().set_bit(); });
+                    Some(Error::ArbitrationLost)
+                } else if isr.timeout().bit_is_set() {
+                    i2c.icr().write(|w| { w.timoutcf().set_bit(); });
+                    Some(Error::Timeout)
+                } else if isr.nackf().bit_is_set() {
+                    i2c.icr().write(|w| { w.stopcf().set_bit().nackcf().set_bit(); });
+                    flush_txdr!(i2c);
+                    Some(Error::Nack)
+                } else {
+                    None
+                };
+                if let Some(error) = error {
+                    i2c.cr1().modify(|_, w| { w.rxdmaen().clear_bit().txdmaen().clear_bit(); });
+                    return Err(nb::Error::Other(error));
+                }
+
+                if isr.tcr().bit_is_set() {
+                    let chunk = cmp::min(self.remaining, 255);
+                    self.remaining -= chunk;
+                    i2c.cr2().modify(|_, w| {
+                        unsafe { w.nbytes().bits(chunk as u8); }
+                        w.reload().bit(self.remaining > 0);
+                    });
+                    return Err(nb::Error::WouldBlock);
                 }
 
-                (self.i2c, self.sda, self.scl)
+                if isr.stopf().bit_is_set() {
+                    i2c.icr().write(|w| { w.stopcf().set_bit(); });
+                    i2c.cr1().modify(|_, w| { w.rxdmaen().clear_bit().txdmaen().clear_bit(); });
+                    return Ok(());
+                }
+
+                Err(nb::Error::WouldBlock)
             }
         }
 
-        impl<SDA, SCL> WriteRead for I2c<$I2CX, SDA, SCL> {
+        unsafe impl<SDA, SCL> TargetAddress<PeripheralToMemory> for I2c<$I2CX, SDA, SCL> {
+            #[inline(always)]
+            fn address(&self) -> u32 {
+                self.i2c.rxdr() as *const _ as u32
+            }
+
+            type MemSize = u8;
+
+            const REQUEST_LINE: Option<u8> = Some(DmaMuxResources::$rx as u8);
+        }
+
+        unsafe impl<SDA, SCL> TargetAddress<MemoryToPeripheral> for I2c<$I2CX, SDA, SCL> {
+            #[inline(always)]
+            fn address(&self) -> u32 {
+                self.i2c.txdr() as *const _ as u32
+            }
+
+            type MemSize = u8;
+
+            const REQUEST_LINE: Option<u8> = Some(DmaMuxResources::$tx as u8);
+        }
+
+        impl<SDA, SCL> hal_1::i2c::ErrorType for I2c<$I2CX, SDA, SCL> {
             type Error = Error;
+        }
 
-            fn write_read(
+        impl<SDA, SCL> I2c<$I2CX, SDA, SCL> {
+            /// Runs `operations` as a single transaction with a 7 or 10 bit `address`, transfers
+            /// of more than 255 bytes are split in chunks with RELOAD.
+            pub fn transaction_addr(
                 &mut self,
-                addr: u8,
-                bytes: &[u8],
-                buffer: &mut [u8],
-            ) -> Result<(), Self::Error> {
-                // TODO support transfers of more than 255 bytes
-                assert!(bytes.len() < 256 && bytes.len() > 0);
-                assert!(buffer.len() < 256 && buffer.len() > 0);
+                address: Address,
+                operations: &mut [hal_1::i2c::Operation<'_>],
+            ) -> Result<(), Error> {
+                use hal_1::i2c::Operation;
+
+                if operations.is_empty() {
+                    return Ok(());
+                }
 
                 // Wait for any previous address sequence to end automatically.
                 // This could be up to 50% of a bus cycle (ie. up to 0.5/freq)
                 while self.i2c.cr2().read().start().bit_is_set() {};
 
-                // Set START and prepare to send `bytes`.
-                // The START bit can be set even if the bus is BUSY or
-                // I2C is in slave mode.
-                self.i2c.cr2().write(|w| {
-                    w
-                        // Start transfer
-                        .start().set_bit();
-                        unsafe{// Set number of bytes to transfer
-                        w.nbytes().bits(bytes.len() as u8)
-                        // Set address to transfer to/from
-                        .sadd().bits((addr << 1) as u16);}
-                        // 7-bit addressing mode
-                        w.add10().clear_bit()
-                        // Set transfer direction to write
-                        .rd_wrn().clear_bit()
-                        // Software end mode
-                        .autoend().clear_bit();
-                });
-
-                for byte in bytes {
-                    // Wait until we are allowed to send data
-                    // (START has been ACKed or last byte went through)
-                    busy_wait!(self.i2c, txis, bit_is_set);
+                let mut first = 0;
+                while first < operations.len() {
+                    // Adjacent operations of the same direction are merged into a single
+                    // transfer, a direction change issues a repeated START.
+                    let read = matches!(operations[first], Operation::Read(_));
+                    let mut last = first + 1;
+                    while last < operations.len() && matches!(operations[last], Operation::Read(_)) == read {
+                        last += 1;
+                    }
 
-                    // Put byte on the wire
-                    self.i2c.txdr().write(|w| unsafe { w.txdata().bits(*byte); });
-                }
+                    let total: usize = operations[first..last]
+                        .iter()
+                        .map(|op| match op {
+                            Operation::Read(buffer) => buffer.len(),
+                            Operation::Write(bytes) => bytes.len(),
+                        })
+                        .sum();
+                    let (mut chunk, mut remaining) =
+                        start_transfer(&self.i2c, address, read, total, false, false);
 
-                // Wait until the write finishes before beginning to read.
-                busy_wait!(self.i2c, tc, bit_is_set);
-
-                // reSTART and prepare to receive bytes into `buffer`
-                self.i2c.cr2().write(|w| {
-                    w
-                        // Start transfer
-                        .start().set_bit();
-                        unsafe{// Set number of bytes to transfer
-                        w.nbytes().bits(buffer.len() as u8)
-                        // Set address to transfer to/from
-                        .sadd().bits((addr << 1) as u16);}
-                        // 7-bit addressing mode
-                        w.add10().clear_bit()
-                        // Set transfer direction to read
-                        .rd_wrn().set_bit()
-                        // Automatic end mode
-                        .autoend().set_bit();
-                });
+                    for op in operations[first..last].iter_mut() {
+                        match op {
+                            Operation::Read(buffer) => {
+                                for byte in buffer.iter_mut() {
+                                    reload_chunk(&self.i2c, &mut chunk, &mut remaining)?;
+                                    *byte = read_byte(&self.i2c)?;
+                                }
+                            }
+                            Operation::Write(bytes) => {
+                                for byte in bytes.iter() {
+                                    reload_chunk(&self.i2c, &mut chunk, &mut remaining)?;
+                                    write_byte(&self.i2c, *byte)?;
+                                }
+                            }
+                        }
+                    }
 
-                for byte in buffer {
-                    // Wait until we have received something
-                    busy_wait!(self.i2c, rxne, bit_is_set);
+                    // Wait until the transfer finishes before the next START or STOP
+                    busy_wait!(self.i2c, tc, bit_is_set);
 
-                    *byte = self.i2c.rxdr().read().rxdata().bits();
+                    first = last;
                 }
 
-                // automatic STOP
+                // Software end mode, send STOP ourselves
+                self.i2c.cr2().modify(|_, w| {w.stop().set_bit();});
 
                 Ok(())
             }
         }
 
-        impl<SDA, SCL> Write for I2c<$I2CX, SDA, SCL> {
-            type Error = Error;
+        impl<SDA, SCL> hal_1::i2c::I2c for I2c<$I2CX, SDA, SCL> {
+            fn transaction(
+                &mut self,
+                address: u8,
+                operations: &mut [hal_1::i2c::Operation<'_>],
+            ) -> Result<(), Self::Error> {
+                I2c::transaction_addr(self, Address::SevenBit(address), operations)
+            }
+        }
 
-            fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
-                assert!(bytes.len() < 256 && bytes.len() > 0);
-
-                self.i2c.cr2().modify(|_, w| {
-                    w
-                        // Start transfer
-                        .start().set_bit();
-                        unsafe{// Set number of bytes to transfer
-                        w.nbytes().bits(bytes.len() as u8)
-                        // Set address to transfer to/from
-                        .sadd().bits((addr << 1) as u16);}
-                        // Set transfer direction to write
-                        w.rd_wrn().clear_bit()
-                        // Automatic end mode
-                        .autoend().set_bit();
-                });
+        impl<SDA, SCL> hal_1::i2c::I2c<hal_1::i2c::TenBitAddress> for I2c<$I2CX, SDA, SCL> {
+            fn transaction(
+                &mut self,
+                address: u16,
+                operations: &mut [hal_1::i2c::Operation<'_>],
+            ) -> Result<(), Self::Error> {
+                I2c::transaction_addr(self, Address::TenBit(address), operations)
+            }
+        }
 
-                for byte in bytes {
-                    // Wait until we are allowed to send data
-                    // (START has been ACKed or last byte when through)
-                    busy_wait!(self.i2c, txis, bit_is_set);
+        #[cfg(feature = "async")]
+        impl asynch::Instance for $I2CX {
+            fn waker() -> &'static crate::asynch::AtomicWaker {
+                static WAKER: crate::asynch::AtomicWaker = crate::asynch::AtomicWaker::new();
+                &WAKER
+            }
+        }
 
-                    // Put byte on the wire
-                    self.i2c.txdr().write(|w| unsafe{w.txdata().bits(*byte);} );
-                }
+        /// Forward both the event and the error interrupt of the peripheral
+        #[cfg(feature = "async")]
+        impl crate::asynch::OnInterrupt for $I2CX {
+            fn on_interrupt() {
+                // NOTE(unsafe) only the interrupt enable bits are modified
+                let i2c = unsafe { &*$I2CX::ptr() };
+                asynch::on_interrupt(i2c, <$I2CX as asynch::Instance>::waker());
+            }
+        }
 
-                // automatic STOP
+        /// Interrupt driven, the task is woken for every byte and no DMA is used. The
+        /// interrupts have to be forwarded to
+        /// [`OnInterrupt::on_interrupt`](crate::asynch::OnInterrupt::on_interrupt).
+        #[cfg(feature = "async")]
+        impl<SDA, SCL> embedded_hal_async::i2c::I2c for I2c<$I2CX, SDA, SCL> {
+            async fn transaction(
+                &mut self,
+                address: u8,
+                operations: &mut [hal_1::i2c::Operation<'_>],
+            ) -> Result<(), Self::Error> {
+                let waker = <$I2CX as asynch::Instance>::waker();
+                asynch::transaction(&self.i2c, waker, Address::SevenBit(address), operations).await
+            }
+        }
 
-                Ok(())
+        /// Interrupt driven like the 7 bit address implementation
+        #[cfg(feature = "async")]
+        impl<SDA, SCL> embedded_hal_async::i2c::I2c<hal_1::i2c::TenBitAddress>
+            for I2c<$I2CX, SDA, SCL>
+        {
+            async fn transaction(
+                &mut self,
+                address: u16,
+                operations: &mut [hal_1::i2c::Operation<'_>],
+            ) -> Result<(), Self::Error> {
+                let waker = <$I2CX as asynch::Instance>::waker();
+                asynch::transaction(&self.i2c, waker, Address::TenBit(address), operations).await
             }
         }
 
-        impl<SDA, SCL> Read for I2c<$I2CX, SDA, SCL> {
+        impl<SDA, SCL> WriteRead for I2c<$I2CX, SDA, SCL> {
             type Error = Error;
 
-            fn read(&mut self, addr: u8, bytes: &mut [u8]) -> Result<(), Self::Error> {
-                // TODO support transfers of more than 255 bytes
-                assert!(bytes.len() < 256 && bytes.len() > 0);
+            fn write_read(
+                &mut self,
+                addr: u8,
+                bytes: &[u8],
+                buffer: &mut [u8],
+            ) -> Result<(), Self::Error> {
+                use hal_1::i2c::Operation;
 
-                // Wait for any previous address sequence to end automatically.
-                // This could be up to 50% of a bus cycle (ie. up to 0.5/freq)
-                while self.i2c.cr2().read().start().bit_is_set() {};
+                self.transaction_addr(
+                    Address::SevenBit(addr),
+                    &mut [Operation::Write(bytes), Operation::Read(buffer)],
+                )
+            }
+        }
 
-                // Set START and prepare to receive bytes into `buffer`.
-                // The START bit can be set even if the bus
-                // is BUSY or I2C is in slave mode.
-                self.i2c.cr2().modify(|_, w| {
-                    w
-                        // Start transfer
-                        .start().set_bit();
-                        unsafe{// Set number of bytes to transfer
-                        w.nbytes().bits(bytes.len() as u8)
-                        // Set address to transfer to/from
-                        .sadd().bits((addr << 1) as u16);}
-                        // Set transfer direction to read
-                        w.rd_wrn().set_bit()
-                        // automatic end mode
-                        .autoend().set_bit();
-                });
+        impl<SDA, SCL> Write for I2c<$I2CX, SDA, SCL> {
+            type Error = Error;
 
-                for byte in bytes {
-                    // Wait until we have received something
-                    busy_wait!(self.i2c, rxne, bit_is_set);
+            fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+                use hal_1::i2c::Operation;
 
-                    *byte = self.i2c.rxdr().read().rxdata().bits();
-                }
+                self.transaction_addr(Address::SevenBit(addr), &mut [Operation::Write(bytes)])
+            }
+        }
+
+        impl<SDA, SCL> Read for I2c<$I2CX, SDA, SCL> {
+            type Error = Error;
 
-                // automatic STOP
+            fn read(&mut self, addr: u8, bytes: &mut [u8]) -> Result<(), Self::Error> {
+                use hal_1::i2c::Operation;
 
-                Ok(())
+                self.transaction_addr(Address::SevenBit(addr), &mut [Operation::Read(bytes)])
             }
         }
     };
@@ -421,6 +992,8 @@ macro_rules! i2c {
 i2c!(
     I2C1,
     i2c1,
+    I2C1_RX,
+    I2C1_TX,
     sda: [
         PA14<AlternateOD<AF4>>,
         PB7<AlternateOD<AF4>>,
@@ -431,11 +1004,17 @@ i2c!(
         PA15<AlternateOD<AF4>>,
         PB8<AlternateOD<AF4>>,
     ],
+    smba: [
+        PA1<AlternateOD<AF4>>,
+        PB5<AlternateOD<AF4>>,
+    ],
 );
 
 i2c!(
     I2C2,
     i2c2,
+    I2C2_RX,
+    I2C2_TX,
     sda: [
         PA8<AlternateOD<AF4>>,
         PF0<AlternateOD<AF4>>,
@@ -452,11 +1031,17 @@ i2c!(
         ))]
         PF6<AlternateOD<AF4>>,
     ],
+    smba: [
+        PA10<AlternateOD<AF4>>,
+        PB12<AlternateOD<AF4>>,
+    ],
 );
 
 i2c!(
     I2C3,
     i2c3,
+    I2C3_RX,
+    I2C3_TX,
     sda: [
         PB5<AlternateOD<AF8>>,
         PC11<AlternateOD<AF8>>,
@@ -498,6 +1083,17 @@ i2c!(
         ))]
         PG7<AlternateOD<AF4>>,
     ],
+    smba: [
+        PB2<AlternateOD<AF4>>,
+        #[cfg(any(
+            feature = "stm32g471",
+            feature = "stm32g473",
+            feature = "stm32g474",
+            feature = "stm32g483",
+            feature = "stm32g484"
+        ))]
+        PG6<AlternateOD<AF4>>,
+    ],
 );
 
 #[cfg(any(
@@ -510,6 +1106,8 @@ i2c!(
 i2c!(
     I2C4,
     i2c4,
+    I2C4_RX,
+    I2C4_TX,
     sda: [
         PB7<AlternateOD<AF3>>,
         PC7<AlternateOD<AF8>>,
@@ -522,4 +1120,8 @@ i2c!(
         PF14<AlternateOD<AF4>>,
         PG3<AlternateOD<AF4>>,
     ],
+    smba: [
+        PD11<AlternateOD<AF4>>,
+        PF13<AlternateOD<AF4>>,
+    ],
 );
@@ -0,0 +1,280 @@
+//! PMBus
+//!
+//! PMBus devices are SMBus devices with standard commands, this module names the common ones
+//! and converts the LINEAR11 and LINEAR16 data formats.
+//!
+//! ```ignore
+//! let mut smbus = i2c.smbus(SmbusConfig::new().pec(true), &rcc)?;
+//!
+//! smbus.set_page(PSU, 0)?;
+//! let vin = smbus.read_linear11(PSU, Command::ReadVin)?;
+//! let vout_mode = smbus.vout_mode(PSU)?;
+//! let vout = vout_mode.decode(smbus.pmbus_read_word(PSU, Command::ReadVout)?);
+//! ```
+use core::ops::Deref;
+
+use super::smbus::Smbus;
+use super::Error;
+use crate::stm32::i2c1::RegisterBlock;
+
+/// Standard PMBus command codes
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Command {
+    Page = 0x00,
+    Operation = 0x01,
+    OnOffConfig = 0x02,
+    ClearFaults = 0x03,
+    Phase = 0x04,
+    WriteProtect = 0x10,
+    Capability = 0x19,
+    VoutMode = 0x20,
+    VoutCommand = 0x21,
+    VoutMarginHigh = 0x25,
+    VoutMarginLow = 0x26,
+    VinOn = 0x35,
+    VinOff = 0x36,
+    IoutOcFaultLimit = 0x46,
+    OtFaultLimit = 0x4F,
+    StatusByte = 0x78,
+    StatusWord = 0x79,
+    StatusVout = 0x7A,
+    StatusIout = 0x7B,
+    StatusInput = 0x7C,
+    StatusTemperature = 0x7D,
+    StatusCml = 0x7E,
+    ReadVin = 0x88,
+    ReadIin = 0x89,
+    ReadVout = 0x8B,
+    ReadIout = 0x8C,
+    ReadTemperature1 = 0x8D,
+    ReadTemperature2 = 0x8E,
+    ReadFanSpeed1 = 0x90,
+    ReadPout = 0x96,
+    ReadPin = 0x97,
+    PmbusRevision = 0x98,
+    MfrId = 0x99,
+    MfrModel = 0x9A,
+    MfrRevision = 0x9B,
+}
+
+/// Returns 2^`exponent`
+fn scale(exponent: i8) -> f32 {
+    if exponent >= 0 {
+        (1u32 << exponent) as f32
+    } else {
+        1.0 / (1u32 << -exponent) as f32
+    }
+}
+
+/// Rounds `value` to the nearest integer
+fn round(value: f32) -> i32 {
+    if value >= 0.0 {
+        (value + 0.5) as i32
+    } else {
+        (value - 0.5) as i32
+    }
+}
+
+/// Decodes a LINEAR11 value: an 11-bit signed mantissa and a 5-bit signed exponent
+pub fn from_linear11(raw: u16) -> f32 {
+    let exponent = (raw as i16 >> 11) as i8;
+    let mantissa = (raw << 5) as i16 >> 5;
+    mantissa as f32 * scale(exponent)
+}
+
+/// Encodes `value` as LINEAR11, with the smallest exponent which fits the mantissa
+pub fn to_linear11(value: f32) -> u16 {
+    let mut exponent = -16;
+    let mut mantissa = round(value / scale(exponent));
+    while !(-1024..=1023).contains(&mantissa) && exponent < 15 {
+        exponent += 1;
+        mantissa = round(value / scale(exponent));
+    }
+    let mantissa = mantissa.clamp(-1024, 1023);
+    ((exponent as u16) << 11) | (mantissa as u16 & 0x7FF)
+}
+
+/// Data format of the output voltage commands, read from VOUT_MODE
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum VoutMode {
+    /// LINEAR16: an unsigned mantissa with this exponent
+    Linear(i8),
+    /// VID codes of this type
+    Vid(u8),
+    /// DIRECT format, with the coefficients read with COEFFICIENTS
+    Direct,
+    /// IEEE 754 half precision
+    HalfPrecision,
+}
+
+impl VoutMode {
+    /// Parses a VOUT_MODE byte
+    pub fn from_bits(bits: u8) -> Self {
+        let parameter = bits & 0x1F;
+        match (bits >> 5) & 0b11 {
+            0b00 => VoutMode::Linear(((parameter << 3) as i8) >> 3),
+            0b01 => VoutMode::Vid(parameter),
+            0b10 => VoutMode::Direct,
+            _ => VoutMode::HalfPrecision,
+        }
+    }
+
+    /// Decodes an output voltage in volts, only the LINEAR16 format is supported
+    pub fn decode(self, raw: u16) -> Option<f32> {
+        match self {
+            VoutMode::Linear(exponent) => Some(raw as f32 * scale(exponent)),
+            _ => None,
+        }
+    }
+
+    /// Encodes an output voltage in volts, only the LINEAR16 format is supported
+    pub fn encode(self, volts: f32) -> Option<u16> {
+        match self {
+            VoutMode::Linear(exponent) => {
+                Some(round(volts / scale(exponent)).clamp(0, u16::MAX as i32) as u16)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<I2C, SDA, SCL> Smbus<I2C, SDA, SCL>
+where
+    I2C: Deref<Target = RegisterBlock>,
+{
+    /// Sends a command without data, such as CLEAR_FAULTS
+    pub fn pmbus_send(&mut self, address: u8, command: Command) -> Result<(), Error> {
+        self.send_byte(address, command as u8)
+    }
+
+    /// Reads a byte command
+    pub fn pmbus_read_byte(&mut self, address: u8, command: Command) -> Result<u8, Error> {
+        self.read_byte(address, command as u8)
+    }
+
+    /// Writes a byte command
+    pub fn pmbus_write_byte(
+        &mut self,
+        address: u8,
+        command: Command,
+        byte: u8,
+    ) -> Result<(), Error> {
+        self.write_byte(address, command as u8, byte)
+    }
+
+    /// Reads a word command
+    pub fn pmbus_read_word(&mut self, address: u8, command: Command) -> Result<u16, Error> {
+        self.read_word(address, command as u8)
+    }
+
+    /// Writes a word command
+    pub fn pmbus_write_word(
+        &mut self,
+        address: u8,
+        command: Command,
+        word: u16,
+    ) -> Result<(), Error> {
+        self.write_word(address, command as u8, word)
+    }
+
+    /// Reads a block command, such as MFR_MODEL, returns the number of bytes read
+    pub fn pmbus_block_read(
+        &mut self,
+        address: u8,
+        command: Command,
+        buffer: &mut [u8],
+    ) -> Result<usize, Error> {
+        self.block_read(address, command as u8, buffer)
+    }
+
+    /// Selects the page, the output or channel addressed by the following commands
+    pub fn set_page(&mut self, address: u8, page: u8) -> Result<(), Error> {
+        self.pmbus_write_byte(address, Command::Page, page)
+    }
+
+    /// Clears the status and fault flags of the device
+    pub fn clear_faults(&mut self, address: u8) -> Result<(), Error> {
+        self.pmbus_send(address, Command::ClearFaults)
+    }
+
+    /// Reads the data format of the output voltage commands
+    pub fn vout_mode(&mut self, address: u8) -> Result<VoutMode, Error> {
+        Ok(VoutMode::from_bits(
+            self.pmbus_read_byte(address, Command::VoutMode)?,
+        ))
+    }
+
+    /// Reads a LINEAR11 command, such as READ_VIN, READ_IOUT or READ_TEMPERATURE_1
+    pub fn read_linear11(&mut self, address: u8, command: Command) -> Result<f32, Error> {
+        Ok(from_linear11(self.pmbus_read_word(address, command)?))
+    }
+
+    /// Writes a LINEAR11 command, such as VIN_ON or IOUT_OC_FAULT_LIMIT
+    pub fn write_linear11(
+        &mut self,
+        address: u8,
+        command: Command,
+        value: f32,
+    ) -> Result<(), Error> {
+        self.pmbus_write_word(address, command, to_linear11(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear11_decoding() {
+        assert_eq!(from_linear11(0x0000), 0.0);
+        // Mantissa 100, exponent -2
+        assert_eq!(from_linear11(0xF064), 25.0);
+        // Mantissa -1, exponent 0
+        assert_eq!(from_linear11(0x07FF), -1.0);
+        // Mantissa 3, exponent 4
+        assert_eq!(from_linear11(0x2003), 48.0);
+        // Mantissa 66, exponent -16
+        assert_eq!(from_linear11(0x8042), 66.0 / 65536.0);
+    }
+
+    #[test]
+    fn linear11_encoding() {
+        // The smallest exponent keeps the most significant bits
+        assert_eq!(to_linear11(25.0), (-5i8 as u16) << 11 | 800);
+        assert_eq!(
+            to_linear11(-1.0),
+            (-10i8 as u16) << 11 | (-1024i16 as u16 & 0x7FF)
+        );
+        assert_eq!(to_linear11(0.001), 0x8042);
+        assert_eq!(to_linear11(0.0), 0x8000);
+    }
+
+    #[test]
+    fn linear11_round_trip() {
+        for &value in &[0.001, 0.5, 3.3, -12.5, 1000.0, 60_000.0, -1_000_000.0] {
+            let decoded = from_linear11(to_linear11(value));
+            let tolerance = value.abs() / 1000.0 + 1.0 / 131_072.0;
+            assert!(
+                (decoded - value).abs() <= tolerance,
+                "{} decoded as {}",
+                value,
+                decoded
+            );
+        }
+        for raw in [0xF064, 0x07FF, 0x8042, 0x7BFF, 0x7C00] {
+            assert_eq!(
+                from_linear11(to_linear11(from_linear11(raw))),
+                from_linear11(raw)
+            );
+        }
+    }
+
+    #[test]
+    fn linear11_saturation() {
+        // Mantissa 1023 and -1024 with the largest exponent, 15
+        assert_eq!(to_linear11(1e12), 0x7BFF);
+        assert_eq!(to_linear11(-1e12), 0x7C00);
+        assert_eq!(from_linear11(0x7BFF), 1023.0 * 32768.0);
+        assert_eq!(from_linear11(0x7C00), -1024.0 * 32768.0);
+    }
+}
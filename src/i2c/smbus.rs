@@ -0,0 +1,476 @@
+//! SMBus host
+//!
+//! [`Smbus`] runs the SMBus protocols on top of an [`I2c`]: quick command, send/receive byte,
+//! read/write byte and word, process call, block read/write and block process call. The Packet
+//! Error Code (PEC) is optionally computed by the peripheral, appended to the writes and verified
+//! on the reads.
+//!
+//! ```ignore
+//! let i2c = dp.I2C1.i2c(sda, scl, Config::new(100.kHz()), &mut rcc);
+//! let mut smbus = i2c.smbus(SmbusConfig::new().pec(true), &rcc)?;
+//!
+//! let status = smbus.read_word(0x58, 0x79)?;
+//! let mut model = [0; 32];
+//! let len = smbus.block_read(0x58, 0x9A, &mut model)?;
+//! ```
+//!
+//! The bus timeouts of the SMBus specification are detected by the peripheral, a transfer
+//! stalled by a device holding SCL low fails with [`Error::Timeout`]. Devices request the
+//! attention of the host by pulling the SMBA pin low, see [`Smbus::enable_alert`].
+use core::ops::Deref;
+
+use super::{read_byte, reload_chunk, start_transfer, write_byte, Address, Error, I2c, SMBAPin};
+use crate::stm32::i2c1::RegisterBlock;
+use crate::time::{ExtU32, Hertz, MicroSecond};
+
+/// Address answered by the devices pulling the SMBA pin low
+pub const ALERT_RESPONSE_ADDRESS: u8 = 0x0C;
+
+/// Longest block transfer allowed by the SMBus 3.0 specification
+pub const MAX_BLOCK_LEN: usize = 255;
+
+/// Computes the Packet Error Code of `bytes`, a CRC-8 with the polynomial x^8 + x^2 + x + 1,
+/// starting from the PEC of the previous bytes of the transaction.
+///
+/// [`Smbus`] lets the peripheral compute the PEC, this is the same computation in software.
+pub fn pec(mut crc: u8, bytes: &[u8]) -> u8 {
+    for byte in bytes {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// A timeout of the [`SmbusConfig`] can not be counted with the kernel clock, it is shorter
+/// than 2048 kernel clock cycles or longer than 4096 times that
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum TimeoutError {
+    /// The SCL low timeout, see [`SmbusConfig::timeout`]
+    Timeout,
+    /// The clock extension timeout, see [`SmbusConfig::extended_timeout`]
+    ExtendedTimeout,
+}
+
+/// SMBus host configuration
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct SmbusConfig {
+    pec: bool,
+    timeout: Option<MicroSecond>,
+    extended_timeout: Option<MicroSecond>,
+}
+
+impl SmbusConfig {
+    /// Creates a configuration without PEC, detecting SCL held low for more than 25 ms
+    pub fn new() -> Self {
+        SmbusConfig {
+            pec: false,
+            timeout: Some(25_000.micros()),
+            extended_timeout: None,
+        }
+    }
+
+    /// Appends the PEC to the writes and verifies it on the reads
+    pub fn pec(mut self, enable: bool) -> Self {
+        self.pec = enable;
+        self
+    }
+
+    /// Fails the transfers once SCL is held low for longer than `timeout` (tTIMEOUT, 25 to
+    /// 35 ms in the specification), `None` disables the detection
+    pub fn timeout(mut self, timeout: Option<MicroSecond>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Fails the transfers once the host stretched the clock of a byte for longer than
+    /// `timeout` in total (tLOW:MEXT, 10 ms in the specification), `None` disables the
+    /// detection
+    pub fn extended_timeout(mut self, timeout: Option<MicroSecond>) -> Self {
+        self.extended_timeout = timeout;
+        self
+    }
+}
+
+impl Default for SmbusConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Data read in the second part of a transfer
+enum Read<'a> {
+    None,
+    /// A fixed number of bytes
+    Bytes(&'a mut [u8]),
+    /// A byte count followed by as many bytes
+    Block(&'a mut [u8]),
+}
+
+/// SMBus host on an I2C peripheral
+pub struct Smbus<I2C, SDA, SCL> {
+    i2c: I2c<I2C, SDA, SCL>,
+    pec: bool,
+}
+
+/// Converts `timeout` to a TIMEOUTR count of 2048 kernel clock cycles, `None` if it does not
+/// fit the 12-bit count
+fn timeout_bits(timeout: MicroSecond, i2c_clk: Hertz) -> Option<u16> {
+    let cycles = timeout.ticks() as u64 * i2c_clk.raw() as u64 / 1_000_000 / 2048;
+    if (1..=0x1000).contains(&cycles) {
+        Some(cycles as u16 - 1)
+    } else {
+        None
+    }
+}
+
+impl<I2C, SDA, SCL> Smbus<I2C, SDA, SCL>
+where
+    I2C: Deref<Target = RegisterBlock>,
+{
+    pub(super) fn init(
+        i2c: I2c<I2C, SDA, SCL>,
+        config: SmbusConfig,
+        i2c_clk: Hertz,
+    ) -> Result<Self, TimeoutError> {
+        let timeout = match config.timeout {
+            Some(timeout) => Some(timeout_bits(timeout, i2c_clk).ok_or(TimeoutError::Timeout)?),
+            None => None,
+        };
+        let extended_timeout = match config.extended_timeout {
+            Some(timeout) => {
+                Some(timeout_bits(timeout, i2c_clk).ok_or(TimeoutError::ExtendedTimeout)?)
+            }
+            None => None,
+        };
+        let regs = &i2c.i2c;
+
+        // The timeouts can only be written while they are disabled
+        regs.timeoutr().write(|w| unsafe {
+            w.bits(0);
+        });
+        regs.timeoutr().write(|w| {
+            if let Some(bits) = timeout {
+                unsafe {
+                    w.timeouta().bits(bits);
+                }
+                // TIDLE cleared: SCL held low is detected
+                w.tidle().clear_bit().timouten().set_bit();
+            }
+            if let Some(bits) = extended_timeout {
+                unsafe {
+                    w.timeoutb().bits(bits);
+                }
+                w.texten().set_bit();
+            }
+        });
+
+        regs.cr1().modify(|_, w| {
+            w.pecen().bit(config.pec);
+        });
+
+        Ok(Smbus {
+            i2c,
+            pec: config.pec,
+        })
+    }
+
+    /// Disables the timeouts, the PEC and the alert, and releases the I2C peripheral
+    pub fn release(self) -> I2c<I2C, SDA, SCL> {
+        self.i2c.i2c.timeoutr().write(|w| unsafe {
+            w.bits(0);
+        });
+        self.i2c.i2c.cr1().modify(|_, w| {
+            w.alerten().clear_bit().pecen().clear_bit();
+        });
+        self.i2c
+    }
+
+    /// Quick command: the read/write bit sent with `address` is the data
+    pub fn quick_command(&mut self, address: u8, read: bool) -> Result<(), Error> {
+        let read = if read {
+            Read::Bytes(&mut [])
+        } else {
+            Read::None
+        };
+        self.transfer(address, &[], read).map(|_| ())
+    }
+
+    /// Send byte protocol
+    pub fn send_byte(&mut self, address: u8, byte: u8) -> Result<(), Error> {
+        self.transfer(address, &[&[byte]], Read::None).map(|_| ())
+    }
+
+    /// Receive byte protocol
+    pub fn receive_byte(&mut self, address: u8) -> Result<u8, Error> {
+        let mut byte = [0];
+        self.transfer(address, &[], Read::Bytes(&mut byte))?;
+        Ok(byte[0])
+    }
+
+    /// Write byte protocol
+    pub fn write_byte(&mut self, address: u8, command: u8, byte: u8) -> Result<(), Error> {
+        self.transfer(address, &[&[command, byte]], Read::None)
+            .map(|_| ())
+    }
+
+    /// Write word protocol, the word is sent least significant byte first
+    pub fn write_word(&mut self, address: u8, command: u8, word: u16) -> Result<(), Error> {
+        let [low, high] = word.to_le_bytes();
+        self.transfer(address, &[&[command, low, high]], Read::None)
+            .map(|_| ())
+    }
+
+    /// Read byte protocol
+    pub fn read_byte(&mut self, address: u8, command: u8) -> Result<u8, Error> {
+        let mut byte = [0];
+        self.transfer(address, &[&[command]], Read::Bytes(&mut byte))?;
+        Ok(byte[0])
+    }
+
+    /// Read word protocol, the word is received least significant byte first
+    pub fn read_word(&mut self, address: u8, command: u8) -> Result<u16, Error> {
+        let mut word = [0; 2];
+        self.transfer(address, &[&[command]], Read::Bytes(&mut word))?;
+        Ok(u16::from_le_bytes(word))
+    }
+
+    /// Process call protocol: writes a word and reads the answer of the device
+    pub fn process_call(&mut self, address: u8, command: u8, word: u16) -> Result<u16, Error> {
+        let [low, high] = word.to_le_bytes();
+        let mut answer = [0; 2];
+        self.transfer(address, &[&[command, low, high]], Read::Bytes(&mut answer))?;
+        Ok(u16::from_le_bytes(answer))
+    }
+
+    /// Block write protocol, `data` is at most [`MAX_BLOCK_LEN`] bytes long
+    pub fn block_write(&mut self, address: u8, command: u8, data: &[u8]) -> Result<(), Error> {
+        assert!(data.len() <= MAX_BLOCK_LEN);
+        self.transfer(address, &[&[command, data.len() as u8], data], Read::None)
+            .map(|_| ())
+    }
+
+    /// Block read protocol, returns the number of bytes read into `buffer`.
+    ///
+    /// A block longer than `buffer` is read to its end but only its first bytes are kept, this
+    /// is reported as [`Error::Overrun`].
+    pub fn block_read(
+        &mut self,
+        address: u8,
+        command: u8,
+        buffer: &mut [u8],
+    ) -> Result<usize, Error> {
+        self.transfer(address, &[&[command]], Read::Block(buffer))
+    }
+
+    /// Block write-block read process call, returns the number of bytes read into `buffer`
+    pub fn block_process_call(
+        &mut self,
+        address: u8,
+        command: u8,
+        data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<usize, Error> {
+        assert!(data.len() <= MAX_BLOCK_LEN);
+        self.transfer(
+            address,
+            &[&[command, data.len() as u8], data],
+            Read::Block(buffer),
+        )
+    }
+
+    /// Reads the address of a device pulling the SMBA pin low from the alert response address.
+    ///
+    /// Devices answer by priority, the device with the lowest address stops pulling SMBA.
+    pub fn alert_response(&mut self) -> Result<u8, Error> {
+        Ok(self.receive_byte(ALERT_RESPONSE_ADDRESS)? >> 1)
+    }
+
+    /// Reports the falling edges of the SMBA pin through [`is_alert`](Self::is_alert)
+    pub fn enable_alert<SMBA>(&mut self, _smba: &SMBA)
+    where
+        SMBA: SMBAPin<I2C>,
+    {
+        self.i2c.i2c.cr1().modify(|_, w| {
+            w.alerten().set_bit();
+        });
+    }
+
+    /// Stops reporting the alerts
+    pub fn disable_alert(&mut self) {
+        self.i2c.i2c.cr1().modify(|_, w| {
+            w.alerten().clear_bit();
+        });
+    }
+
+    /// Returns `true` if a device pulled the SMBA pin low
+    pub fn is_alert(&self) -> bool {
+        self.i2c.i2c.isr().read().alert().bit_is_set()
+    }
+
+    /// Clears the alert flag
+    pub fn clear_alert(&mut self) {
+        self.i2c.i2c.icr().write(|w| {
+            w.alertcf().set_bit();
+        });
+    }
+
+    /// Enables the error interrupt, raised on alerts as well as on timeouts and bus errors
+    pub fn listen_alert(&mut self) {
+        self.i2c.i2c.cr1().modify(|_, w| {
+            w.errie().set_bit();
+        });
+    }
+
+    /// Disables the error interrupt
+    pub fn unlisten_alert(&mut self) {
+        self.i2c.i2c.cr1().modify(|_, w| {
+            w.errie().clear_bit();
+        });
+    }
+
+    /// Writes `write` to `address`, then reads `read` after a repeated START. The PEC covers
+    /// the address bytes and all the data of the transaction.
+    fn transfer(&mut self, address: u8, write: &[&[u8]], read: Read<'_>) -> Result<usize, Error> {
+        let i2c: &RegisterBlock = &self.i2c.i2c;
+
+        // Wait for any previous address sequence to end automatically.
+        while i2c.cr2().read().start().bit_is_set() {}
+
+        let len: usize = write.iter().map(|bytes| bytes.len()).sum();
+
+        if len > 0 || matches!(read, Read::None) {
+            // The PEC ends the transaction, the quick command has none
+            let send_pec = self.pec && len > 0 && matches!(read, Read::None);
+            let (mut chunk, mut remaining) = start_transfer(
+                i2c,
+                Address::SevenBit(address),
+                false,
+                len + send_pec as usize,
+                false,
+                send_pec,
+            );
+
+            // The peripheral sends the PEC after the data
+            for bytes in write {
+                for &byte in bytes.iter() {
+                    reload_chunk(i2c, &mut chunk, &mut remaining)?;
+                    write_byte(i2c, byte)?;
+                }
+            }
+
+            busy_wait!(i2c, tc, bit_is_set);
+        }
+
+        let result = match read {
+            Read::None => Ok(0),
+            Read::Bytes(buffer) => {
+                let check_pec = self.pec && !buffer.is_empty();
+                let (mut chunk, mut remaining) = start_transfer(
+                    i2c,
+                    Address::SevenBit(address),
+                    true,
+                    buffer.len() + check_pec as usize,
+                    false,
+                    check_pec,
+                );
+
+                for byte in buffer.iter_mut() {
+                    reload_chunk(i2c, &mut chunk, &mut remaining)?;
+                    *byte = read_byte(i2c)?;
+                }
+
+                // The peripheral checks the PEC it received
+                if check_pec {
+                    reload_chunk(i2c, &mut chunk, &mut remaining)?;
+                    read_byte(i2c)?;
+                }
+                Ok(buffer.len())
+            }
+            Read::Block(buffer) => {
+                // The byte count is read first, the length of the transfer is updated with it
+                start_transfer(i2c, Address::SevenBit(address), true, 1, true, self.pec);
+                let count = read_byte(i2c)? as usize;
+
+                // The byte count has been acknowledged: without data nor PEC to follow, one more
+                // byte is read and not acknowledged to end the transfer
+                let dummy = count == 0 && !self.pec;
+
+                // The first chunk is loaded once the byte count has been transferred
+                let mut chunk = 0;
+                let mut remaining = count + self.pec as usize + dummy as usize;
+
+                for i in 0..count {
+                    reload_chunk(i2c, &mut chunk, &mut remaining)?;
+                    let byte = read_byte(i2c)?;
+                    // The end of a block longer than the buffer is dropped
+                    if let Some(slot) = buffer.get_mut(i) {
+                        *slot = byte;
+                    }
+                }
+
+                if self.pec || dummy {
+                    reload_chunk(i2c, &mut chunk, &mut remaining)?;
+                    read_byte(i2c)?;
+                }
+                if count > buffer.len() {
+                    Err(Error::Overrun)
+                } else {
+                    Ok(count)
+                }
+            }
+        };
+
+        busy_wait!(i2c, tc, bit_is_set);
+        i2c.cr2().modify(|_, w| {
+            w.stop().set_bit();
+        });
+
+        if i2c.isr().read().pecerr().bit_is_set() {
+            i2c.icr().write(|w| {
+                w.peccf().set_bit();
+            });
+            return Err(Error::PECError);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::RateExtU32;
+
+    #[test]
+    fn pec_check_value() {
+        assert_eq!(pec(0, b"123456789"), 0xF4);
+        assert_eq!(pec(0, &[]), 0);
+    }
+
+    #[test]
+    fn pec_is_incremental() {
+        let bytes = b"123456789";
+        for split in 0..=bytes.len() {
+            let (first, last) = bytes.split_at(split);
+            assert_eq!(pec(pec(0, first), last), 0xF4);
+        }
+        // The PEC of a transaction followed by its PEC is 0
+        assert_eq!(pec(0, b"123456789\xF4"), 0);
+    }
+
+    #[test]
+    fn timeouts() {
+        // 25 ms at 16 MHz is 195 periods of 2048 cycles
+        assert_eq!(timeout_bits(25_000.micros(), 16.MHz()), Some(194));
+        assert_eq!(timeout_bits(128.micros(), 16.MHz()), Some(0));
+        assert_eq!(timeout_bits(127.micros(), 16.MHz()), None);
+        assert_eq!(timeout_bits(49_346.micros(), 170.MHz()), Some(0xFFF));
+        assert_eq!(timeout_bits(49_400.micros(), 170.MHz()), None);
+    }
+}
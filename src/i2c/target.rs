@@ -0,0 +1,586 @@
+//! I2C target mode
+//!
+//! [`I2cTarget`] answers a controller on the bus at its own addresses: a 7-bit or 10-bit primary
+//! address, an optional 7-bit secondary address with masked bits, and optionally the general
+//! call address. The bus activity is reported by [`I2cTarget::next_event`], which can be polled
+//! or called from the event and error interrupts.
+//!
+//! ```ignore
+//! let mut target = dp.I2C1.i2c_target(
+//!     sda,
+//!     scl,
+//!     Config::new(400.kHz()),
+//!     TargetConfig::new(Address::SevenBit(0x42)),
+//!     &mut rcc,
+//! );
+//!
+//! let mut register = 0;
+//! loop {
+//!     match block!(target.next_event()) {
+//!         Ok(TargetEvent::Received(byte)) => register = byte,
+//!         Ok(TargetEvent::TransmitRequest) => target.write_byte(register),
+//!         _ => {}
+//!     }
+//! }
+//! ```
+//!
+//! With clock stretching enabled (the default), SCL is held low after the address match and
+//! while a byte has to be read or written, so the events can be served at any pace. Without
+//! clock stretching the first byte of a read has to be written before the controller addresses
+//! the target, and each event has to be served within a bit period.
+use core::ops::Deref;
+
+use super::smbus::ALERT_RESPONSE_ADDRESS;
+use super::{Address, Config, Error, SCLPin, SDAPin, SMBAPin, TimingError};
+use crate::dma::{mux::DmaMuxResources, traits::TargetAddress};
+use crate::dma::{MemoryToPeripheral, PeripheralToMemory};
+use crate::rcc::{Enable, GetBusFreq, Rcc, RccBus, Reset};
+use crate::stm32::i2c1::RegisterBlock;
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+use crate::stm32::I2C4;
+use crate::stm32::{I2C1, I2C2, I2C3, RCC};
+
+/// Low bits of the secondary address which are ignored when matching an address
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Oa2Mask {
+    /// The address has to match exactly
+    NoMask = 0,
+    /// Bit 0 is ignored
+    Mask1 = 1,
+    /// Bits 1 to 0 are ignored
+    Mask2 = 2,
+    /// Bits 2 to 0 are ignored
+    Mask3 = 3,
+    /// Bits 3 to 0 are ignored
+    Mask4 = 4,
+    /// Bits 4 to 0 are ignored
+    Mask5 = 5,
+    /// Bits 5 to 0 are ignored
+    Mask6 = 6,
+    /// All the addresses are matched, except the reserved ones
+    Mask7 = 7,
+}
+
+/// Addressing and clock stretching of the target
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct TargetConfig {
+    address: Address,
+    secondary_address: Option<(u8, Oa2Mask)>,
+    general_call: bool,
+    clock_stretching: bool,
+    smbus_host: bool,
+    smbus_device_default: bool,
+}
+
+impl TargetConfig {
+    /// Creates a configuration answering at `address`, with clock stretching
+    pub fn new(address: Address) -> Self {
+        TargetConfig {
+            address,
+            secondary_address: None,
+            general_call: false,
+            clock_stretching: true,
+            smbus_host: false,
+            smbus_device_default: false,
+        }
+    }
+
+    /// Also answers at the 7-bit `address`, ignoring the bits masked by `mask`
+    pub fn secondary_address(mut self, address: u8, mask: Oa2Mask) -> Self {
+        assert!(address < 0x80);
+        self.secondary_address = Some((address, mask));
+        self
+    }
+
+    /// Also answers the general call address `0x00`
+    pub fn general_call(mut self, enable: bool) -> Self {
+        self.general_call = enable;
+        self
+    }
+
+    /// Enables or disables clock stretching
+    pub fn clock_stretching(mut self, enable: bool) -> Self {
+        self.clock_stretching = enable;
+        self
+    }
+
+    /// Also answers the SMBus host address `0x08`, used by the devices to notify the host
+    pub fn smbus_host(mut self, enable: bool) -> Self {
+        self.smbus_host = enable;
+        self
+    }
+
+    /// Also answers the SMBus device default address `0x61`, used to assign the addresses of
+    /// the devices
+    pub fn smbus_device_default(mut self, enable: bool) -> Self {
+        self.smbus_device_default = enable;
+        self
+    }
+}
+
+/// Address a transfer has been addressed to
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum AddressMatch {
+    /// The primary address
+    Primary,
+    /// The secondary address, with the 7-bit address sent by the controller
+    Secondary(u8),
+    /// The general call address
+    GeneralCall,
+    /// The SMBus host address
+    SmbusHost,
+    /// The SMBus device default address
+    SmbusDeviceDefault,
+    /// The SMBus alert response address, while the alert is asserted
+    AlertResponse,
+}
+
+/// Bus activity involving the target
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum TargetEvent {
+    /// The controller starts writing to the target
+    Write(AddressMatch),
+    /// The controller starts reading from the target
+    Read(AddressMatch),
+    /// A byte has been written by the controller
+    Received(u8),
+    /// The controller reads the next byte, which has to be written with
+    /// [`I2cTarget::write_byte`]
+    TransmitRequest,
+    /// The controller did not acknowledge the last byte it read, which ends the read
+    Nack,
+    /// The controller ended the transfer with a STOP condition
+    Stop,
+}
+
+/// I2C peripheral in target mode
+pub struct I2cTarget<I2C, SDA, SCL> {
+    i2c: I2C,
+    sda: SDA,
+    scl: SCL,
+    config: TargetConfig,
+}
+
+pub trait I2cTargetExt<I2C> {
+    /// Initializes the I2C peripheral in target mode, see [`I2cTargetExt::try_i2c_target`].
+    ///
+    /// Panics if the bus timing can not be met with the kernel clock, in particular for a bus
+    /// speed below about 1/8192 of the kernel clock, for example 21 kHz at 170 MHz.
+    fn i2c_target<SDA, SCL>(
+        self,
+        sda: SDA,
+        scl: SCL,
+        config: Config,
+        target: TargetConfig,
+        rcc: &mut Rcc,
+    ) -> I2cTarget<I2C, SDA, SCL>
+    where
+        SDA: SDAPin<I2C>,
+        SCL: SCLPin<I2C>;
+
+    /// Initializes the I2C peripheral in target mode, or returns why the bus timing can not be
+    /// met with the kernel clock.
+    fn try_i2c_target<SDA, SCL>(
+        self,
+        sda: SDA,
+        scl: SCL,
+        config: Config,
+        target: TargetConfig,
+        rcc: &mut Rcc,
+    ) -> Result<I2cTarget<I2C, SDA, SCL>, TimingError>
+    where
+        SDA: SDAPin<I2C>,
+        SCL: SCLPin<I2C>;
+}
+
+impl<I2C, SDA, SCL> I2cTarget<I2C, SDA, SCL>
+where
+    I2C: Deref<Target = RegisterBlock>,
+{
+    fn init(
+        i2c: I2C,
+        sda: SDA,
+        scl: SCL,
+        config: Config,
+        target: TargetConfig,
+        timing_bits: u32,
+    ) -> Self {
+        // Make sure the I2C unit is disabled so we can configure it
+        i2c.cr1().modify(|_, w| {
+            w.pe().clear_bit();
+        });
+
+        // The data setup and hold times also apply in target mode
+        i2c.timingr().write(|w| unsafe {
+            w.bits(timing_bits);
+        });
+
+        // The own addresses can only be written while they are disabled
+        i2c.oar1().write(|w| {
+            w.oa1en().clear_bit();
+        });
+        match target.address {
+            Address::SevenBit(address) => i2c.oar1().write(|w| unsafe {
+                w.oa1()
+                    .bits((address as u16) << 1)
+                    .oa1mode()
+                    .clear_bit()
+                    .oa1en()
+                    .set_bit();
+            }),
+            Address::TenBit(address) => i2c.oar1().write(|w| unsafe {
+                w.oa1()
+                    .bits(address & 0x3FF)
+                    .oa1mode()
+                    .set_bit()
+                    .oa1en()
+                    .set_bit();
+            }),
+        };
+
+        i2c.oar2().write(|w| {
+            w.oa2en().clear_bit();
+        });
+        if let Some((address, mask)) = target.secondary_address {
+            i2c.oar2().write(|w| unsafe {
+                w.oa2()
+                    .bits(address)
+                    .oa2msk()
+                    .bits(mask as u8)
+                    .oa2en()
+                    .set_bit();
+            });
+        }
+
+        i2c.cr1().modify(|_, w| {
+            unsafe {
+                w.dnf().bits(config.digital_filter);
+            }
+            w.anfoff()
+                .bit(!config.analog_filter)
+                .gcen()
+                .bit(target.general_call)
+                .nostretch()
+                .bit(!target.clock_stretching)
+                .smbhen()
+                .bit(target.smbus_host)
+                .smbden()
+                .bit(target.smbus_device_default)
+                .sbc()
+                .clear_bit()
+                .pe()
+                .set_bit();
+        });
+
+        I2cTarget {
+            i2c,
+            sda,
+            scl,
+            config: target,
+        }
+    }
+
+    /// Returns the next bus event, or `WouldBlock` if there is none.
+    ///
+    /// The address match is acknowledged by this call, with clock stretching SCL is released
+    /// when the event is returned.
+    pub fn next_event(&mut self) -> nb::Result<TargetEvent, Error> {
+        let isr = self.i2c.isr().read();
+
+        if isr.berr().bit_is_set() {
+            self.i2c.icr().write(|w| {
+                w.berrcf().set_bit();
+            });
+            return Err(nb::Error::Other(Error::BusError));
+        }
+        if isr.arlo().bit_is_set() {
+            self.i2c.icr().write(|w| {
+                w.arlocf().set_bit();
+            });
+            return Err(nb::Error::Other(Error::ArbitrationLost));
+        }
+        if isr.timeout().bit_is_set() {
+            self.i2c.icr().write(|w| {
+                w.timoutcf().set_bit();
+            });
+            return Err(nb::Error::Other(Error::Timeout));
+        }
+        if isr.ovr().bit_is_set() {
+            self.i2c.icr().write(|w| {
+                w.ovrcf().set_bit();
+            });
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+
+        // The last byte of a write is read before a repeated START or a STOP is reported
+        if isr.rxne().bit_is_set() {
+            return Ok(TargetEvent::Received(
+                self.i2c.rxdr().read().rxdata().bits(),
+            ));
+        }
+        if isr.nackf().bit_is_set() {
+            self.i2c.icr().write(|w| {
+                w.nackcf().set_bit();
+            });
+            return Ok(TargetEvent::Nack);
+        }
+        if isr.stopf().bit_is_set() {
+            self.i2c.icr().write(|w| {
+                w.stopcf().set_bit();
+            });
+            // A byte written for a read which the controller ended earlier is not sent
+            if self.config.clock_stretching {
+                self.i2c.isr().write(|w| {
+                    w.txe().set_bit();
+                });
+            }
+            return Ok(TargetEvent::Stop);
+        }
+        if isr.addr().bit_is_set() {
+            let matched = self.address_match(isr.addcode().bits());
+            let read = isr.dir().bit_is_set();
+            if read && self.config.clock_stretching {
+                // Discard a byte left over from a previous read
+                self.i2c.isr().write(|w| {
+                    w.txe().set_bit();
+                });
+            }
+            self.i2c.icr().write(|w| {
+                w.addrcf().set_bit();
+            });
+            return Ok(if read {
+                TargetEvent::Read(matched)
+            } else {
+                TargetEvent::Write(matched)
+            });
+        }
+        if isr.txis().bit_is_set() {
+            return Ok(TargetEvent::TransmitRequest);
+        }
+
+        Err(nb::Error::WouldBlock)
+    }
+
+    /// Writes the next byte read by the controller
+    #[inline]
+    pub fn write_byte(&mut self, byte: u8) {
+        self.i2c.txdr().write(|w| unsafe {
+            w.txdata().bits(byte);
+        });
+    }
+
+    /// Enables the address match, STOP, NACK and error interrupts, as well as the receive and
+    /// transmit interrupts when the DMA does not serve them
+    pub fn listen(&mut self) {
+        let cr1 = self.i2c.cr1().read();
+        let rx_dma = cr1.rxdmaen().bit_is_set();
+        let tx_dma = cr1.txdmaen().bit_is_set();
+        self.i2c.cr1().modify(|_, w| {
+            w.addrie()
+                .set_bit()
+                .stopie()
+                .set_bit()
+                .nackie()
+                .set_bit()
+                .errie()
+                .set_bit()
+                .rxie()
+                .bit(!rx_dma)
+                .txie()
+                .bit(!tx_dma);
+        });
+    }
+
+    /// Disables the interrupts
+    pub fn unlisten(&mut self) {
+        self.i2c.cr1().modify(|_, w| {
+            w.addrie()
+                .clear_bit()
+                .stopie()
+                .clear_bit()
+                .nackie()
+                .clear_bit()
+                .errie()
+                .clear_bit()
+                .rxie()
+                .clear_bit()
+                .txie()
+                .clear_bit();
+        });
+    }
+
+    /// Lets the DMA read the received bytes, they are no longer reported by
+    /// [`next_event`](Self::next_event)
+    pub fn enable_rx_dma(&mut self) {
+        self.i2c.cr1().modify(|_, w| {
+            w.rxie().clear_bit().rxdmaen().set_bit();
+        });
+    }
+
+    /// Stops the DMA from reading the received bytes
+    pub fn disable_rx_dma(&mut self) {
+        self.i2c.cr1().modify(|_, w| {
+            w.rxdmaen().clear_bit();
+        });
+    }
+
+    /// Lets the DMA write the transmitted bytes, transmit requests are no longer reported by
+    /// [`next_event`](Self::next_event)
+    pub fn enable_tx_dma(&mut self) {
+        self.i2c.cr1().modify(|_, w| {
+            w.txie().clear_bit().txdmaen().set_bit();
+        });
+    }
+
+    /// Stops the DMA from writing the transmitted bytes
+    pub fn disable_tx_dma(&mut self) {
+        self.i2c.cr1().modify(|_, w| {
+            w.txdmaen().clear_bit();
+        });
+    }
+
+    /// Pulls the SMBA pin low to request the attention of the SMBus host, the target then
+    /// answers the alert response address until [`release_alert`](Self::release_alert)
+    pub fn assert_alert<SMBA>(&mut self, _smba: &SMBA)
+    where
+        SMBA: SMBAPin<I2C>,
+    {
+        self.i2c.cr1().modify(|_, w| {
+            w.alerten().set_bit();
+        });
+    }
+
+    /// Releases the SMBA pin
+    pub fn release_alert(&mut self) {
+        self.i2c.cr1().modify(|_, w| {
+            w.alerten().clear_bit();
+        });
+    }
+
+    fn address_match(&self, addcode: u8) -> AddressMatch {
+        let primary = match self.config.address {
+            Address::SevenBit(address) => address,
+            // The header of a 10-bit address ends with its two most significant bits
+            Address::TenBit(address) => 0b111_1000 | (address >> 8) as u8 & 0b11,
+        };
+        match addcode {
+            addcode if addcode == primary => AddressMatch::Primary,
+            0 if self.config.general_call => AddressMatch::GeneralCall,
+            0x08 if self.config.smbus_host => AddressMatch::SmbusHost,
+            0x61 if self.config.smbus_device_default => AddressMatch::SmbusDeviceDefault,
+            ALERT_RESPONSE_ADDRESS if self.i2c.cr1().read().alerten().bit_is_set() => {
+                AddressMatch::AlertResponse
+            }
+            addcode => AddressMatch::Secondary(addcode),
+        }
+    }
+}
+
+macro_rules! i2c_target {
+    ($($(#[$meta:meta])* $I2CX:ident: ($rx:ident, $tx:ident),)+) => {
+        $(
+            $(#[$meta])*
+            impl I2cTargetExt<$I2CX> for $I2CX {
+                fn i2c_target<SDA, SCL>(
+                    self,
+                    sda: SDA,
+                    scl: SCL,
+                    config: Config,
+                    target: TargetConfig,
+                    rcc: &mut Rcc,
+                ) -> I2cTarget<$I2CX, SDA, SCL>
+                where
+                    SDA: SDAPin<$I2CX>,
+                    SCL: SCLPin<$I2CX>,
+                {
+                    self.try_i2c_target(sda, scl, config, target, rcc)
+                        .expect("the I2C bus timing can not be met with this kernel clock")
+                }
+
+                fn try_i2c_target<SDA, SCL>(
+                    self,
+                    sda: SDA,
+                    scl: SCL,
+                    config: Config,
+                    target: TargetConfig,
+                    rcc: &mut Rcc,
+                ) -> Result<I2cTarget<$I2CX, SDA, SCL>, TimingError>
+                where
+                    SDA: SDAPin<$I2CX>,
+                    SCL: SCLPin<$I2CX>,
+                {
+                    let i2c_clk = <$I2CX as RccBus>::Bus::get_frequency(&rcc.clocks);
+                    let timing_bits = config.timing(i2c_clk)?;
+
+                    // Enable and reset I2C
+                    unsafe {
+                        let rcc_ptr = &(*RCC::ptr());
+                        $I2CX::enable(rcc_ptr);
+                        $I2CX::reset(rcc_ptr);
+                    }
+
+                    Ok(I2cTarget::init(self, sda, scl, config, target, timing_bits))
+                }
+            }
+
+            $(#[$meta])*
+            impl<SDA, SCL> I2cTarget<$I2CX, SDA, SCL> {
+                /// Disables I2C and releases the peripheral as well as the pins.
+                pub fn release(self) -> ($I2CX, SDA, SCL) {
+                    // Disable I2C.
+                    unsafe {
+                        let rcc_ptr = &(*RCC::ptr());
+                        $I2CX::reset(rcc_ptr);
+                        $I2CX::disable(rcc_ptr);
+                    }
+
+                    (self.i2c, self.sda, self.scl)
+                }
+            }
+
+            $(#[$meta])*
+            unsafe impl<SDA, SCL> TargetAddress<PeripheralToMemory> for I2cTarget<$I2CX, SDA, SCL> {
+                #[inline(always)]
+                fn address(&self) -> u32 {
+                    self.i2c.rxdr() as *const _ as u32
+                }
+
+                type MemSize = u8;
+
+                const REQUEST_LINE: Option<u8> = Some(DmaMuxResources::$rx as u8);
+            }
+
+            $(#[$meta])*
+            unsafe impl<SDA, SCL> TargetAddress<MemoryToPeripheral> for I2cTarget<$I2CX, SDA, SCL> {
+                #[inline(always)]
+                fn address(&self) -> u32 {
+                    self.i2c.txdr() as *const _ as u32
+                }
+
+                type MemSize = u8;
+
+                const REQUEST_LINE: Option<u8> = Some(DmaMuxResources::$tx as u8);
+            }
+        )+
+    };
+}
+
+i2c_target! {
+    I2C1: (I2C1_RX, I2C1_TX),
+    I2C2: (I2C2_RX, I2C2_TX),
+    I2C3: (I2C3_RX, I2C3_TX),
+    #[cfg(any(
+        feature = "stm32g471",
+        feature = "stm32g473",
+        feature = "stm32g474",
+        feature = "stm32g483",
+        feature = "stm32g484"
+    ))]
+    I2C4: (I2C4_RX, I2C4_TX),
+}
@@ -73,32 +73,41 @@ pub use stm32g4::stm32g4a1 as stm32;
 pub use crate::stm32::interrupt;
 
 pub mod adc;
+#[cfg(feature = "async")]
+pub mod asynch;
 pub mod bb;
 pub mod can;
+pub mod capture;
 pub mod comparator;
 #[cfg(feature = "cordic")]
 pub mod cordic;
-// pub mod crc;
+pub mod crc;
 pub mod dac;
 pub mod delay;
 pub mod dma;
 pub mod exti;
+pub mod fdcan;
 pub mod flash;
+#[cfg(feature = "fmac")]
+pub mod fmac;
 pub mod gpio;
+#[cfg(any(feature = "stm32g474", feature = "stm32g484"))]
+pub mod hrtim;
 pub mod i2c;
+pub mod lptim;
 pub mod opamp;
 pub mod prelude;
 pub mod pwm;
 pub mod pwr;
-// pub mod qei;
+pub mod qei;
 pub mod rcc;
-// pub mod rng;
+pub mod rng;
+pub mod rtc;
 pub mod serial;
 pub mod signature;
 pub mod spi;
-// pub mod stopwatch;
 pub mod syscfg;
 pub mod time;
 pub mod timer;
-// pub mod watchdog;
+pub mod watchdog;
 pub mod independent_watchdog;
@@ -0,0 +1,418 @@
+//! Low-power timer (LPTIM)
+//!
+//! `LPTIM1` is a 16-bit timer which keeps running in Stop mode when it is clocked by LSE, LSI or
+//! HSI16, or by an external clock on its IN1 pin. Its compare and auto-reload matches can wake
+//! the core up through EXTI line 37 ([`exti::Event::LPTIM1`](crate::exti::Event::LPTIM1)).
+//!
+//! ```ignore
+//! let mut lptim = dp.LPTIMER1.lptim(ClockSource::Lse, &mut rcc);
+//! lptim.set_prescaler(Prescaler::Div32);
+//!
+//! // Wake up every second
+//! lptim.listen(Event::AutoReloadMatch);
+//! lptim.enable_wakeup(&dp.EXTI);
+//! lptim.start(CountMode::Continuous, 1023);
+//! ```
+//!
+//! The counter can also count the edges of an external clock on IN1 with
+//! [`LowPowerTimer::count_external_clock`], or decode a quadrature encoder on IN1 and IN2 with
+//! [`LowPowerTimer::encoder`].
+use crate::exti::{self, ExtiExt};
+use crate::gpio::gpiob::{PB5, PB6, PB7};
+use crate::gpio::gpioc::{PC0, PC2, PC3};
+use crate::gpio::{Alternate, AF1, AF11};
+use crate::rcc::{Enable, Rcc, Reset};
+use crate::stm32::{EXTI, LPTIMER1};
+use crate::time::Hertz;
+
+/// Kernel clock of the timer
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ClockSource {
+    /// APB1 clock, stopped in Stop mode
+    Pclk = 0b00,
+    /// 32 kHz internal oscillator
+    Lsi = 0b01,
+    /// 16 MHz internal oscillator
+    Hsi16 = 0b10,
+    /// 32.768 kHz external oscillator
+    Lse = 0b11,
+}
+
+/// Division of the kernel clock
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Prescaler {
+    Div1 = 0b000,
+    Div2 = 0b001,
+    Div4 = 0b010,
+    Div8 = 0b011,
+    Div16 = 0b100,
+    Div32 = 0b101,
+    Div64 = 0b110,
+    Div128 = 0b111,
+}
+
+/// Counting of the timer once started
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum CountMode {
+    /// Count up to the auto-reload value once, then stop
+    OneShot,
+    /// Count up to the auto-reload value, wrap around to zero and keep counting
+    Continuous,
+}
+
+/// External trigger starting the counter
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Trigger {
+    /// ETR pin
+    Etr = 0b000,
+    /// RTC alarm A
+    RtcAlarmA = 0b001,
+    /// RTC alarm B
+    RtcAlarmB = 0b010,
+    /// RTC tamper 1
+    Tamp1 = 0b011,
+    /// RTC tamper 2
+    Tamp2 = 0b100,
+    /// RTC tamper 3
+    Tamp3 = 0b101,
+    /// Comparator 1 output
+    Comp1 = 0b110,
+    /// Comparator 2 output
+    Comp2 = 0b111,
+}
+
+/// Active edge of an input
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Edge {
+    /// Rising edges
+    Rising = 0b01,
+    /// Falling edges
+    Falling = 0b10,
+    /// Both edges
+    Both = 0b11,
+}
+
+/// Interrupt events
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Event {
+    /// The counter matched the compare value
+    CompareMatch,
+    /// The counter matched the auto-reload value
+    AutoReloadMatch,
+    /// The external trigger occurred
+    ExternalTrigger,
+    /// The counter of the encoder changed to counting up
+    Up,
+    /// The counter of the encoder changed to counting down
+    Down,
+}
+
+/// Pins usable as the IN1 input
+pub trait In1Pin {}
+/// Pins usable as the IN2 input
+pub trait In2Pin {}
+/// Pins usable as the ETR input
+pub trait EtrPin {}
+
+impl In1Pin for PB5<Alternate<AF11>> {}
+impl In1Pin for PC0<Alternate<AF1>> {}
+impl In2Pin for PB7<Alternate<AF11>> {}
+impl In2Pin for PC2<Alternate<AF1>> {}
+impl EtrPin for PB6<Alternate<AF11>> {}
+impl EtrPin for PC3<Alternate<AF1>> {}
+
+/// Low-power timer
+pub struct LowPowerTimer {
+    tim: LPTIMER1,
+    clk: Hertz,
+    core_clk: Hertz,
+}
+
+/// Allows the lptim() method to be added to the peripheral
+pub trait LptimExt {
+    /// Selects the kernel clock of the timer, LSE and LSI are started if needed
+    fn lptim(self, clock: ClockSource, rcc: &mut Rcc) -> LowPowerTimer;
+}
+
+impl LptimExt for LPTIMER1 {
+    fn lptim(self, clock: ClockSource, rcc: &mut Rcc) -> LowPowerTimer {
+        unsafe {
+            let rcc_ptr = &(*crate::stm32::RCC::ptr());
+            LPTIMER1::enable(rcc_ptr);
+            LPTIMER1::reset(rcc_ptr);
+        }
+
+        let clk = match clock {
+            ClockSource::Pclk => rcc.clocks.apb1_clk,
+            ClockSource::Lsi => {
+                rcc.enable_lsi();
+                Hertz::from_raw(32_000)
+            }
+            ClockSource::Hsi16 => {
+                rcc.enable_hsi();
+                Hertz::from_raw(16_000_000)
+            }
+            ClockSource::Lse => {
+                rcc.unlock_rtc();
+                rcc.enable_lse(false);
+                Hertz::from_raw(32_768)
+            }
+        };
+
+        rcc.rb.ccipr().modify(|_, w| {
+            // This is sound, as `ClockSource` only contains valid values for this field.
+            unsafe {
+                w.lptim1sel().bits(clock as u8);
+            }
+        });
+
+        LowPowerTimer {
+            tim: self,
+            clk,
+            core_clk: rcc.clocks.core_clk,
+        }
+    }
+}
+
+impl LowPowerTimer {
+    /// Divides the kernel clock, the timer has to be stopped
+    pub fn set_prescaler(&mut self, prescaler: Prescaler) {
+        self.assert_disabled();
+        self.tim.cfgr().modify(|_, w| unsafe {
+            w.presc().bits(prescaler as u8);
+        });
+    }
+
+    /// Returns the frequency of the counter
+    pub fn frequency(&self) -> Hertz {
+        let presc = self.tim.cfgr().read().presc().bits();
+        Hertz::from_raw(self.clk.raw() >> presc)
+    }
+
+    /// Starts the counter from zero, counting up to `arr`. The counter waits for the external
+    /// trigger if one is selected.
+    pub fn start(&mut self, mode: CountMode, arr: u16) {
+        self.tim.cr().modify(|_, w| {
+            w.enable().set_bit();
+        });
+        // The timer is enabled two kernel clock cycles later, ARR can not be written before
+        let cycles = 2 * (self.core_clk.raw() / self.clk.raw() + 1);
+        cortex_m::asm::delay(cycles);
+        self.set_auto_reload(arr);
+        match mode {
+            CountMode::OneShot => self.tim.cr().modify(|_, w| {
+                w.sngstrt().set_bit();
+            }),
+            CountMode::Continuous => self.tim.cr().modify(|_, w| {
+                w.cntstrt().set_bit();
+            }),
+        }
+    }
+
+    /// Stops the counter, which resets it
+    pub fn stop(&mut self) {
+        self.tim.cr().modify(|_, w| {
+            w.enable().clear_bit();
+        });
+    }
+
+    /// Sets the auto-reload value, the timer has to be started
+    pub fn set_auto_reload(&mut self, arr: u16) {
+        self.tim.arr().write(|w| unsafe {
+            w.arr().bits(arr);
+        });
+        while self.tim.isr().read().arrok().bit_is_clear() {}
+        self.tim.icr().write(|w| {
+            w.arrokcf().set_bit();
+        });
+    }
+
+    /// Sets the compare value, the timer has to be started
+    pub fn set_compare(&mut self, cmp: u16) {
+        self.tim.cmp().write(|w| unsafe {
+            w.cmp().bits(cmp);
+        });
+        while self.tim.isr().read().cmpok().bit_is_clear() {}
+        self.tim.icr().write(|w| {
+            w.cmpokcf().set_bit();
+        });
+    }
+
+    /// Returns the current count
+    pub fn count(&self) -> u16 {
+        // The counter may be clocked asynchronously, two consecutive reads have to match
+        loop {
+            let cnt = self.tim.cnt().read().cnt().bits();
+            if self.tim.cnt().read().cnt().bits() == cnt {
+                return cnt;
+            }
+        }
+    }
+
+    /// Waits for `trigger` before starting to count, the timer has to be stopped
+    pub fn set_trigger(&mut self, trigger: Trigger, edge: Edge) {
+        self.assert_disabled();
+        self.tim.cfgr().modify(|_, w| unsafe {
+            w.trigsel().bits(trigger as u8).trigen().bits(edge as u8);
+        });
+    }
+
+    /// Waits for the ETR input before starting to count, the timer has to be stopped
+    pub fn set_trigger_pin<PIN: EtrPin>(&mut self, _pin: PIN, edge: Edge) {
+        self.set_trigger(Trigger::Etr, edge);
+    }
+
+    /// Starts counting on [`start`](Self::start), the timer has to be stopped
+    pub fn disable_trigger(&mut self) {
+        self.assert_disabled();
+        self.tim.cfgr().modify(|_, w| unsafe {
+            w.trigen().bits(0b00);
+        });
+    }
+
+    /// In timeout mode, each trigger restarts the counter from zero, so that the compare match
+    /// only happens if no trigger occurred for the compare value. The timer has to be stopped.
+    pub fn set_timeout_mode(&mut self, timeout: bool) {
+        self.assert_disabled();
+        self.tim.cfgr().modify(|_, w| {
+            w.timout().bit(timeout);
+        });
+    }
+
+    /// Counts the `edge`s of the IN1 input instead of the kernel clock. The edges are counted
+    /// in Stop mode as well, without any kernel clock. The timer has to be stopped.
+    pub fn count_external_clock<PIN: In1Pin>(&mut self, _pin: PIN, edge: Edge) {
+        self.assert_disabled();
+        // The clock polarity selects the active edges, rising is 00 and both edges 10
+        let ckpol = match edge {
+            Edge::Rising => 0b00,
+            Edge::Falling => 0b01,
+            Edge::Both => 0b10,
+        };
+        self.tim.cfgr().modify(|_, w| unsafe {
+            w.cksel()
+                .set_bit()
+                .ckpol()
+                .bits(ckpol)
+                .countmode()
+                .set_bit();
+        });
+    }
+
+    /// Decodes a quadrature encoder on the IN1 and IN2 inputs, counting on both edges of both
+    /// inputs. The kernel clock samples the inputs, the timer has to be stopped and the
+    /// prescaler has to be [`Prescaler::Div1`].
+    pub fn encoder<IN1: In1Pin, IN2: In2Pin>(&mut self, _in1: IN1, _in2: IN2) {
+        self.assert_disabled();
+        self.tim.cfgr().modify(|_, w| unsafe {
+            w.cksel()
+                .clear_bit()
+                .ckpol()
+                .bits(0b10)
+                .countmode()
+                .set_bit()
+                .enc()
+                .set_bit();
+        });
+    }
+
+    /// Starts listening for an `event`, the timer has to be stopped
+    pub fn listen(&mut self, event: Event) {
+        // The interrupt enable register can only be written while the timer is stopped
+        self.assert_disabled();
+        self.tim.ier().modify(|_, w| match event {
+            Event::CompareMatch => {
+                w.cmpmie().set_bit();
+            }
+            Event::AutoReloadMatch => {
+                w.arrmie().set_bit();
+            }
+            Event::ExternalTrigger => {
+                w.exttrigie().set_bit();
+            }
+            Event::Up => {
+                w.upie().set_bit();
+            }
+            Event::Down => {
+                w.downie().set_bit();
+            }
+        });
+    }
+
+    /// Stops listening for an `event`, the timer has to be stopped
+    pub fn unlisten(&mut self, event: Event) {
+        self.assert_disabled();
+        self.tim.ier().modify(|_, w| match event {
+            Event::CompareMatch => {
+                w.cmpmie().clear_bit();
+            }
+            Event::AutoReloadMatch => {
+                w.arrmie().clear_bit();
+            }
+            Event::ExternalTrigger => {
+                w.exttrigie().clear_bit();
+            }
+            Event::Up => {
+                w.upie().clear_bit();
+            }
+            Event::Down => {
+                w.downie().clear_bit();
+            }
+        });
+    }
+
+    /// Returns `true` if `event` occurred
+    pub fn is_pending(&self, event: Event) -> bool {
+        let isr = self.tim.isr().read();
+        match event {
+            Event::CompareMatch => isr.cmpm().bit_is_set(),
+            Event::AutoReloadMatch => isr.arrm().bit_is_set(),
+            Event::ExternalTrigger => isr.exttrig().bit_is_set(),
+            Event::Up => isr.up().bit_is_set(),
+            Event::Down => isr.down().bit_is_set(),
+        }
+    }
+
+    /// Clears the interrupt flag of an `event`
+    pub fn clear_interrupt(&mut self, event: Event) {
+        self.tim.icr().write(|w| match event {
+            Event::CompareMatch => {
+                w.cmpmcf().set_bit();
+            }
+            Event::AutoReloadMatch => {
+                w.arrmcf().set_bit();
+            }
+            Event::ExternalTrigger => {
+                w.exttrigcf().set_bit();
+            }
+            Event::Up => {
+                w.upcf().set_bit();
+            }
+            Event::Down => {
+                w.downcf().set_bit();
+            }
+        });
+    }
+
+    /// Lets the events being listened to wake the core up from Stop mode
+    pub fn enable_wakeup(&mut self, exti: &EXTI) {
+        exti.wakeup(exti::Event::LPTIM1);
+    }
+
+    /// Stops the events from waking the core up
+    pub fn disable_wakeup(&mut self, exti: &EXTI) {
+        exti.unlisten(exti::Event::LPTIM1);
+    }
+
+    /// Stops the timer and releases it
+    pub fn release(self) -> LPTIMER1 {
+        self.tim.cr().modify(|_, w| {
+            w.enable().clear_bit();
+        });
+        self.tim
+    }
+
+    fn assert_disabled(&self) {
+        assert!(self.tim.cr().read().enable().bit_is_clear());
+    }
+}
@@ -113,6 +113,14 @@
 //!
 //! Currently only one break input (BKIN or BKIN2) can be enabled, this could be changed to allow two break inputs at the same time.
 //!
+//! ## Analog watchdog protection
+//!
+//! The analog watchdogs of the ADCs can not drive the break inputs, they are connected to the ETR input of the advanced timers instead.
+//! [PwmControl::clear_on_analog_watchdog](struct.PwmControl.html#method.clear_on_analog_watchdog) routes a watchdog through ETR to the OCxREF clear
+//! input of all channels, so that the outputs go inactive in hardware as long as a guarded conversion is out of the thresholds (e.g. overcurrent).
+//!
+//! TIM1 is connected to ADC1 and ADC4, TIM8 to ADC2 and ADC3 and TIM20 to ADC3 and ADC5.
+//!
 //! ## Complementary outputs
 //!
 //! Once a PWM channel has been created through TIMx.pwm(...) or TIMx.pwm_advanced(...).finalize(), it can be put into complementary mode or have its polarity changed.
@@ -172,6 +180,7 @@
 use core::marker::PhantomData;
 use core::mem::MaybeUninit;
 
+use crate::adc::{config::AnalogWatchdog, Adc, TriggerType};
 use crate::hal_02;
 use crate::stm32::LPTIMER1;
 use crate::stm32::RCC;
@@ -1482,6 +1491,107 @@ tim_hal! {
     TIM20: (tim20, u16, 16, BDTR: bdtr, set_bit, af1, set_bit),
 }
 
+/// ADCs whose analog watchdog outputs are connected to the ETR input of a timer
+pub trait AnalogWatchdogEtr<TIM> {
+    /// ETRSEL value selecting analog watchdog 1 of the ADC, watchdogs 2 and 3 follow
+    const ETRSEL_AWD1: u8;
+}
+
+// Implement analog watchdog ETR routing for the advanced timers
+macro_rules! awd_etr {
+    ($($TIMX:ident: [$($(#[$attr:meta])* $ADC:ident => $etrsel:expr,)+],)+) => {
+        $(
+            $(
+                $(#[$attr])*
+                impl AnalogWatchdogEtr<$TIMX> for crate::stm32::$ADC {
+                    const ETRSEL_AWD1: u8 = $etrsel;
+                }
+            )+
+
+            impl<FAULT> PwmControl<$TIMX, FAULT> {
+                /// Clears OCxREF of all channels while the analog watchdog `awd` of `adc` is raised
+                ///
+                /// The watchdog output is routed to ETR, which drives the OCxREF clear input. Once the
+                /// watchdog output goes low again the outputs resume at the next update event.
+                pub fn clear_on_analog_watchdog<ADC, STATUS>(&mut self, _adc: &Adc<ADC, STATUS>, awd: AnalogWatchdog)
+                where
+                    ADC: TriggerType + AnalogWatchdogEtr<$TIMX>,
+                {
+                    let tim = unsafe { &*$TIMX::ptr() };
+
+                    // Safety: the analog watchdog inputs are valid ETRSEL values for this timer
+                    tim.af1().modify(|_, w| unsafe { w.etrsel().bits(ADC::ETRSEL_AWD1 + u8::from(awd)); });
+
+                    // SMCR:
+                    //  OCCS = 1 -> OCREF_CLR is driven by ETRF
+                    //  ETP = 0, ETPS = 0, ETF = 0 -> active high, not prescaled, not filtered
+                    tim.smcr().modify(|_, w| unsafe {
+                        w.occs().set_bit().etp().clear_bit().etps().bits(0).etf().bits(0);
+                    });
+
+                    tim.ccmr1_output().modify(|_, w| {w.oc1ce().set_bit().oc2ce().set_bit();});
+                    tim.ccmr2_output().modify(|_, w| {w.oc3ce().set_bit().oc4ce().set_bit();});
+                }
+
+                /// Stops clearing OCxREF on ETR
+                pub fn disable_ocref_clear(&mut self) {
+                    let tim = unsafe { &*$TIMX::ptr() };
+
+                    tim.ccmr1_output().modify(|_, w| {w.oc1ce().clear_bit().oc2ce().clear_bit();});
+                    tim.ccmr2_output().modify(|_, w| {w.oc3ce().clear_bit().oc4ce().clear_bit();});
+                }
+            }
+        )+
+    };
+}
+
+awd_etr! {
+    TIM1: [
+        ADC1 => 0b1000,
+        #[cfg(any(
+            feature = "stm32g473",
+            feature = "stm32g474",
+            feature = "stm32g483",
+            feature = "stm32g484",
+        ))]
+        ADC4 => 0b1011,
+    ],
+    TIM8: [
+        ADC2 => 0b1000,
+        #[cfg(any(
+            feature = "stm32g471",
+            feature = "stm32g473",
+            feature = "stm32g474",
+            feature = "stm32g483",
+            feature = "stm32g484",
+            feature = "stm32g491",
+            feature = "stm32g4a1",
+        ))]
+        ADC3 => 0b1011,
+    ],
+}
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+awd_etr! {
+    TIM20: [
+        ADC3 => 0b1000,
+        #[cfg(any(
+            feature = "stm32g473",
+            feature = "stm32g474",
+            feature = "stm32g483",
+            feature = "stm32g484",
+        ))]
+        ADC5 => 0b1011,
+    ],
+}
+
 pub trait PwmPinEnable {
     fn ccer_enable(&mut self);
     fn ccer_disable(&mut self);
@@ -1554,6 +1664,33 @@ macro_rules! tim_pin_hal {
                 }
             }
 
+            impl<COMP, POL, NPOL> hal_1::pwm::ErrorType for Pwm<$TIMX, $CH, COMP, POL, NPOL> {
+                type Error = core::convert::Infallible;
+            }
+
+            impl<COMP, POL, NPOL> hal_1::pwm::SetDutyCycle for Pwm<$TIMX, $CH, COMP, POL, NPOL>
+                where Pwm<$TIMX, $CH, COMP, POL, NPOL>: PwmPinEnable {
+                fn max_duty_cycle(&self) -> u16 {
+                    let max = u64::from(hal_02::PwmPin::get_max_duty(self));
+
+                    // 32-bit timers may count past what fits in the u16 duty, the
+                    // duty is scaled in that case
+                    max.min(u16::MAX as u64) as u16
+                }
+
+                fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+                    let max = u64::from(hal_02::PwmPin::get_max_duty(self));
+                    let duty = if max > u16::MAX as u64 {
+                        u64::from(duty) * max / u16::MAX as u64
+                    } else {
+                        u64::from(duty)
+                    };
+
+                    hal_02::PwmPin::set_duty(self, duty as $typ);
+                    Ok(())
+                }
+            }
+
             // Enable implementation for ComplementaryImpossible
             impl<POL, NPOL> PwmPinEnable for Pwm<$TIMX, $CH, ComplementaryImpossible, POL, NPOL> {
                 fn ccer_enable(&mut self) {
@@ -1864,6 +2001,21 @@ macro_rules! lptim_hal {
                     tim.icr().write(|w| {w.cmpokcf().set_bit();});
                 }
             }
+
+            impl hal_1::pwm::ErrorType for Pwm<$TIMX, C1, ComplementaryImpossible, ActiveHigh, ActiveHigh> {
+                type Error = core::convert::Infallible;
+            }
+
+            impl hal_1::pwm::SetDutyCycle for Pwm<$TIMX, C1, ComplementaryImpossible, ActiveHigh, ActiveHigh> {
+                fn max_duty_cycle(&self) -> u16 {
+                    hal_02::PwmPin::get_max_duty(self)
+                }
+
+                fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+                    hal_02::PwmPin::set_duty(self, duty);
+                    Ok(())
+                }
+            }
         )+
     }
 }
@@ -0,0 +1,332 @@
+//! Quadrature Encoder Interface
+//!
+//! The general purpose timers TIM2, TIM3, TIM4 and TIM5 and the advanced timers TIM1, TIM8 and
+//! TIM20 can decode the A and B signals of a quadrature encoder connected to their channel 1 and
+//! channel 2 pins. TIM2 and TIM5 count on 32 bits, the other timers on 16 bits.
+//!
+//! The index signal of the encoder is connected to the ETR pin of the timer, each index pulse
+//! resets the counter. Index pins are available for TIM1, TIM2, TIM3, TIM4 and TIM8.
+//!
+//! ```ignore
+//! let a = gpioa.pa0.into_alternate();
+//! let b = gpioa.pa1.into_alternate();
+//! let index = gpioa.pa5.into_alternate();
+//!
+//! let mut qei = dp.TIM2.qei((a, b), EncoderMode::X4, &mut rcc);
+//! qei.set_filter(InputFilter::FckInt_N8);
+//! qei.enable_index(&index, IndexPosition::AB00, IndexDirection::Both);
+//!
+//! let position = qei.count();
+//! ```
+use crate::gpio::{gpioa::*, gpiob::*, gpioc::*, gpiod::*, gpioe::*};
+use crate::gpio::{Alternate, AF10, AF11, AF14, AF2, AF6};
+use crate::hal_02::{self, Direction};
+use crate::pwm::{ComplementaryDisabled, ComplementaryImpossible, Pins, C1, C2};
+use crate::rcc::{Enable, Rcc, Reset};
+use crate::stm32::RCC;
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+use crate::stm32::TIM20;
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+use crate::stm32::TIM5;
+use crate::stm32::{TIM1, TIM2, TIM3, TIM4, TIM8};
+use crate::timer::InputFilter;
+
+/// Quadrature Encoder Interface
+pub struct Qei<TIM, PINS> {
+    tim: TIM,
+    pins: PINS,
+}
+
+/// Resolution of the encoder
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum EncoderMode {
+    /// Count once per encoder period, on the edges of A
+    X1,
+    /// Count twice per encoder period, on the edges of A
+    X2,
+    /// Count four times per encoder period, on the edges of A and B
+    X4,
+}
+
+impl EncoderMode {
+    /// The SMS bits of the slave mode controller
+    fn sms(self) -> u8 {
+        match self {
+            EncoderMode::X1 => 0b1110,
+            EncoderMode::X2 => 0b0001,
+            EncoderMode::X4 => 0b0011,
+        }
+    }
+}
+
+/// State of the A and B signals at which the index pulse resets the counter
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum IndexPosition {
+    /// A and B low
+    AB00 = 0b00,
+    /// A low and B high
+    AB01 = 0b01,
+    /// A high and B low
+    AB10 = 0b10,
+    /// A and B high
+    AB11 = 0b11,
+}
+
+/// Counting direction in which the index pulse resets the counter
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum IndexDirection {
+    /// Both directions
+    Both = 0b00,
+    /// Up-counting only
+    Up = 0b01,
+    /// Down-counting only
+    Down = 0b10,
+}
+
+/// Interrupt events
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Event {
+    /// The index pulse reset the counter
+    Index,
+    /// The counting direction changed
+    DirectionChange,
+}
+
+/// Pairs of channel 1 and channel 2 pins usable for the A and B signals of an encoder
+pub trait QeiPins<TIM> {}
+
+/// Pins connected to the ETR input of a timer, usable for the index signal of an encoder
+pub trait IndexPin<TIM> {}
+
+/// Allows the qei() method to be added to the timer peripherals
+pub trait QeiExt<PINS>: Sized {
+    /// Configures the timer as a quadrature encoder interface
+    fn qei(self, pins: PINS, mode: EncoderMode, rcc: &mut Rcc) -> Qei<Self, PINS>;
+}
+
+macro_rules! qei {
+    ($($TIMX:ident: ($typ:ty, $COMP:ty),)+) => {
+        $(
+            impl<P1, P2> QeiPins<$TIMX> for (P1, P2)
+            where
+                P1: Pins<$TIMX, C1, $COMP>,
+                P2: Pins<$TIMX, C2, $COMP>,
+            {}
+
+            impl<PINS> QeiExt<PINS> for $TIMX
+            where
+                PINS: QeiPins<$TIMX>,
+            {
+                fn qei(self, pins: PINS, mode: EncoderMode, _rcc: &mut Rcc) -> Qei<Self, PINS> {
+                    unsafe {
+                        let rcc_ptr = &(*RCC::ptr());
+                        $TIMX::enable(rcc_ptr);
+                        $TIMX::reset(rcc_ptr);
+                    }
+
+                    // Map TI1 to IC1 and TI2 to IC2
+                    self.ccmr1_input().write(|w| unsafe { w.cc1s().bits(0b01).cc2s().bits(0b01); });
+                    // Non-inverted inputs
+                    self.ccer().write(|w| { w.cc1e().set_bit().cc2e().set_bit(); });
+
+                    let sms = mode.sms();
+                    self.smcr().write(|w| unsafe { w.sms().bits(sms & 0b111).sms_3().bit(sms & 0b1000 != 0); });
+
+                    self.cr1().write(|w| { w.cen().set_bit(); });
+
+                    Qei { tim: self, pins }
+                }
+            }
+
+            impl<PINS> Qei<$TIMX, PINS> {
+                /// Returns the current count
+                #[inline]
+                pub fn count(&self) -> $typ {
+                    self.tim.cnt().read().bits() as $typ
+                }
+
+                /// Sets the current count
+                #[inline]
+                pub fn set_count(&mut self, count: $typ) {
+                    self.tim.cnt().write(|w| unsafe { w.bits(count as u32); });
+                }
+
+                /// Returns the counting direction
+                #[inline]
+                pub fn direction(&self) -> Direction {
+                    if self.tim.cr1().read().dir().bit_is_set() {
+                        Direction::Downcounting
+                    } else {
+                        Direction::Upcounting
+                    }
+                }
+
+                /// Sets the maximum count, the counter wraps around between 0 and `max`.
+                /// Use the number of counts per revolution minus one to count the angle.
+                #[inline]
+                pub fn set_max_count(&mut self, max: $typ) {
+                    self.tim.arr().write(|w| unsafe { w.bits(max as u32); });
+                }
+
+                /// Sets the digital filter of the A, B and index inputs
+                #[inline]
+                pub fn set_filter(&mut self, filter: InputFilter) {
+                    self.tim.ccmr1_input().modify(|_, w| unsafe { w.ic1f().bits(filter as u8).ic2f().bits(filter as u8); });
+                    self.tim.smcr().modify(|_, w| unsafe { w.etf().bits(filter as u8); });
+                }
+
+                /// Inverts the counting direction
+                #[inline]
+                pub fn set_reversed(&mut self, reversed: bool) {
+                    self.tim.ccer().modify(|_, w| { w.cc1p().bit(reversed); });
+                }
+
+                /// Resets the counter on the index pulse at the given position of the A and B signals
+                #[inline]
+                pub fn enable_index<IDX>(&mut self, _pin: &IDX, position: IndexPosition, direction: IndexDirection)
+                where
+                    IDX: IndexPin<$TIMX>,
+                {
+                    self.tim.ecr().modify(|_, w| unsafe { w
+                        .ipos().bits(position as u8)
+                        .idir().bits(direction as u8)
+                        .ie().set_bit();
+                    });
+                }
+
+                /// Stops resetting the counter on the index pulse
+                #[inline]
+                pub fn disable_index(&mut self) {
+                    self.tim.ecr().modify(|_, w| { w.ie().clear_bit(); });
+                }
+
+                /// Starts listening for an `event`
+                #[inline]
+                pub fn listen(&mut self, event: Event) {
+                    match event {
+                        Event::Index => self.tim.dier().modify(|_, w| { w.idxie().set_bit(); }),
+                        Event::DirectionChange => self.tim.dier().modify(|_, w| { w.dirie().set_bit(); }),
+                    }
+                }
+
+                /// Stops listening for an `event`
+                #[inline]
+                pub fn unlisten(&mut self, event: Event) {
+                    match event {
+                        Event::Index => self.tim.dier().modify(|_, w| { w.idxie().clear_bit(); }),
+                        Event::DirectionChange => self.tim.dier().modify(|_, w| { w.dirie().clear_bit(); }),
+                    }
+                }
+
+                /// Clears the interrupt flag of an `event`
+                #[inline]
+                pub fn clear_interrupt(&mut self, event: Event) {
+                    match event {
+                        Event::Index => self.tim.sr().modify(|_, w| { w.idxf().clear_bit(); }),
+                        Event::DirectionChange => self.tim.sr().modify(|_, w| { w.dirf().clear_bit(); }),
+                    }
+                }
+
+                /// Stops the timer and releases it along with the pins
+                #[inline]
+                pub fn release(self) -> ($TIMX, PINS) {
+                    self.tim.cr1().modify(|_, w| { w.cen().clear_bit(); });
+                    (self.tim, self.pins)
+                }
+            }
+
+            impl<PINS> hal_02::Qei for Qei<$TIMX, PINS> {
+                type Count = $typ;
+
+                fn count(&self) -> $typ {
+                    Qei::count(self)
+                }
+
+                fn direction(&self) -> Direction {
+                    Qei::direction(self)
+                }
+            }
+        )+
+    };
+}
+
+qei! {
+    TIM1: (u16, ComplementaryDisabled),
+    TIM2: (u32, ComplementaryImpossible),
+    TIM3: (u16, ComplementaryImpossible),
+    TIM4: (u16, ComplementaryImpossible),
+    TIM8: (u16, ComplementaryDisabled),
+}
+
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+qei! {
+    TIM5: (u32, ComplementaryImpossible),
+}
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+qei! {
+    TIM20: (u16, ComplementaryDisabled),
+}
+
+macro_rules! index_pins {
+    ($($TIMX:ident: [$($PIN:ty),*],)+) => {
+        $(
+            $(
+                impl IndexPin<$TIMX> for $PIN {}
+            )*
+        )+
+    };
+}
+
+index_pins! {
+    TIM1: [
+        PA12<Alternate<AF11>>,
+        PC4<Alternate<AF2>>,
+        PE7<Alternate<AF2>>
+    ],
+    TIM2: [
+        PA0<Alternate<AF14>>,
+        PA5<Alternate<AF2>>,
+        PA15<Alternate<AF14>>,
+        PD3<Alternate<AF2>>
+    ],
+    TIM3: [
+        PB3<Alternate<AF10>>,
+        PD2<Alternate<AF2>>
+    ],
+    TIM4: [
+        PA8<Alternate<AF10>>,
+        PB3<Alternate<AF2>>,
+        PE0<Alternate<AF2>>
+    ],
+    TIM8: [
+        PA0<Alternate<AF10>>,
+        PB6<Alternate<AF6>>
+    ],
+}
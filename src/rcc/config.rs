@@ -38,6 +38,14 @@ pub enum LSCOSrc {
     LSE,
 }
 
+/// RTC clock input source
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RTCSrc {
+    LSE,
+    LSE_BYPASS,
+    LSI,
+}
+
 /// PLL clock input source
 #[derive(Clone, Copy)]
 pub enum PllSrc {
@@ -202,11 +202,24 @@ bus! {
 ))]
 bus! {
     FDCAN3 => (APB1_1, 25),
+}
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+bus! {
     TIM20 => (APB2, 20),
 }
 
 #[cfg(any(feature = "stm32g474", feature = "stm32g484"))]
 bus! {
+    HRTIM_COMMON => (APB2, 26),
+    HRTIM_MASTER => (APB2, 26),
     HRTIM_TIMA => (APB2, 26),
     HRTIM_TIMB => (APB2, 26),
     HRTIM_TIMC => (APB2, 26),
@@ -243,6 +243,50 @@ impl Rcc {
         });
     }
 
+    /// Enables the RTC and selects its kernel clock
+    ///
+    /// The backup domain is only reset if the RTC is not already running from `src`,
+    /// so the calendar and backup registers survive a system reset. Returns `true` in
+    /// that case.
+    pub(crate) fn enable_rtc(&mut self, src: RTCSrc) -> bool {
+        self.unlock_rtc();
+        self.rb.apb1enr1().modify(|_, w| {
+            w.rtcapben().set_bit();
+        });
+
+        let rtc_sel = match src {
+            RTCSrc::LSE | RTCSrc::LSE_BYPASS => 0b01,
+            RTCSrc::LSI => 0b10,
+        };
+
+        let bdcr = self.rb.bdcr().read();
+        if bdcr.rtcen().bit_is_set() && bdcr.rtcsel().bits() == rtc_sel {
+            // The backup domain is not reset on system reset, LSI however is
+            if src == RTCSrc::LSI {
+                self.enable_lsi();
+            }
+            return true;
+        }
+
+        self.rb.bdcr().modify(|_, w| {
+            w.bdrst().set_bit();
+        });
+        self.rb.bdcr().modify(|_, w| {
+            w.bdrst().clear_bit();
+        });
+
+        match src {
+            RTCSrc::LSE => self.enable_lse(false),
+            RTCSrc::LSE_BYPASS => self.enable_lse(true),
+            RTCSrc::LSI => self.enable_lsi(),
+        }
+
+        self.rb.bdcr().modify(|_, w| unsafe {
+            w.rtcsel().bits(rtc_sel).rtcen().set_bit();
+        });
+        false
+    }
+
     fn config_pll(&self, PllConfig(pll_cfg): PllConfig) -> PllClocks {
         // Disable PLL
         self.rb.cr().modify(|_, w| {
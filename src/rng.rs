@@ -0,0 +1,195 @@
+//! True Random Number Generator
+//!
+//! The RNG produces 32-bit random numbers from analog noise, it is clocked by the 48 MHz output
+//! of the PLL Q divider. The health of the entropy source is monitored: a seed error restarts
+//! the generator, and a clock error is reported until the RNG clock is correct again.
+//!
+//! ```ignore
+//! // HSI16 / 4 * 48 = 192 MHz VCO, 48 MHz on Q and 96 MHz on R
+//! let pll_cfg = PllConfig::new(RawPllConfig {
+//!     mux: PllSrc::HSI,
+//!     m: PllMDiv::DIV_4,
+//!     n: PllNMul::MUL_48,
+//!     r: Some(PllRDiv::DIV_2),
+//!     q: Some(PllQDiv::DIV_4),
+//!     p: None,
+//! });
+//! let mut rcc = dp.RCC.freeze(rcc::Config::pll().pll_cfg(pll_cfg), pwr);
+//! let mut rng = dp.RNG.constrain(&mut rcc);
+//!
+//! let mut nonce = [0u8; 12];
+//! rng.fill_bytes(&mut nonce);
+//! ```
+pub use rand_core::{CryptoRng, RngCore};
+
+use crate::rcc::{Enable, Rcc, Reset};
+use crate::stm32::RNG;
+
+/// RNG errors
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Error {
+    /// The RNG clock is too slow, no numbers are generated until it is correct again
+    ClockError,
+    /// The entropy source keeps failing after the recovery sequence, the numbers are not
+    /// random
+    SeedError,
+}
+
+impl From<Error> for rand_core::Error {
+    fn from(error: Error) -> Self {
+        let code = match error {
+            Error::ClockError => rand_core::Error::CUSTOM_START,
+            Error::SeedError => rand_core::Error::CUSTOM_START + 1,
+        };
+        core::num::NonZeroU32::new(code).unwrap().into()
+    }
+}
+
+/// Extension trait to constrain the RNG peripheral
+pub trait RngExt {
+    /// Selects the PLL Q output as the 48 MHz clock and starts the RNG
+    ///
+    /// # Panics
+    ///
+    /// If the PLL Q output is not enabled at 48 MHz
+    fn constrain(self, rcc: &mut Rcc) -> Rng;
+}
+
+impl RngExt for RNG {
+    fn constrain(self, rcc: &mut Rcc) -> Rng {
+        let pll_q = rcc
+            .clocks
+            .pll_clk
+            .q
+            .expect("the RNG requires the PLL Q output");
+        assert_eq!(pll_q.raw(), 48_000_000, "the RNG requires a 48 MHz clock");
+
+        rcc.rb.ccipr().modify(|_, w| unsafe {
+            w.clk48sel().bits(0b10);
+        });
+        RNG::enable(&rcc.rb);
+        RNG::reset(&rcc.rb);
+
+        // Clock error detection is enabled when CED is cleared
+        self.cr().write(|w| {
+            w.ced().clear_bit().rngen().set_bit();
+        });
+
+        Rng { rb: self }
+    }
+}
+
+/// True random number generator
+pub struct Rng {
+    rb: RNG,
+}
+
+impl Rng {
+    /// Returns a random number, or `WouldBlock` until a new one is generated.
+    ///
+    /// A transient seed error is recovered from, the numbers generated meanwhile are discarded
+    /// and `WouldBlock` is returned. A persistent seed error is reported.
+    pub fn value(&mut self) -> nb::Result<u32, Error> {
+        let sr = self.rb.sr().read();
+        if sr.seis().bit_is_set() || sr.secs().bit_is_set() {
+            self.recover_seed_error()?;
+            return Err(nb::Error::WouldBlock);
+        }
+        if sr.ceis().bit_is_set() {
+            self.rb.sr().modify(|_, w| {
+                w.ceis().clear_bit();
+            });
+        }
+        if sr.cecs().bit_is_set() {
+            return Err(nb::Error::Other(Error::ClockError));
+        }
+        if sr.drdy().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let value = self.rb.dr().read().bits();
+        // A seed error may have been raised while the number was generated
+        let sr = self.rb.sr().read();
+        if sr.seis().bit_is_set() || sr.secs().bit_is_set() {
+            self.recover_seed_error()?;
+            return Err(nb::Error::WouldBlock);
+        }
+        Ok(value)
+    }
+
+    /// Blocks until a random number is generated, transient seed errors are recovered from
+    pub fn read(&mut self) -> Result<u32, Error> {
+        nb::block!(self.value())
+    }
+
+    /// Enables the interrupt raised when a number is ready or on errors
+    pub fn listen(&mut self) {
+        self.rb.cr().modify(|_, w| {
+            w.ie().set_bit();
+        });
+    }
+
+    /// Disables the interrupt
+    pub fn unlisten(&mut self) {
+        self.rb.cr().modify(|_, w| {
+            w.ie().clear_bit();
+        });
+    }
+
+    /// Stops the RNG and releases the peripheral
+    pub fn release(self) -> RNG {
+        self.rb.cr().modify(|_, w| {
+            w.rngen().clear_bit();
+        });
+        self.rb
+    }
+
+    /// Seed error recovery sequence of RM0440
+    fn recover_seed_error(&mut self) -> Result<(), Error> {
+        self.rb.sr().modify(|_, w| {
+            w.seis().clear_bit();
+        });
+        // Clear the pipeline of the numbers affected by the error
+        for _ in 0..12 {
+            let _ = self.rb.dr().read().bits();
+        }
+        let sr = self.rb.sr().read();
+        if sr.seis().bit_is_set() || sr.secs().bit_is_set() {
+            return Err(Error::SeedError);
+        }
+        Ok(())
+    }
+}
+
+impl RngCore for Rng {
+    /// # Panics
+    ///
+    /// On a clock error
+    fn next_u32(&mut self) -> u32 {
+        self.read().expect("RNG clock error")
+    }
+
+    /// # Panics
+    ///
+    /// On a clock error
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_u32(self)
+    }
+
+    /// # Panics
+    ///
+    /// On a clock error
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.read()?.to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
+
+impl CryptoRng for Rng {}
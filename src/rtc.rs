@@ -0,0 +1,601 @@
+//! Real Time Clock
+//!
+//! The RTC keeps running from its backup domain clock (LSE or LSI) across system resets,
+//! so the calendar is only reset if the clock source changes.
+//!
+//! Alarm interrupts are signaled on [`exti::Event::RTC_ALARM`](crate::exti::Event::RTC_ALARM)
+//! and wakeup timer interrupts on [`exti::Event::RTC_WAKEUP`](crate::exti::Event::RTC_WAKEUP).
+//! Both need to be enabled on the rising edge in the EXTI to reach the NVIC or to wake the
+//! MCU from a low power mode:
+//!
+//! ```ignore
+//! rtc.listen(rtc::Event::AlarmA);
+//! exti.listen(exti::Event::RTC_ALARM, SignalEdge::Rising);
+//! ```
+use core::convert::TryInto;
+
+use crate::rcc::{RTCSrc, Rcc};
+use crate::stm32::{RTC, TAMP};
+use crate::time::*;
+
+/// Number of 32-bit backup registers
+pub const BACKUP_REGISTERS: usize = 32;
+
+/// The RTC counts years from 2000 to 2099
+const YEAR_OFFSET: u32 = 2000;
+
+/// RTC interrupt events
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    AlarmA,
+    AlarmB,
+    WakeupTimer,
+}
+
+/// Length of a smooth calibration cycle
+///
+/// Shorter cycles ignore the lowest bits of the calibration value, see
+/// [`Rtc::smooth_calibration`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalibrationCycle {
+    Seconds8,
+    Seconds16,
+    Seconds32,
+}
+
+/// Alarm configuration
+///
+/// Fields that are not set are masked, i.e. they are "don't care" when matching against
+/// the calendar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Alarm {
+    day: Option<u32>,
+    hours: Option<u32>,
+    minutes: Option<u32>,
+    seconds: Option<u32>,
+    subseconds: u16,
+    subseconds_mask_bits: u8,
+    use_weekday: bool,
+}
+
+impl Alarm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match the day of the month (1-31)
+    pub fn set_month_day(mut self, day: MonthDay) -> Self {
+        self.use_weekday = false;
+        self.day = Some(day.0);
+        self
+    }
+
+    /// Match the day of the week (1-7, Monday is 1)
+    pub fn set_week_day(mut self, day: WeekDay) -> Self {
+        self.use_weekday = true;
+        self.day = Some(day.0);
+        self
+    }
+
+    /// Match hours, minutes and seconds
+    pub fn set_time(mut self, time: Time) -> Self {
+        self.hours = Some(time.hours);
+        self.minutes = Some(time.minutes);
+        self.seconds = Some(time.seconds);
+        self
+    }
+
+    pub fn set_hours(mut self, hours: Hour) -> Self {
+        self.hours = Some(hours.ticks());
+        self
+    }
+
+    pub fn set_minutes(mut self, minutes: Minute) -> Self {
+        self.minutes = Some(minutes.ticks());
+        self
+    }
+
+    pub fn set_seconds(mut self, seconds: Second) -> Self {
+        self.seconds = Some(seconds.ticks());
+        self
+    }
+
+    /// Match the `mask_bits` lowest bits of the sub second counter against `subseconds`
+    pub fn set_subseconds(mut self, subseconds: u16, mask_bits: u8) -> Self {
+        assert!(mask_bits <= 15);
+        self.subseconds = subseconds;
+        self.subseconds_mask_bits = mask_bits;
+        self
+    }
+
+    pub fn mask_day(mut self) -> Self {
+        self.day = None;
+        self
+    }
+
+    pub fn mask_hours(mut self) -> Self {
+        self.hours = None;
+        self
+    }
+
+    pub fn mask_minutes(mut self) -> Self {
+        self.minutes = None;
+        self
+    }
+
+    pub fn mask_seconds(mut self) -> Self {
+        self.seconds = None;
+        self
+    }
+}
+
+impl From<Time> for Alarm {
+    fn from(time: Time) -> Self {
+        Self::default().set_time(time)
+    }
+}
+
+pub struct Rtc {
+    rb: RTC,
+    clock: Hertz,
+}
+
+impl Rtc {
+    /// Enables the RTC clocked from `src`
+    ///
+    /// If the RTC is already running from `src`, the calendar is left untouched.
+    pub fn new(rtc: RTC, src: RTCSrc, rcc: &mut Rcc) -> Self {
+        let running = rcc.enable_rtc(src);
+
+        let (clock, prediv_a, prediv_s) = match src {
+            RTCSrc::LSE | RTCSrc::LSE_BYPASS => (32_768.Hz(), 127, 255),
+            RTCSrc::LSI => (32_000.Hz(), 127, 249),
+        };
+
+        let mut rtc = Rtc { rb: rtc, clock };
+        // Entering the initialization mode would reset the sub second counter
+        let prer = rtc.rb.prer().read();
+        if running
+            && rtc.rb.icsr().read().inits().bit_is_set()
+            && prer.prediv_a().bits() == prediv_a
+            && prer.prediv_s().bits() == prediv_s
+            && rtc.rb.cr().read().fmt().bit_is_clear()
+        {
+            return rtc;
+        }
+
+        rtc.modify(|rb| {
+            rb.prer().write(|w| unsafe {
+                w.prediv_a().bits(prediv_a);
+                w.prediv_s().bits(prediv_s);
+            });
+            rb.cr().modify(|_, w| {
+                w.fmt().clear_bit();
+            });
+        });
+        rtc
+    }
+
+    pub fn set_date(&mut self, date: &Date) {
+        assert!((YEAR_OFFSET..YEAR_OFFSET + 100).contains(&date.year));
+        assert!((1..=12).contains(&date.month));
+        assert!((1..=31).contains(&date.day));
+
+        let (yt, yu) = bcd2_encode(date.year - YEAR_OFFSET);
+        let (mt, mu) = bcd2_encode(date.month);
+        let (dt, du) = bcd2_encode(date.day);
+        let wdu = week_day(date).0 as u8;
+
+        self.modify(|rb| {
+            rb.dr().write(|w| unsafe {
+                w.dt().bits(dt);
+                w.du().bits(du);
+                w.mt().bit(mt > 0);
+                w.mu().bits(mu);
+                w.yt().bits(yt);
+                w.yu().bits(yu);
+                w.wdu().bits(wdu);
+            });
+        });
+    }
+
+    pub fn set_time(&mut self, time: &Time) {
+        assert!(time.hours < 24);
+        assert!(time.minutes < 60);
+        assert!(time.seconds < 60);
+
+        let (ht, hu) = bcd2_encode(time.hours);
+        let (mnt, mnu) = bcd2_encode(time.minutes);
+        let (st, su) = bcd2_encode(time.seconds);
+
+        self.modify(|rb| {
+            rb.tr().write(|w| unsafe {
+                w.ht().bits(ht);
+                w.hu().bits(hu);
+                w.mnt().bits(mnt);
+                w.mnu().bits(mnu);
+                w.st().bits(st);
+                w.su().bits(su);
+                w.pm().clear_bit();
+            });
+            rb.cr().modify(|_, w| {
+                w.bkp().bit(time.daylight_savings);
+            });
+        });
+    }
+
+    pub fn set_date_time(&mut self, date: &Date, time: &Time) {
+        self.set_date(date);
+        self.set_time(time);
+    }
+
+    pub fn get_time(&self) -> Time {
+        let timereg = self.rb.tr().read();
+        // Reading TR locks the shadow DR until it is read
+        let _ = self.rb.dr().read();
+
+        Time::new(
+            bcd2_decode(timereg.ht().bits(), timereg.hu().bits()).hours(),
+            bcd2_decode(timereg.mnt().bits(), timereg.mnu().bits()).minutes(),
+            bcd2_decode(timereg.st().bits(), timereg.su().bits()).secs(),
+            self.rb.cr().read().bkp().bit(),
+        )
+    }
+
+    pub fn get_date(&self) -> Date {
+        let date = self.rb.dr().read();
+
+        let year = bcd2_decode(date.yt().bits(), date.yu().bits()) + YEAR_OFFSET;
+        let month = bcd2_decode(date.mt().bit() as u8, date.mu().bits());
+        let day = bcd2_decode(date.dt().bits(), date.du().bits());
+        Date::new(year.year(), month.month(), day.day())
+    }
+
+    /// Returns the date and time from a single consistent snapshot of the calendar
+    pub fn get_date_time(&self) -> (Date, Time) {
+        let timereg = self.rb.tr().read();
+        let date = self.rb.dr().read();
+
+        let year = bcd2_decode(date.yt().bits(), date.yu().bits()) + YEAR_OFFSET;
+        let month = bcd2_decode(date.mt().bit() as u8, date.mu().bits());
+        let day = bcd2_decode(date.dt().bits(), date.du().bits());
+
+        (
+            Date::new(year.year(), month.month(), day.day()),
+            Time::new(
+                bcd2_decode(timereg.ht().bits(), timereg.hu().bits()).hours(),
+                bcd2_decode(timereg.mnt().bits(), timereg.mnu().bits()).minutes(),
+                bcd2_decode(timereg.st().bits(), timereg.su().bits()).secs(),
+                self.rb.cr().read().bkp().bit(),
+            ),
+        )
+    }
+
+    /// Day of the week (1-7, Monday is 1)
+    pub fn get_week_day(&self) -> WeekDay {
+        WeekDay(self.rb.dr().read().wdu().bits() as u32)
+    }
+
+    pub fn set_alarm_a(&mut self, alarm: impl Into<Alarm>) {
+        let alarm = alarm.into();
+        let (dt, du) = bcd2_encode(alarm.day.unwrap_or_default());
+        let (ht, hu) = bcd2_encode(alarm.hours.unwrap_or_default());
+        let (mnt, mnu) = bcd2_encode(alarm.minutes.unwrap_or_default());
+        let (st, su) = bcd2_encode(alarm.seconds.unwrap_or_default());
+
+        // The alarm registers are written while the alarm is disabled, outside of the
+        // initialization mode which would stop the calendar
+        self.unlocked(|rb| {
+            rb.cr().modify(|_, w| {
+                w.alrae().clear_bit();
+            });
+            while rb.icsr().read().alrawf().bit_is_clear() {}
+
+            rb.alrmassr().write(|w| unsafe {
+                w.maskss().bits(alarm.subseconds_mask_bits);
+                w.ss().bits(alarm.subseconds);
+            });
+            rb.alrmar().write(|w| unsafe {
+                w.wdsel().bit(alarm.use_weekday);
+                w.msk1().bit(alarm.seconds.is_none());
+                w.msk2().bit(alarm.minutes.is_none());
+                w.msk3().bit(alarm.hours.is_none());
+                w.msk4().bit(alarm.day.is_none());
+                w.pm().clear_bit();
+                w.ht().bits(ht);
+                w.hu().bits(hu);
+                w.mnt().bits(mnt);
+                w.mnu().bits(mnu);
+                w.st().bits(st);
+                w.su().bits(su);
+                w.dt().bits(dt);
+                w.du().bits(du);
+            });
+            rb.cr().modify(|_, w| {
+                w.alrae().set_bit();
+            });
+        });
+    }
+
+    pub fn set_alarm_b(&mut self, alarm: impl Into<Alarm>) {
+        let alarm = alarm.into();
+        let (dt, du) = bcd2_encode(alarm.day.unwrap_or_default());
+        let (ht, hu) = bcd2_encode(alarm.hours.unwrap_or_default());
+        let (mnt, mnu) = bcd2_encode(alarm.minutes.unwrap_or_default());
+        let (st, su) = bcd2_encode(alarm.seconds.unwrap_or_default());
+
+        // Same sequence as alarm A
+        self.unlocked(|rb| {
+            rb.cr().modify(|_, w| {
+                w.alrbe().clear_bit();
+            });
+            while rb.icsr().read().alrbwf().bit_is_clear() {}
+
+            rb.alrmbssr().write(|w| unsafe {
+                w.maskss().bits(alarm.subseconds_mask_bits);
+                w.ss().bits(alarm.subseconds);
+            });
+            rb.alrmbr().write(|w| unsafe {
+                w.wdsel().bit(alarm.use_weekday);
+                w.msk1().bit(alarm.seconds.is_none());
+                w.msk2().bit(alarm.minutes.is_none());
+                w.msk3().bit(alarm.hours.is_none());
+                w.msk4().bit(alarm.day.is_none());
+                w.pm().clear_bit();
+                w.ht().bits(ht);
+                w.hu().bits(hu);
+                w.mnt().bits(mnt);
+                w.mnu().bits(mnu);
+                w.st().bits(st);
+                w.su().bits(su);
+                w.dt().bits(dt);
+                w.du().bits(du);
+            });
+            rb.cr().modify(|_, w| {
+                w.alrbe().set_bit();
+            });
+        });
+    }
+
+    pub fn disable_alarm_a(&mut self) {
+        self.unlocked(|rb| {
+            rb.cr().modify(|_, w| {
+                w.alrae().clear_bit();
+            });
+        });
+    }
+
+    pub fn disable_alarm_b(&mut self) {
+        self.unlocked(|rb| {
+            rb.cr().modify(|_, w| {
+                w.alrbe().clear_bit();
+            });
+        });
+    }
+
+    /// Starts the periodic wakeup timer
+    ///
+    /// Intervals up to about 4 seconds (with LSE) are counted with the RTC clock divided
+    /// by 2 to 16, longer intervals are counted in whole seconds.
+    pub fn enable_wakeup(&mut self, interval: MicroSecond) {
+        let us = interval.ticks() as u64;
+        let clock = self.clock.raw() as u64;
+
+        let (wucksel, wut) = [(0b011, 2), (0b010, 4), (0b001, 8), (0b000, 16)]
+            .iter()
+            .map(|&(sel, div)| (sel, us * clock / div / 1_000_000))
+            .find(|&(_, ticks)| ticks <= 1 << 16)
+            .unwrap_or((0b100, us / 1_000_000));
+        let wut = wut.clamp(1, 1 << 16) - 1;
+
+        self.unlocked(|rb| {
+            rb.cr().modify(|_, w| {
+                w.wute().clear_bit();
+            });
+            while rb.icsr().read().wutwf().bit_is_clear() {}
+
+            rb.wutr().write(|w| unsafe {
+                w.wut().bits(wut as u16);
+            });
+            rb.cr().modify(|_, w| unsafe {
+                w.wucksel().bits(wucksel);
+                w.wute().set_bit();
+            });
+        });
+    }
+
+    pub fn disable_wakeup(&mut self) {
+        self.unlocked(|rb| {
+            rb.cr().modify(|_, w| {
+                w.wute().clear_bit();
+            });
+        });
+    }
+
+    /// Configures the smooth digital calibration
+    ///
+    /// `pulses` is the number of RTC clock pulses added (positive) or removed (negative)
+    /// every 2^20 RTC clock cycles (32 seconds with LSE), in the range -511..=512. One pulse
+    /// corresponds to about 0.954 ppm.
+    ///
+    /// With an 8 second `cycle` the 2 lowest bits of the masked pulses are ignored, with a
+    /// 16 second `cycle` the lowest bit is ignored.
+    pub fn smooth_calibration(&mut self, pulses: i16, cycle: CalibrationCycle) {
+        assert!((-511..=512).contains(&pulses));
+        let (calp, calm) = if pulses > 0 {
+            (true, (512 - pulses) as u16)
+        } else {
+            (false, (-pulses) as u16)
+        };
+
+        self.unlocked(|rb| {
+            while rb.icsr().read().recalpf().bit_is_set() {}
+            rb.calr().write(|w| unsafe {
+                w.calp().bit(calp);
+                w.calw8().bit(cycle == CalibrationCycle::Seconds8);
+                w.calw16().bit(cycle == CalibrationCycle::Seconds16);
+                w.calm().bits(calm);
+            });
+        });
+    }
+
+    /// Reads backup register `idx`
+    ///
+    /// Backup registers are kept in the backup domain and survive system resets as well
+    /// as low power modes. They are reset together with the RTC on a clock source change.
+    pub fn read_backup_register(&self, idx: usize) -> u32 {
+        assert!(idx < BACKUP_REGISTERS);
+        let tamp = unsafe { &*TAMP::ptr() };
+        tamp.bkpr(idx).read().bits()
+    }
+
+    /// Writes backup register `idx`
+    pub fn write_backup_register(&mut self, idx: usize, value: u32) {
+        assert!(idx < BACKUP_REGISTERS);
+        let tamp = unsafe { &*TAMP::ptr() };
+        tamp.bkpr(idx).write(|w| unsafe {
+            w.bits(value);
+        });
+    }
+
+    /// Enables the RTC interrupt for `ev`
+    ///
+    /// The corresponding EXTI line needs to be enabled as well to trigger an interrupt.
+    pub fn listen(&mut self, ev: Event) {
+        self.unlocked(|rb| {
+            rb.cr().modify(|_, w| match ev {
+                Event::AlarmA => {
+                    w.alraie().set_bit();
+                }
+                Event::AlarmB => {
+                    w.alrbie().set_bit();
+                }
+                Event::WakeupTimer => {
+                    w.wutie().set_bit();
+                }
+            });
+        });
+    }
+
+    pub fn unlisten(&mut self, ev: Event) {
+        self.unlocked(|rb| {
+            rb.cr().modify(|_, w| match ev {
+                Event::AlarmA => {
+                    w.alraie().clear_bit();
+                }
+                Event::AlarmB => {
+                    w.alrbie().clear_bit();
+                }
+                Event::WakeupTimer => {
+                    w.wutie().clear_bit();
+                }
+            });
+        });
+    }
+
+    /// Returns `true` if `ev` is pending and clears it
+    pub fn check_event(&mut self, ev: Event) -> bool {
+        let sr = self.rb.sr().read();
+        let pending = match ev {
+            Event::AlarmA => sr.alraf().bit_is_set(),
+            Event::AlarmB => sr.alrbf().bit_is_set(),
+            Event::WakeupTimer => sr.wutf().bit_is_set(),
+        };
+        if pending {
+            self.rb.scr().write(|w| match ev {
+                Event::AlarmA => {
+                    w.calraf().set_bit();
+                }
+                Event::AlarmB => {
+                    w.calrbf().set_bit();
+                }
+                Event::WakeupTimer => {
+                    w.cwutf().set_bit();
+                }
+            });
+        }
+        pending
+    }
+
+    pub fn release(self) -> RTC {
+        self.rb
+    }
+
+    /// Runs `closure` with the write protection disabled
+    fn unlocked<F>(&mut self, mut closure: F)
+    where
+        F: FnMut(&mut RTC),
+    {
+        self.rb.wpr().write(|w| unsafe {
+            w.key().bits(0xCA);
+        });
+        self.rb.wpr().write(|w| unsafe {
+            w.key().bits(0x53);
+        });
+
+        closure(&mut self.rb);
+
+        self.rb.wpr().write(|w| unsafe {
+            w.key().bits(0xFF);
+        });
+    }
+
+    /// Runs `closure` in initialization mode with the write protection disabled
+    fn modify<F>(&mut self, mut closure: F)
+    where
+        F: FnMut(&mut RTC),
+    {
+        self.unlocked(|rb| {
+            if rb.icsr().read().initf().bit_is_clear() {
+                rb.icsr().modify(|_, w| {
+                    w.init().set_bit();
+                });
+                while rb.icsr().read().initf().bit_is_clear() {}
+            }
+
+            closure(rb);
+
+            rb.icsr().modify(|_, w| {
+                w.init().clear_bit();
+                w.rsf().clear_bit();
+            });
+            // Wait for the shadow registers to pick up the new calendar
+            while rb.icsr().read().rsf().bit_is_clear() {}
+        });
+    }
+}
+
+pub trait RtcExt {
+    fn constrain(self, src: RTCSrc, rcc: &mut Rcc) -> Rtc;
+}
+
+impl RtcExt for RTC {
+    fn constrain(self, src: RTCSrc, rcc: &mut Rcc) -> Rtc {
+        Rtc::new(self, src, rcc)
+    }
+}
+
+/// Day of the week (1-7, Monday is 1) of a date in the Gregorian calendar
+fn week_day(date: &Date) -> WeekDay {
+    const OFFSETS: [u32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    let year = if date.month < 3 {
+        date.year - 1
+    } else {
+        date.year
+    };
+    // 0 is Sunday
+    let day =
+        (year + year / 4 - year / 100 + year / 400 + OFFSETS[date.month as usize - 1] + date.day)
+            % 7;
+    WeekDay(if day == 0 { 7 } else { day })
+}
+
+fn bcd2_encode(word: u32) -> (u8, u8) {
+    let tens: u8 = (word / 10).try_into().unwrap();
+    let units: u8 = (word % 10).try_into().unwrap();
+    (tens, units)
+}
+
+fn bcd2_decode(tens: u8, units: u8) -> u32 {
+    (tens * 10 + units).into()
+}
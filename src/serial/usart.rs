@@ -14,6 +14,19 @@ use cortex_m::interrupt;
 use nb::block;
 
 use crate::serial::config::*;
+#[cfg(feature = "async")]
+use {
+    crate::asynch::AtomicWaker,
+    core::{future::poll_fn, task::Poll},
+};
+
+/// USART with wakers for its receiver and transmitter
+#[cfg(feature = "async")]
+trait AsyncInstance {
+    fn rx_waker() -> &'static AtomicWaker;
+    fn tx_waker() -> &'static AtomicWaker;
+}
+
 /// Serial error
 #[derive(Debug)]
 pub enum Error {
@@ -27,6 +40,15 @@ pub enum Error {
     Parity,
 }
 
+impl embedded_io::Error for Error {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        match self {
+            Error::Framing | Error::Noise | Error::Parity => embedded_io::ErrorKind::InvalidData,
+            Error::Overrun => embedded_io::ErrorKind::Other,
+        }
+    }
+}
+
 /// Interrupt event
 pub enum Event {
     /// TXFIFO reaches the threshold
@@ -350,6 +372,209 @@ macro_rules! uart_shared {
         }
 
 
+        impl<Pin> embedded_io::ErrorType for Rx<$USARTX, Pin, NoDMA> {
+            type Error = Error;
+        }
+
+        impl<Pin> embedded_io::Read for Rx<$USARTX, Pin, NoDMA> {
+            fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+                if buf.is_empty() {
+                    return Ok(0);
+                }
+
+                // Block for the first byte, then only take what is already available
+                buf[0] = block!(hal_02::serial::Read::read(self))?;
+                let mut count = 1;
+                while count < buf.len() && self.is_rxne() {
+                    buf[count] = block!(hal_02::serial::Read::read(self))?;
+                    count += 1;
+                }
+                Ok(count)
+            }
+        }
+
+        impl<Pin> embedded_io::ErrorType for Tx<$USARTX, Pin, NoDMA> {
+            type Error = Error;
+        }
+
+        impl<Pin> embedded_io::Write for Tx<$USARTX, Pin, NoDMA> {
+            fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+                if buf.is_empty() {
+                    return Ok(0);
+                }
+
+                // Block for the first byte, then only push while there is room
+                block!(hal_02::serial::Write::write(self, buf[0]))?;
+                let mut count = 1;
+                while count < buf.len() && self.is_txe() {
+                    block!(hal_02::serial::Write::write(self, buf[count]))?;
+                    count += 1;
+                }
+                Ok(count)
+            }
+
+            fn flush(&mut self) -> Result<(), Error> {
+                block!(hal_02::serial::Write::flush(self))
+            }
+        }
+
+        impl<TX, RX> embedded_io::ErrorType for Serial<$USARTX, TX, RX> {
+            type Error = Error;
+        }
+
+        impl<TX, RX> embedded_io::Read for Serial<$USARTX, TX, RX> {
+            fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+                embedded_io::Read::read(&mut self.rx, buf)
+            }
+        }
+
+        impl<TX, RX> embedded_io::Write for Serial<$USARTX, TX, RX> {
+            fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+                embedded_io::Write::write(&mut self.tx, buf)
+            }
+
+            fn flush(&mut self) -> Result<(), Error> {
+                embedded_io::Write::flush(&mut self.tx)
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl AsyncInstance for $USARTX {
+            fn rx_waker() -> &'static AtomicWaker {
+                static WAKER: AtomicWaker = AtomicWaker::new();
+                &WAKER
+            }
+
+            fn tx_waker() -> &'static AtomicWaker {
+                static WAKER: AtomicWaker = AtomicWaker::new();
+                &WAKER
+            }
+        }
+
+        /// Masks the interrupt sources enabled by the async `Read`/`Write` implementations,
+        /// this clashes with [`Rx::listen`] and [`Tx::listen`]
+        #[cfg(feature = "async")]
+        impl crate::asynch::OnInterrupt for $USARTX {
+            fn on_interrupt() {
+                let usart = unsafe { &(*$USARTX::ptr()) };
+                let isr = usart.isr().read();
+                let cr1 = usart.cr1().read();
+
+                if cr1.rxneie().bit_is_set() && (isr.rxne().bit_is_set() || isr.ore().bit_is_set()) {
+                    usart.cr1().modify(|_, w| {w.rxneie().clear_bit();});
+                    <$USARTX as AsyncInstance>::rx_waker().wake();
+                }
+                if (cr1.txeie().bit_is_set() && isr.txe().bit_is_set())
+                    || (cr1.tcie().bit_is_set() && isr.tc().bit_is_set())
+                {
+                    usart.cr1().modify(|_, w| {w.txeie().clear_bit().tcie().clear_bit();});
+                    <$USARTX as AsyncInstance>::tx_waker().wake();
+                }
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<Pin> Rx<$USARTX, Pin, NoDMA> {
+            async fn read_async(&mut self) -> Result<u8, Error> {
+                poll_fn(|cx| {
+                    <$USARTX as AsyncInstance>::rx_waker().register(cx.waker());
+                    match hal_02::serial::Read::read(self) {
+                        Ok(byte) => Poll::Ready(Ok(byte)),
+                        Err(nb::Error::Other(e)) => Poll::Ready(Err(e)),
+                        Err(nb::Error::WouldBlock) => {
+                            self.listen();
+                            Poll::Pending
+                        }
+                    }
+                })
+                .await
+            }
+        }
+
+        /// Interrupt driven, the task is woken for every received byte and no DMA is
+        /// used. The interrupt has to be forwarded to
+        /// [`OnInterrupt::on_interrupt`](crate::asynch::OnInterrupt::on_interrupt).
+        #[cfg(feature = "async")]
+        impl<Pin> embedded_io_async::Read for Rx<$USARTX, Pin, NoDMA> {
+            async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+                if buf.is_empty() {
+                    return Ok(0);
+                }
+
+                // Wait for the first byte, then only take what is already available
+                buf[0] = self.read_async().await?;
+                let mut count = 1;
+                while count < buf.len() && self.is_rxne() {
+                    buf[count] = block!(hal_02::serial::Read::read(self))?;
+                    count += 1;
+                }
+                Ok(count)
+            }
+        }
+
+        /// Interrupt driven, the task is woken for every transmitted byte and no DMA is
+        /// used. The interrupt has to be forwarded to
+        /// [`OnInterrupt::on_interrupt`](crate::asynch::OnInterrupt::on_interrupt).
+        #[cfg(feature = "async")]
+        impl<Pin> embedded_io_async::Write for Tx<$USARTX, Pin, NoDMA> {
+            async fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+                if buf.is_empty() {
+                    return Ok(0);
+                }
+
+                // Wait for room for the first byte, then only push while there is room
+                poll_fn(|cx| {
+                    <$USARTX as AsyncInstance>::tx_waker().register(cx.waker());
+                    if self.is_txe() {
+                        Poll::Ready(())
+                    } else {
+                        self.listen();
+                        Poll::Pending
+                    }
+                })
+                .await;
+
+                let mut count = 0;
+                while count < buf.len() && self.is_txe() {
+                    block!(hal_02::serial::Write::write(self, buf[count]))?;
+                    count += 1;
+                }
+                Ok(count)
+            }
+
+            async fn flush(&mut self) -> Result<(), Error> {
+                poll_fn(|cx| {
+                    <$USARTX as AsyncInstance>::tx_waker().register(cx.waker());
+                    let usart = unsafe { &(*$USARTX::ptr()) };
+                    if usart.isr().read().tc().bit_is_set() {
+                        Poll::Ready(Ok(()))
+                    } else {
+                        usart.cr1().modify(|_, w| {w.tcie().set_bit();});
+                        Poll::Pending
+                    }
+                })
+                .await
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<TX, RX> embedded_io_async::Read for Serial<$USARTX, TX, RX> {
+            async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+                embedded_io_async::Read::read(&mut self.rx, buf).await
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<TX, RX> embedded_io_async::Write for Serial<$USARTX, TX, RX> {
+            async fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+                embedded_io_async::Write::write(&mut self.tx, buf).await
+            }
+
+            async fn flush(&mut self) -> Result<(), Error> {
+                embedded_io_async::Write::flush(&mut self.tx).await
+            }
+        }
+
         impl<TX, RX> Serial<$USARTX, TX, RX> {
 
             /// Separates the serial struct into separate channel objects for sending (Tx) and
@@ -1,6 +1,6 @@
 use crate::dma::mux::DmaMuxResources;
 use crate::dma::traits::TargetAddress;
-use crate::dma::MemoryToPeripheral;
+use crate::dma::{MemoryToPeripheral, PeripheralToMemory};
 use crate::gpio::{gpioa::*, gpiob::*, gpioc::*, gpiof::*, Alternate, AF5, AF6};
 #[cfg(any(
     feature = "stm32g471",
@@ -11,6 +11,7 @@ use crate::gpio::{gpioa::*, gpiob::*, gpioc::*, gpiof::*, Alternate, AF5, AF6};
 ))]
 use crate::gpio::{gpioe::*, gpiog::*};
 use crate::rcc::{Enable, GetBusFreq, Rcc, RccBus, Reset};
+use crate::stm32::spi1::RegisterBlock as SpiRegisterBlock;
 #[cfg(any(
     feature = "stm32g471",
     feature = "stm32g473",
@@ -22,7 +23,17 @@ use crate::stm32::SPI4;
 use crate::stm32::{RCC, SPI1, SPI2, SPI3};
 use crate::time::Hertz;
 use core::cell::UnsafeCell;
-use core::ptr;
+use core::ops::Deref;
+use core::{mem, ptr};
+#[cfg(feature = "async")]
+use {
+    crate::dma::{
+        config::DmaConfig,
+        traits::{Direction, Stream},
+        Transfer,
+    },
+    embedded_dma::{StaticReadBuffer, StaticWriteBuffer},
+};
 
 pub use hal_02::spi::{Mode, Phase, Polarity, MODE_0, MODE_1, MODE_2, MODE_3};
 
@@ -35,6 +46,24 @@ pub enum Error {
     ModeFault,
     /// CRC error
     Crc,
+    /// DMA transfer error
+    Dma,
+    /// The word type does not match the frame size, see [`Word`]
+    FrameSize,
+    /// A full duplex transfer was requested in the bidirectional mode
+    Bidirectional,
+}
+
+impl hal_1::spi::Error for Error {
+    fn kind(&self) -> hal_1::spi::ErrorKind {
+        match self {
+            Error::Overrun => hal_1::spi::ErrorKind::Overrun,
+            Error::ModeFault => hal_1::spi::ErrorKind::ModeFault,
+            Error::Crc | Error::Dma | Error::FrameSize | Error::Bidirectional => {
+                hal_1::spi::ErrorKind::Other
+            }
+        }
+    }
 }
 
 /// A filler type for when the SCK pin is unnecessary
@@ -44,7 +73,10 @@ pub struct NoMiso;
 /// A filler type for when the Mosi pin is unnecessary
 pub struct NoMosi;
 
-pub trait Pins<SPI> {}
+pub trait Pins<SPI> {
+    /// The NSS pin is driven by the peripheral
+    const HARDWARE_NSS: bool = false;
+}
 
 pub trait PinSck<SPI> {}
 
@@ -52,6 +84,8 @@ pub trait PinMiso<SPI> {}
 
 pub trait PinMosi<SPI> {}
 
+pub trait PinNss<SPI> {}
+
 impl<SPI, SCK, MISO, MOSI> Pins<SPI> for (SCK, MISO, MOSI)
 where
     SCK: PinSck<SPI>,
@@ -60,6 +94,89 @@ where
 {
 }
 
+/// The NSS pin is driven low while the SPI is enabled, or pulsed high between the frames with
+/// [`Config::nss_pulse`]
+impl<SPI, SCK, MISO, MOSI, NSS> Pins<SPI> for (SCK, MISO, MOSI, NSS)
+where
+    SCK: PinSck<SPI>,
+    MISO: PinMiso<SPI>,
+    MOSI: PinMosi<SPI>,
+    NSS: PinNss<SPI>,
+{
+    const HARDWARE_NSS: bool = true;
+}
+
+/// Data word of the frames: `u8` for frames of up to 8 bits, `u16` for longer frames. Words of
+/// the other type are rejected with [`Error::FrameSize`].
+pub trait Word: Copy + Default + 'static {}
+
+impl Word for u8 {}
+
+impl Word for u16 {}
+
+/// SPI configuration
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Config {
+    mode: Mode,
+    frequency: Hertz,
+    frame_size: u8,
+    lsb_first: bool,
+    nss_pulse: bool,
+    crc_polynomial: Option<u16>,
+    bidirectional: bool,
+}
+
+impl Config {
+    /// Creates a configuration with 8-bit frames sent MSB first, without CRC
+    pub fn new<T>(mode: Mode, frequency: T) -> Self
+    where
+        T: Into<Hertz>,
+    {
+        Config {
+            mode,
+            frequency: frequency.into(),
+            frame_size: 8,
+            lsb_first: false,
+            nss_pulse: false,
+            crc_polynomial: None,
+            bidirectional: false,
+        }
+    }
+
+    /// Sets the number of bits of the frames, from 4 to 16
+    pub fn frame_size(mut self, bits: u8) -> Self {
+        assert!((4..=16).contains(&bits));
+        self.frame_size = bits;
+        self
+    }
+
+    /// Sends the least significant bit of the frames first
+    pub fn lsb_first(mut self, enable: bool) -> Self {
+        self.lsb_first = enable;
+        self
+    }
+
+    /// Pulses the hardware NSS pin high between the frames, the pins have to include the NSS
+    /// pin and the clock phase has to be [`Phase::CaptureOnFirstTransition`]
+    pub fn nss_pulse(mut self, enable: bool) -> Self {
+        self.nss_pulse = enable;
+        self
+    }
+
+    /// Computes a CRC of the frames with `polynomial`, the frames have to be 8 or 16 bits
+    /// long and the CRC has the same size
+    pub fn crc(mut self, polynomial: Option<u16>) -> Self {
+        self.crc_polynomial = polynomial;
+        self
+    }
+
+    /// Uses the single data line of the 3-wire bidirectional mode, MOSI in master mode
+    pub fn bidirectional(mut self, enable: bool) -> Self {
+        self.bidirectional = enable;
+        self
+    }
+}
+
 #[derive(Debug)]
 pub struct Spi<SPI, PINS> {
     spi: SPI,
@@ -71,6 +188,469 @@ pub trait SpiExt<SPI>: Sized {
     where
         PINS: Pins<SPI>,
         T: Into<Hertz>;
+
+    fn spi_with_config<PINS>(self, pins: PINS, config: Config, rcc: &mut Rcc) -> Spi<SPI, PINS>
+    where
+        PINS: Pins<SPI>;
+}
+
+/// Async SPI, the transfers are done by DMA
+///
+/// The DMA moves bytes only, so the frame size has to be 8 bits, and every transfer is full
+/// duplex, so the transfers fail with [`Error::Bidirectional`] in the bidirectional mode. The
+/// interrupt of the `RX` stream has to be forwarded to
+/// [`OnInterrupt::on_interrupt`](crate::asynch::OnInterrupt::on_interrupt).
+#[cfg(feature = "async")]
+pub struct AsyncSpi<SPI, PINS, TX, RX> {
+    spi: Spi<SPI, PINS>,
+    // The streams are moved into the transfers and put back by `StreamGuard`
+    tx: Option<TX>,
+    rx: Option<RX>,
+}
+
+/// Memory side of an async transfer
+///
+/// Walks `len` bytes from `ptr`, or reuses the byte at `ptr` if it does not increment.
+#[cfg(feature = "async")]
+#[derive(Clone, Copy)]
+struct DmaBuffer<P> {
+    ptr: P,
+    len: usize,
+    increment: bool,
+}
+
+#[cfg(feature = "async")]
+impl<P> DmaBuffer<P> {
+    fn incrementing(ptr: P, len: usize) -> Self {
+        Self {
+            ptr,
+            len,
+            increment: true,
+        }
+    }
+
+    fn fixed(ptr: P, len: usize) -> Self {
+        Self {
+            ptr,
+            len,
+            increment: false,
+        }
+    }
+
+    fn config(&self) -> DmaConfig {
+        DmaConfig::default().memory_increment(self.increment)
+    }
+}
+
+#[cfg(feature = "async")]
+impl DmaBuffer<*mut u8> {
+    fn window(&self, offset: usize, len: usize) -> Self {
+        let ptr = if self.increment {
+            self.ptr.wrapping_add(offset)
+        } else {
+            self.ptr
+        };
+        Self { ptr, len, ..*self }
+    }
+}
+
+#[cfg(feature = "async")]
+impl DmaBuffer<*const u8> {
+    fn window(&self, offset: usize, len: usize) -> Self {
+        let ptr = if self.increment {
+            self.ptr.wrapping_add(offset)
+        } else {
+            self.ptr
+        };
+        Self { ptr, len, ..*self }
+    }
+}
+
+// NOTE(unsafe) the async SPI stops the transfers before the borrowed buffers are
+// given back to the caller, also when the future is dropped
+#[cfg(feature = "async")]
+unsafe impl StaticReadBuffer for DmaBuffer<*const u8> {
+    type Word = u8;
+
+    unsafe fn read_buffer(&self) -> (*const u8, usize) {
+        (self.ptr, self.len)
+    }
+}
+
+#[cfg(feature = "async")]
+unsafe impl StaticWriteBuffer for DmaBuffer<*mut u8> {
+    type Word = u8;
+
+    unsafe fn write_buffer(&mut self) -> (*mut u8, usize) {
+        (self.ptr, self.len)
+    }
+}
+
+/// Stops a transfer and hands its stream back once it is done or has been cancelled
+#[cfg(feature = "async")]
+struct StreamGuard<'a, STREAM, PERIPHERAL, DIR, BUF, TXFRT>
+where
+    STREAM: Stream,
+    PERIPHERAL: TargetAddress<DIR>,
+    DIR: Direction,
+{
+    transfer: Option<Transfer<STREAM, PERIPHERAL, DIR, BUF, TXFRT>>,
+    stream: &'a mut Option<STREAM>,
+}
+
+#[cfg(feature = "async")]
+impl<STREAM, PERIPHERAL, DIR, BUF, TXFRT> StreamGuard<'_, STREAM, PERIPHERAL, DIR, BUF, TXFRT>
+where
+    STREAM: Stream,
+    PERIPHERAL: TargetAddress<DIR>,
+    DIR: Direction,
+{
+    fn transfer(&mut self) -> &mut Transfer<STREAM, PERIPHERAL, DIR, BUF, TXFRT> {
+        // Only taken on drop
+        self.transfer.as_mut().unwrap()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<STREAM, PERIPHERAL, DIR, BUF, TXFRT> Drop
+    for StreamGuard<'_, STREAM, PERIPHERAL, DIR, BUF, TXFRT>
+where
+    STREAM: Stream,
+    PERIPHERAL: TargetAddress<DIR>,
+    DIR: Direction,
+{
+    fn drop(&mut self) {
+        if let Some(transfer) = self.transfer.take() {
+            let (stream, _, _) = transfer.free();
+            *self.stream = Some(stream);
+        }
+    }
+}
+
+/// Disables the DMA requests of the SPI once the streams have been stopped
+#[cfg(feature = "async")]
+struct DmaRequests<'a>(&'a SpiRegisterBlock);
+
+#[cfg(feature = "async")]
+impl Drop for DmaRequests<'_> {
+    fn drop(&mut self) {
+        self.0.cr2().modify(|_, w| {
+            w.txdmaen().clear_bit().rxdmaen().clear_bit();
+        });
+    }
+}
+
+impl<SPI, PINS> Spi<SPI, PINS>
+where
+    SPI: Deref<Target = SpiRegisterBlock>,
+{
+    fn is_bidirectional(&self) -> bool {
+        self.spi.cr1().read().bidimode().bit_is_set()
+    }
+
+    fn wait_idle(&self) {
+        while self.spi.sr().read().bsy().bit_is_set() {}
+    }
+
+    /// Checks that `W` is the word type of the configured frame size
+    fn check_word<W: Word>(&self) -> Result<(), Error> {
+        let frame_size = self.spi.cr2().read().ds().bits() + 1;
+        let size = if frame_size > 8 { 2 } else { 1 };
+        if mem::size_of::<W>() == size {
+            Ok(())
+        } else {
+            Err(Error::FrameSize)
+        }
+    }
+
+    /// Returns `true` if a received CRC did not match the computed one
+    pub fn is_crc_error(&self) -> bool {
+        self.spi.sr().read().crcerr().bit_is_set()
+    }
+
+    /// Clears the CRC error flag
+    pub fn clear_crc_error(&mut self) {
+        self.spi.sr().modify(|_, w| {
+            w.crcerr().clear_bit();
+        });
+    }
+
+    /// Restarts the CRC computation
+    ///
+    /// # Panics
+    ///
+    /// If the CRC is not enabled
+    pub fn reset_crc(&mut self) {
+        assert!(
+            self.spi.cr1().read().crcen().bit_is_set(),
+            "the CRC is not enabled"
+        );
+        self.wait_idle();
+        // The CRC is reset when it is enabled, which is only allowed while the SPI is disabled
+        self.spi.cr1().modify(|_, w| {
+            w.spe().clear_bit();
+        });
+        self.spi.cr1().modify(|_, w| {
+            w.crcen().clear_bit();
+        });
+        self.spi.cr1().modify(|_, w| {
+            w.crcen().set_bit();
+        });
+        self.spi.cr1().modify(|_, w| {
+            w.spe().set_bit();
+        });
+    }
+
+    /// Exchanges `words` in place followed by the CRC, the received CRC is checked by the
+    /// peripheral and a mismatch is reported as [`Error::Crc`]. The CRC is restarted first.
+    ///
+    /// Nothing is sent when `words` is empty.
+    pub fn transfer_with_crc<W: Word>(&mut self, words: &mut [W]) -> Result<(), Error> {
+        if words.is_empty() {
+            return Ok(());
+        }
+        self.check_word::<W>()?;
+        self.reset_crc();
+        let last = words.len() - 1;
+        for (i, word) in words.iter_mut().enumerate() {
+            nb::block!(hal_02::spi::FullDuplex::send(self, *word))?;
+            if i == last {
+                // The CRC is sent once the last word written to the FIFO is sent
+                self.spi.cr1().modify(|_, w| {
+                    w.crcnext().set_bit();
+                });
+            }
+            *word = nb::block!(hal_02::spi::FullDuplex::read(self))?;
+        }
+        self.finish_crc::<W>()
+    }
+
+    /// Writes `words` followed by the CRC, see [`transfer_with_crc`](Self::transfer_with_crc)
+    pub fn write_with_crc<W: Word>(&mut self, words: &[W]) -> Result<(), Error> {
+        if words.is_empty() {
+            return Ok(());
+        }
+        self.check_word::<W>()?;
+        self.reset_crc();
+        let last = words.len() - 1;
+        for (i, word) in words.iter().enumerate() {
+            nb::block!(hal_02::spi::FullDuplex::send(self, *word))?;
+            if i == last {
+                self.spi.cr1().modify(|_, w| {
+                    w.crcnext().set_bit();
+                });
+            }
+            let _: W = nb::block!(hal_02::spi::FullDuplex::read(self))?;
+        }
+        self.finish_crc::<W>()
+    }
+
+    /// Discards the received CRC and reports a mismatch
+    fn finish_crc<W: Word>(&mut self) -> Result<(), Error> {
+        while self.spi.sr().read().rxne().bit_is_clear() {}
+        // NOTE(read_volatile) see `FullDuplex::read`
+        let _ = unsafe { ptr::read_volatile(self.spi.dr() as *const _ as *const W) };
+        self.wait_idle();
+        if self.is_crc_error() {
+            self.clear_crc_error();
+            return Err(Error::Crc);
+        }
+        Ok(())
+    }
+
+    /// Writes `words` on the data line of the bidirectional mode
+    pub fn write_half_duplex<W: Word>(&mut self, words: &[W]) -> Result<(), Error> {
+        self.wait_idle();
+        self.spi.cr1().modify(|_, w| {
+            w.bidioe().set_bit();
+        });
+        for word in words {
+            nb::block!(hal_02::spi::FullDuplex::send(self, *word))?;
+        }
+        while self.spi.sr().read().ftlvl().bits() != 0 {}
+        self.wait_idle();
+        Ok(())
+    }
+
+    /// Reads `words` from the data line of the bidirectional mode.
+    ///
+    /// The clock runs as soon as the data line is an input, the SPI is disabled during the
+    /// last frame to stop it after `words`.
+    pub fn read_half_duplex<W: Word>(&mut self, words: &mut [W]) -> Result<(), Error> {
+        if words.is_empty() {
+            return Ok(());
+        }
+
+        self.wait_idle();
+        self.spi.cr1().modify(|_, w| {
+            w.spe().clear_bit();
+        });
+        self.spi.cr1().modify(|_, w| {
+            w.bidioe().clear_bit();
+        });
+        self.spi.cr1().modify(|_, w| {
+            w.spe().set_bit();
+        });
+
+        let last = words.len() - 1;
+        let mut result = Ok(());
+        for (i, word) in words.iter_mut().enumerate() {
+            if i == last {
+                self.spi.cr1().modify(|_, w| {
+                    w.spe().clear_bit();
+                });
+            }
+            match nb::block!(hal_02::spi::FullDuplex::read(self)) {
+                Ok(received) => *word = received,
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+
+        // Back to an idle output, with the clock stopped
+        self.spi.cr1().modify(|_, w| {
+            w.spe().clear_bit();
+        });
+        self.spi.cr1().modify(|_, w| {
+            w.bidioe().set_bit();
+        });
+        self.spi.cr1().modify(|_, w| {
+            w.spe().set_bit();
+        });
+        result
+    }
+}
+
+impl<SPI, PINS, W> hal_02::spi::FullDuplex<W> for Spi<SPI, PINS>
+where
+    SPI: Deref<Target = SpiRegisterBlock>,
+    W: Word,
+{
+    type Error = Error;
+
+    fn read(&mut self) -> nb::Result<W, Error> {
+        self.check_word::<W>().map_err(nb::Error::Other)?;
+        let sr = self.spi.sr().read();
+
+        Err(if sr.ovr().bit_is_set() {
+            nb::Error::Other(Error::Overrun)
+        } else if sr.modf().bit_is_set() {
+            nb::Error::Other(Error::ModeFault)
+        } else if sr.crcerr().bit_is_set() {
+            self.clear_crc_error();
+            nb::Error::Other(Error::Crc)
+        } else if sr.rxne().bit_is_set() {
+            // NOTE(read_volatile) read only 1 byte for 8-bit words (the svd2rust API only
+            // allows reading a half-word)
+            return Ok(unsafe { ptr::read_volatile(self.spi.dr() as *const _ as *const W) });
+        } else {
+            nb::Error::WouldBlock
+        })
+    }
+
+    fn send(&mut self, word: W) -> nb::Result<(), Error> {
+        self.check_word::<W>().map_err(nb::Error::Other)?;
+        let sr = self.spi.sr().read();
+
+        Err(if sr.ovr().bit_is_set() {
+            nb::Error::Other(Error::Overrun)
+        } else if sr.modf().bit_is_set() {
+            nb::Error::Other(Error::ModeFault)
+        } else if sr.crcerr().bit_is_set() {
+            self.clear_crc_error();
+            nb::Error::Other(Error::Crc)
+        } else if sr.txe().bit_is_set() {
+            let dr = self.spi.dr() as *const _ as *const UnsafeCell<W>;
+            // NOTE(write_volatile) see note above
+            unsafe { ptr::write_volatile(UnsafeCell::raw_get(dr), word) };
+            return Ok(());
+        } else {
+            nb::Error::WouldBlock
+        })
+    }
+}
+
+impl<SPI, PINS> hal_1::spi::ErrorType for Spi<SPI, PINS> {
+    type Error = Error;
+}
+
+/// In the bidirectional mode, [`read`](hal_1::spi::SpiBus::read) and
+/// [`write`](hal_1::spi::SpiBus::write) use the data line in half duplex and the transfers fail
+/// with [`Error::Bidirectional`].
+impl<SPI, PINS, W> hal_1::spi::SpiBus<W> for Spi<SPI, PINS>
+where
+    SPI: Deref<Target = SpiRegisterBlock>,
+    W: Word,
+{
+    fn read(&mut self, words: &mut [W]) -> Result<(), Error> {
+        if self.is_bidirectional() {
+            return self.read_half_duplex(words);
+        }
+        for word in words.iter_mut() {
+            nb::block!(hal_02::spi::FullDuplex::send(self, W::default()))?;
+            *word = nb::block!(hal_02::spi::FullDuplex::read(self))?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[W]) -> Result<(), Error> {
+        if self.is_bidirectional() {
+            return self.write_half_duplex(words);
+        }
+        for word in words {
+            nb::block!(hal_02::spi::FullDuplex::send(self, *word))?;
+            let _: W = nb::block!(hal_02::spi::FullDuplex::read(self))?;
+        }
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Error> {
+        if self.is_bidirectional() {
+            return Err(Error::Bidirectional);
+        }
+        let len = read.len().max(write.len());
+        for i in 0..len {
+            let word = write.get(i).copied().unwrap_or_default();
+            nb::block!(hal_02::spi::FullDuplex::send(self, word))?;
+            let word = nb::block!(hal_02::spi::FullDuplex::read(self))?;
+            if let Some(read) = read.get_mut(i) {
+                *read = word;
+            }
+        }
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Error> {
+        if self.is_bidirectional() {
+            return Err(Error::Bidirectional);
+        }
+        for word in words.iter_mut() {
+            nb::block!(hal_02::spi::FullDuplex::send(self, *word))?;
+            *word = nb::block!(hal_02::spi::FullDuplex::read(self))?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.wait_idle();
+        Ok(())
+    }
+}
+
+impl<SPI, PINS, W> ::hal_02::blocking::spi::transfer::Default<W> for Spi<SPI, PINS>
+where
+    SPI: Deref<Target = SpiRegisterBlock>,
+    W: Word,
+{
+}
+
+impl<SPI, PINS, W> ::hal_02::blocking::spi::write::Default<W> for Spi<SPI, PINS>
+where
+    SPI: Deref<Target = SpiRegisterBlock>,
+    W: Word,
+{
 }
 
 macro_rules! spi {
@@ -78,7 +658,8 @@ macro_rules! spi {
         sck: [ $($( #[ $pmetasck:meta ] )* $SCK:ty,)+ ],
         miso: [ $($( #[ $pmetamiso:meta ] )* $MISO:ty,)+ ],
         mosi: [ $($( #[ $pmetamosi:meta ] )* $MOSI:ty,)+ ],
-        $mux:expr,
+        nss: [ $($( #[ $pmetanss:meta ] )* $NSS:ty,)+ ],
+        $mux:expr, $mux_rx:expr,
     ) => {
         impl PinSck<$SPIX> for NoSck {}
 
@@ -98,6 +679,10 @@ macro_rules! spi {
             $( #[ $pmetamosi ] )*
             impl PinMosi<$SPIX> for $MOSI {}
         )*
+        $(
+            $( #[ $pmetanss ] )*
+            impl PinNss<$SPIX> for $NSS {}
+        )*
 
         impl<PINS: Pins<$SPIX>> Spi<$SPIX, PINS> {
             pub fn $spiX<T>(
@@ -110,6 +695,11 @@ macro_rules! spi {
             where
             T: Into<Hertz>
             {
+                Self::with_config(spi, pins, Config::new(mode, speed), rcc)
+            }
+
+            /// Initializes the SPI in master mode with `config`
+            pub fn with_config(spi: $SPIX, pins: PINS, config: Config, rcc: &mut Rcc) -> Self {
                  // Enable and reset SPI
                 unsafe {
                     let rcc_ptr = &(*RCC::ptr());
@@ -117,10 +707,21 @@ macro_rules! spi {
                     $SPIX::reset(rcc_ptr);
                 }
 
-                // disable SS output
-                spi.cr2().write(|w| {w.ssoe().clear_bit();});
+                assert!(
+                    config.crc_polynomial.is_none() || config.frame_size == 8 || config.frame_size == 16,
+                    "the CRC is only computed on 8-bit and 16-bit frames"
+                );
+                assert!(
+                    !config.nss_pulse || config.mode.phase == Phase::CaptureOnFirstTransition,
+                    "NSS pulses require the first clock transition to capture the data"
+                );
+                assert!(
+                    !config.nss_pulse || PINS::HARDWARE_NSS,
+                    "NSS pulses require a hardware NSS pin"
+                );
 
-                let spi_freq = speed.into().raw();
+                let mode = config.mode;
+                let spi_freq = config.frequency.raw();
                 let bus_freq = <$SPIX as RccBus>::Bus::get_frequency(&rcc.clocks).raw();
                 let br = match bus_freq / spi_freq {
                     0 => unreachable!(),
@@ -134,10 +735,22 @@ macro_rules! spi {
                     _ => 0b111,
                 };
 
+                // The RXNE event is raised on 8-bit reads for frames of up to 8 bits
                 spi.cr2().write(|w| unsafe {
-                    w.frxth().set_bit().ds().bits(0b111).ssoe().clear_bit();
+                    w.frxth()
+                        .bit(config.frame_size <= 8)
+                        .ds()
+                        .bits(config.frame_size - 1)
+                        .ssoe()
+                        .bit(PINS::HARDWARE_NSS)
+                        .nssp()
+                        .bit(config.nss_pulse);
                 });
 
+                if let Some(polynomial) = config.crc_polynomial {
+                    spi.crcpr().write(|w| unsafe { w.crcpoly().bits(polynomial); });
+                }
+
                 spi.cr1().write(|w| unsafe {
                     w.cpha()
                         .bit(mode.phase == Phase::CaptureOnSecondTransition)
@@ -148,18 +761,21 @@ macro_rules! spi {
                         .br()
                         .bits(br)
                         .lsbfirst()
-                        .clear_bit()
+                        .bit(config.lsb_first)
                         .ssm()
-                        .set_bit()
+                        .bit(!PINS::HARDWARE_NSS)
                         .ssi()
                         .set_bit()
                         .rxonly()
                         .clear_bit()
+                        .crcen()
+                        .bit(config.crc_polynomial.is_some())
+                        // CRCL, the 16-bit CRC length
                         .dff()
-                        .clear_bit()
+                        .bit(config.frame_size == 16)
                         .bidimode()
-                        .clear_bit()
-                        .ssi()
+                        .bit(config.bidirectional)
+                        .bidioe()
                         .set_bit()
                         .spe()
                         .set_bit();
@@ -179,65 +795,178 @@ macro_rules! spi {
                     pins: self.pins,
                 }
             }
-        }
 
-        impl SpiExt<$SPIX> for $SPIX {
-            fn spi<PINS, T>(self, pins: PINS, mode: Mode, freq: T, rcc: &mut Rcc) -> Spi<$SPIX, PINS>
+            /// Converts the SPI into an async SPI using the `tx` and `rx` DMA streams
+            #[cfg(feature = "async")]
+            pub fn into_async<TX, RX>(self, tx: TX, rx: RX) -> AsyncSpi<$SPIX, PINS, TX, RX>
             where
-                PINS: Pins<$SPIX>,
-                T: Into<Hertz>
-                {
-                    Spi::$spiX(self, pins, mode, freq, rcc)
+                TX: Stream<Config = DmaConfig>,
+                RX: Stream<Config = DmaConfig>,
+            {
+                AsyncSpi { spi: self, tx: Some(tx), rx: Some(rx) }
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<PINS, TX, RX> AsyncSpi<$SPIX, PINS, TX, RX>
+        where
+            TX: Stream<Config = DmaConfig>,
+            RX: Stream<Config = DmaConfig>,
+        {
+            /// Releases the blocking SPI and the DMA streams
+            pub fn release(self) -> (Spi<$SPIX, PINS>, TX, RX) {
+                // The streams are always handed back when a transfer stops
+                (self.spi, self.tx.unwrap(), self.rx.unwrap())
+            }
+
+            async fn transfer_dma(
+                &mut self,
+                read: DmaBuffer<*mut u8>,
+                write: DmaBuffer<*const u8>,
+            ) -> Result<(), Error> {
+                // The DMA transfers bytes, in full duplex
+                self.spi.check_word::<u8>()?;
+                if self.spi.is_bidirectional() {
+                    return Err(Error::Bidirectional);
                 }
+
+                let AsyncSpi { spi, tx, rx } = self;
+                let spi = &*spi;
+                let mut offset = 0;
+                while offset < read.len {
+                    let chunk = (read.len - offset).min(u16::MAX as usize);
+
+                    // Dropped last, after both streams have been stopped
+                    let _requests = DmaRequests(&spi.spi);
+
+                    // The Rx DMA request has to be enabled before the Tx one, see RM0440
+                    spi.spi.cr2().modify(|_, w| {
+                        w.rxdmaen().set_bit();
+                    });
+                    let rx_buffer = read.window(offset, chunk);
+                    let mut rx = StreamGuard {
+                        transfer: Some(Transfer::<_, _, PeripheralToMemory, _, _>::init(
+                            rx.take().unwrap(),
+                            spi,
+                            rx_buffer,
+                            rx_buffer.config(),
+                        )),
+                        stream: &mut *rx,
+                    };
+                    let tx_buffer = write.window(offset, chunk);
+                    let mut tx = StreamGuard {
+                        transfer: Some(Transfer::<_, _, MemoryToPeripheral, _, _>::init_const(
+                            tx.take().unwrap(),
+                            spi,
+                            tx_buffer,
+                            tx_buffer.config(),
+                        )),
+                        stream: &mut *tx,
+                    };
+
+                    rx.transfer().start(|_| {});
+                    tx.transfer().start(|spi| {
+                        spi.spi.cr2().modify(|_, w| {
+                            w.txdmaen().set_bit();
+                        });
+                    });
+
+                    // The last byte has been clocked out once it has been received
+                    rx.transfer().wait().await.map_err(|_| Error::Dma)?;
+
+                    let sr = spi.spi.sr().read();
+                    if sr.ovr().bit_is_set() {
+                        // Cleared by reading DR followed by SR
+                        let _ = spi.spi.dr().read();
+                        let _ = spi.spi.sr().read();
+                        return Err(Error::Overrun);
+                    } else if sr.modf().bit_is_set() {
+                        return Err(Error::ModeFault);
+                    } else if sr.crcerr().bit_is_set() {
+                        return Err(Error::Crc);
+                    }
+
+                    offset += chunk;
+                }
+
+                Ok(())
+            }
         }
 
-        impl<PINS> hal_02::spi::FullDuplex<u8> for Spi<$SPIX, PINS> {
+        #[cfg(feature = "async")]
+        impl<PINS, TX, RX> hal_1::spi::ErrorType for AsyncSpi<$SPIX, PINS, TX, RX> {
             type Error = Error;
+        }
 
-            fn read(&mut self) -> nb::Result<u8, Error> {
-                let sr = self.spi.sr().read();
-
-                Err(if sr.ovr().bit_is_set() {
-                    nb::Error::Other(Error::Overrun)
-                } else if sr.modf().bit_is_set() {
-                    nb::Error::Other(Error::ModeFault)
-                } else if sr.crcerr().bit_is_set() {
-                    nb::Error::Other(Error::Crc)
-                } else if sr.rxne().bit_is_set() {
-                    // NOTE(read_volatile) read only 1 byte (the svd2rust API only allows
-                    // reading a half-word)
-                    return Ok(unsafe {
-                        ptr::read_volatile(&self.spi.dr() as *const _ as *const u8)
-                    });
-                } else {
-                    nb::Error::WouldBlock
-                })
+        #[cfg(feature = "async")]
+        impl<PINS, TX, RX> embedded_hal_async::spi::SpiBus<u8> for AsyncSpi<$SPIX, PINS, TX, RX>
+        where
+            TX: Stream<Config = DmaConfig>,
+            RX: Stream<Config = DmaConfig>,
+        {
+            async fn read(&mut self, words: &mut [u8]) -> Result<(), Error> {
+                static ZERO: u8 = 0;
+                let len = words.len();
+                let read = DmaBuffer::incrementing(words.as_mut_ptr(), len);
+                self.transfer_dma(read, DmaBuffer::fixed(&ZERO as *const u8, len)).await
+            }
+
+            async fn write(&mut self, words: &[u8]) -> Result<(), Error> {
+                let mut sink = 0u8;
+                let len = words.len();
+                let write = DmaBuffer::incrementing(words.as_ptr(), len);
+                self.transfer_dma(DmaBuffer::fixed(&mut sink as *mut u8, len), write).await
             }
 
-            fn send(&mut self, byte: u8) -> nb::Result<(), Error> {
-                let sr = self.spi.sr().read();
-
-                Err(if sr.ovr().bit_is_set() {
-                    nb::Error::Other(Error::Overrun)
-                } else if sr.modf().bit_is_set() {
-                    nb::Error::Other(Error::ModeFault)
-                } else if sr.crcerr().bit_is_set() {
-                    nb::Error::Other(Error::Crc)
-                } else if sr.txe().bit_is_set() {
-                    let dr = self.spi.dr() as *const _ as *const UnsafeCell<u8>;
-                    // NOTE(write_volatile) see note above
-                    unsafe { ptr::write_volatile(UnsafeCell::raw_get(dr), byte) };
-                    return Ok(());
+            async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Error> {
+                let common = read.len().min(write.len());
+                let r = DmaBuffer::incrementing(read.as_mut_ptr(), common);
+                let w = DmaBuffer::incrementing(write.as_ptr(), common);
+                self.transfer_dma(r, w).await?;
+                if read.len() > common {
+                    embedded_hal_async::spi::SpiBus::read(self, &mut read[common..]).await
                 } else {
-                    nb::Error::WouldBlock
-                })
+                    embedded_hal_async::spi::SpiBus::write(self, &write[common..]).await
+                }
+            }
+
+            async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Error> {
+                // The received byte always lags behind the transmitted one
+                let len = words.len();
+                let ptr = words.as_mut_ptr();
+                let write = DmaBuffer::incrementing(ptr as *const u8, len);
+                self.transfer_dma(DmaBuffer::incrementing(ptr, len), write).await
             }
+
+            async fn flush(&mut self) -> Result<(), Error> {
+                // Every transfer waits for its last byte to be received, by then the
+                // bus is idle
+                Ok(())
+            }
+        }
+
+        impl SpiExt<$SPIX> for $SPIX {
+            fn spi<PINS, T>(self, pins: PINS, mode: Mode, freq: T, rcc: &mut Rcc) -> Spi<$SPIX, PINS>
+            where
+                PINS: Pins<$SPIX>,
+                T: Into<Hertz>
+                {
+                    Spi::$spiX(self, pins, mode, freq, rcc)
+                }
+
+            fn spi_with_config<PINS>(self, pins: PINS, config: Config, rcc: &mut Rcc) -> Spi<$SPIX, PINS>
+            where
+                PINS: Pins<$SPIX>,
+                {
+                    Spi::with_config(self, pins, config, rcc)
+                }
         }
+
         unsafe impl<Pin> TargetAddress<MemoryToPeripheral> for Spi<$SPIX, Pin> {
             #[inline(always)]
             fn address(&self) -> u32 {
                 // unsafe: only the Tx part accesses the Tx register
-                &unsafe { &*<$SPIX>::ptr() }.dr() as *const _ as u32
+                unsafe { &*<$SPIX>::ptr() }.dr() as *const _ as u32
             }
 
             type MemSize = u8;
@@ -245,10 +974,17 @@ macro_rules! spi {
             const REQUEST_LINE: Option<u8> = Some($mux as u8);
         }
 
+        unsafe impl<Pin> TargetAddress<PeripheralToMemory> for Spi<$SPIX, Pin> {
+            #[inline(always)]
+            fn address(&self) -> u32 {
+                // unsafe: only the Rx part accesses the Rx register
+                unsafe { &*<$SPIX>::ptr() }.dr() as *const _ as u32
+            }
 
-        impl<PINS> ::hal_02::blocking::spi::transfer::Default<u8> for Spi<$SPIX, PINS> {}
+            type MemSize = u8;
 
-        impl<PINS> ::hal_02::blocking::spi::write::Default<u8> for Spi<$SPIX, PINS> {}
+            const REQUEST_LINE: Option<u8> = Some($mux_rx as u8);
+        }
     }
 }
 
@@ -291,7 +1027,20 @@ spi!(
         ))]
         PG4<Alternate<AF5>>,
     ],
+    nss: [
+        PA4<Alternate<AF5>>,
+        PA15<Alternate<AF5>>,
+        #[cfg(any(
+            feature = "stm32g471",
+            feature = "stm32g473",
+            feature = "stm32g474",
+            feature = "stm32g483",
+            feature = "stm32g484"
+        ))]
+        PG5<Alternate<AF5>>,
+    ],
     DmaMuxResources::SPI1_TX,
+    DmaMuxResources::SPI1_RX,
 );
 
 spi!(
@@ -311,7 +1060,12 @@ spi!(
         PA11<Alternate<AF5>>,
         PB15<Alternate<AF5>>,
     ],
+    nss: [
+        PB12<Alternate<AF5>>,
+        PF0<Alternate<AF5>>,
+    ],
     DmaMuxResources::SPI2_TX,
+    DmaMuxResources::SPI2_RX,
 );
 
 spi!(
@@ -337,7 +1091,12 @@ spi!(
         PB5<Alternate<AF6>>,
         PC12<Alternate<AF6>>,
     ],
+    nss: [
+        PA4<Alternate<AF6>>,
+        PA15<Alternate<AF6>>,
+    ],
     DmaMuxResources::SPI3_TX,
+    DmaMuxResources::SPI3_RX,
 );
 
 #[cfg(any(
@@ -362,5 +1121,96 @@ spi!(
         PE6<Alternate<AF5>>,
         PE14<Alternate<AF5>>,
     ],
+    nss: [
+        PE4<Alternate<AF5>>,
+        PE11<Alternate<AF5>>,
+    ],
     DmaMuxResources::SPI4_TX,
+    DmaMuxResources::SPI4_RX,
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Registers of a peripheral in memory, reset to 0
+    fn registers() -> SpiRegisterBlock {
+        // NOTE(unsafe) the registers are plain integers
+        unsafe { mem::zeroed() }
+    }
+
+    #[test]
+    fn empty_crc_transfers() {
+        let registers = registers();
+        registers.cr1().write(|w| {
+            w.crcen().set_bit();
+        });
+        let mut spi = Spi {
+            spi: &registers,
+            pins: (),
+        };
+
+        // Without a last word CRCNEXT would never be set, and the CRC never received
+        assert!(spi.transfer_with_crc::<u8>(&mut []).is_ok());
+        assert!(spi.write_with_crc::<u16>(&[]).is_ok());
+        assert!(registers.cr1().read().crcnext().bit_is_clear());
+    }
+
+    #[test]
+    fn word_type_matches_frame_size() {
+        let registers = registers();
+        let mut spi = Spi {
+            spi: &registers,
+            pins: (),
+        };
+
+        for (frame_size, bytes) in [(4, true), (8, true), (9, false), (16, false)] {
+            registers.cr2().write(|w| unsafe {
+                w.ds().bits(frame_size - 1);
+            });
+            assert_eq!(spi.check_word::<u8>().is_ok(), bytes, "{} bits", frame_size);
+            assert_eq!(
+                spi.check_word::<u16>().is_ok(),
+                !bytes,
+                "{} bits",
+                frame_size
+            );
+        }
+
+        // 16-bit frames
+        assert!(matches!(
+            hal_02::spi::FullDuplex::send(&mut spi, 0u8),
+            Err(nb::Error::Other(Error::FrameSize))
+        ));
+        assert!(matches!(
+            hal_02::spi::FullDuplex::<u8>::read(&mut spi),
+            Err(nb::Error::Other(Error::FrameSize))
+        ));
+        assert!(matches!(
+            hal_1::spi::SpiBus::write(&mut spi, &[0u8]),
+            Err(Error::FrameSize)
+        ));
+        assert!(matches!(spi.write_with_crc(&[0u8]), Err(Error::FrameSize)));
+    }
+
+    #[test]
+    fn bidirectional_transfers() {
+        let registers = registers();
+        registers.cr1().write(|w| {
+            w.bidimode().set_bit();
+        });
+        let mut spi = Spi {
+            spi: &registers,
+            pins: (),
+        };
+
+        assert!(matches!(
+            hal_1::spi::SpiBus::transfer(&mut spi, &mut [0u8], &[0u8]),
+            Err(Error::Bidirectional)
+        ));
+        assert!(matches!(
+            hal_1::spi::SpiBus::transfer_in_place(&mut spi, &mut [0u8]),
+            Err(Error::Bidirectional)
+        ));
+    }
+}
@@ -5,6 +5,7 @@
 
 use crate::delay::CountDown;
 use cast::{u16, u32};
+use core::convert::TryFrom;
 use cortex_m::peripheral::syst::SystClkSource;
 use cortex_m::peripheral::{DCB, DWT, SYST};
 use embedded_hal_02::timer::{Cancel, CountDown as _, Periodic};
@@ -15,6 +16,11 @@ use crate::stm32::RCC;
 use crate::rcc::{self, Clocks};
 use crate::time::{Hertz, MicroSecond};
 
+pub mod monotonic;
+pub mod opm;
+pub mod stopwatch;
+pub mod sync;
+
 /// Timer wrapper
 pub struct Timer<TIM> {
     pub(crate) tim: TIM,
@@ -71,6 +77,66 @@ pub enum TriggerSource {
     Compare4 = 0b111,
 }
 
+/// Internal trigger input of the slave mode controller
+///
+/// Each ITRx input is connected to the trigger output of another timer, see the internal
+/// trigger connection table of the reference manual.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum InternalTrigger {
+    Itr0 = 0b00000,
+    Itr1 = 0b00001,
+    Itr2 = 0b00010,
+    Itr3 = 0b00011,
+    Itr4 = 0b01000,
+    Itr5 = 0b01001,
+    Itr6 = 0b01010,
+    Itr7 = 0b01011,
+    Itr8 = 0b01100,
+    Itr9 = 0b01101,
+    Itr10 = 0b01110,
+}
+
+/// Digital filter of a timer input
+///
+/// The input is sampled at `f_CK_INT` or a fraction of `f_DTS` and a transition is only
+/// accepted after `N` consecutive samples at the new level.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum InputFilter {
+    /// No filter, sampled at f_DTS
+    None = 0b0000,
+    /// f_CK_INT, N=2
+    FckInt_N2 = 0b0001,
+    /// f_CK_INT, N=4
+    FckInt_N4 = 0b0010,
+    /// f_CK_INT, N=8
+    FckInt_N8 = 0b0011,
+    /// f_DTS/2, N=6
+    FDts_Div2_N6 = 0b0100,
+    /// f_DTS/2, N=8
+    FDts_Div2_N8 = 0b0101,
+    /// f_DTS/4, N=6
+    FDts_Div4_N6 = 0b0110,
+    /// f_DTS/4, N=8
+    FDts_Div4_N8 = 0b0111,
+    /// f_DTS/8, N=6
+    FDts_Div8_N6 = 0b1000,
+    /// f_DTS/8, N=8
+    FDts_Div8_N8 = 0b1001,
+    /// f_DTS/16, N=5
+    FDts_Div16_N5 = 0b1010,
+    /// f_DTS/16, N=6
+    FDts_Div16_N6 = 0b1011,
+    /// f_DTS/16, N=8
+    FDts_Div16_N8 = 0b1100,
+    /// f_DTS/32, N=5
+    FDts_Div32_N5 = 0b1101,
+    /// f_DTS/32, N=6
+    FDts_Div32_N6 = 0b1110,
+    /// f_DTS/32, N=8
+    FDts_Div32_N8 = 0b1111,
+}
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Error {
     /// CountDownTimer is disabled
@@ -240,7 +306,7 @@ macro_rules! hal_ext_trgo {
 }
 
 macro_rules! hal {
-    ($($TIM:ty: ($tim:ident),)+) => {
+    ($($TIM:ty: ($tim:ident, $typ:ty),)+) => {
         $(
             impl Instance for $TIM { }
 
@@ -303,11 +369,10 @@ macro_rules! hal {
 
                     let ticks = crate::time::cycles(timeout.into(), self.clk);
 
-                    let psc = u16((ticks - 1) / (1 << 16)).unwrap();
+                    let psc = u16((ticks - 1) as u64 / (<$typ>::MAX as u64 + 1)).unwrap();
                     self.tim.psc().write(|w| unsafe {w.psc().bits(psc);} );
 
-                    // TODO: TIM2 and TIM5 are 32 bit
-                    let arr = u16(ticks / u32(psc + 1)).unwrap();
+                    let arr = <$typ as TryFrom<u32>>::try_from(ticks / u32(psc + 1)).unwrap();
                     self.tim.arr().write(|w| unsafe { w.bits(u32(arr)); });
 
                     // Trigger update event to load the registers
@@ -331,8 +396,7 @@ macro_rules! hal {
 
             impl CountDown for CountDownTimer<$TIM> {
                 fn max_period(&self) -> MicroSecond {
-                    // TODO: TIM2 and TIM5 are 32 bit
-                    crate::time::duration(self.clk, u16::MAX as u32)
+                    crate::time::duration(self.clk, <$typ>::MAX as u32)
                 }
             }
 
@@ -357,17 +421,17 @@ macro_rules! hal {
 }
 
 hal! {
-    crate::stm32::TIM1: (tim1),
-    crate::stm32::TIM2: (tim2),
-    crate::stm32::TIM3: (tim3),
-    crate::stm32::TIM4: (tim4),
-    crate::stm32::TIM6: (tim6),
-    crate::stm32::TIM7: (tim7),
-    crate::stm32::TIM8: (tim8),
-
-    crate::stm32::TIM15: (tim15),
-    crate::stm32::TIM16: (tim16),
-    crate::stm32::TIM17: (tim17),
+    crate::stm32::TIM1: (tim1, u16),
+    crate::stm32::TIM2: (tim2, u32),
+    crate::stm32::TIM3: (tim3, u16),
+    crate::stm32::TIM4: (tim4, u16),
+    crate::stm32::TIM6: (tim6, u16),
+    crate::stm32::TIM7: (tim7, u16),
+    crate::stm32::TIM8: (tim8, u16),
+
+    crate::stm32::TIM15: (tim15, u16),
+    crate::stm32::TIM16: (tim16, u16),
+    crate::stm32::TIM17: (tim17, u16),
 }
 
 hal_ext_trgo! {
@@ -390,15 +454,17 @@ hal_ext_trgo! {
     feature = "stm32g484"
 ))]
 hal! {
-    crate::stm32::TIM5: (tim5),
+    crate::stm32::TIM5: (tim5, u32),
 }
 
 #[cfg(any(
     feature = "stm32g473",
     feature = "stm32g474",
     feature = "stm32g483",
-    feature = "stm32g484"
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
 ))]
 hal! {
-    crate::stm32::TIM20: (tim20),
+    crate::stm32::TIM20: (tim20, u16),
 }
@@ -0,0 +1,174 @@
+//! Monotonic timer
+//!
+//! [`Monotonic`] extends the 32-bit counter of `TIM2` or `TIM5` to 64 bits by counting its
+//! overflows in the update interrupt, so that it never wraps around in practice. Its instants
+//! and durations are the `fugit` 64-bit types at the tick rate `FREQ`.
+//!
+//! Channel 1 compares the counter against an alarm, which is what an RTIC monotonic or an
+//! embassy time driver needs: enable the rtic-monotonic feature for the `rtic_monotonic`
+//! implementation, or call [`Monotonic::set_alarm`] and [`Monotonic::on_interrupt`] from a
+//! time driver.
+//!
+//! ```ignore
+//! let mut mono = Monotonic::<TIM2, 1_000_000>::new(dp.TIM2, &mut rcc);
+//!
+//! // In the TIM2 interrupt handler
+//! mono.on_interrupt();
+//! ```
+use crate::rcc::{Enable, GetBusFreq, Rcc, Reset};
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+use crate::stm32::TIM5;
+use crate::stm32::{RCC, TIM2};
+
+pub use fugit::{TimerDurationU64, TimerInstantU64};
+
+/// 64-bit monotonic timer ticking at `FREQ` Hz
+pub struct Monotonic<TIM, const FREQ: u32> {
+    tim: TIM,
+    overflows: u32,
+}
+
+macro_rules! monotonic {
+    ($($TIMX:ident,)+) => {
+        $(
+            impl<const FREQ: u32> Monotonic<$TIMX, FREQ> {
+                /// Starts the timer, the timer clock has to be a multiple of `FREQ`.
+                ///
+                /// The timer interrupt has to be unmasked in the NVIC and call
+                /// [`on_interrupt`](Self::on_interrupt).
+                pub fn new(tim: $TIMX, rcc: &mut Rcc) -> Self {
+                    unsafe {
+                        let rcc_ptr = &(*RCC::ptr());
+                        $TIMX::enable(rcc_ptr);
+                        $TIMX::reset(rcc_ptr);
+                    }
+
+                    let clk = $TIMX::get_timer_frequency(&rcc.clocks).raw();
+                    assert!(clk % FREQ == 0);
+                    let psc = clk / FREQ - 1;
+                    assert!(psc <= 0xFFFF);
+                    tim.psc().write(|w| unsafe { w.psc().bits(psc as u16); });
+                    tim.arr().write(|w| unsafe { w.bits(u32::MAX); });
+
+                    // Load the prescaler without raising the update flag
+                    tim.cr1().modify(|_, w| { w.urs().set_bit(); });
+                    tim.egr().write(|w| { w.ug().set_bit(); });
+                    tim.cr1().modify(|_, w| { w.urs().clear_bit(); });
+
+                    // Count the overflows
+                    tim.dier().write(|w| { w.uie().set_bit(); });
+                    tim.cr1().modify(|_, w| { w.cen().set_bit(); });
+
+                    Self { tim, overflows: 0 }
+                }
+
+                /// Returns the current time
+                pub fn now(&self) -> TimerInstantU64<FREQ> {
+                    let mut overflows = self.overflows;
+                    let cnt = self.tim.cnt().read().bits();
+                    // An overflow may have happened since the last interrupt, the counter then
+                    // has to be small, which tells it apart from a read just before the overflow
+                    if self.tim.sr().read().uif().bit_is_set() && cnt < (1 << 31) {
+                        overflows += 1;
+                    }
+                    TimerInstantU64::from_ticks((overflows as u64) << 32 | cnt as u64)
+                }
+
+                /// Raises the compare interrupt when the counter reaches the low 32 bits of
+                /// `instant`.
+                ///
+                /// The interrupt is raised early if `instant` is more than 2^32 ticks away, the
+                /// caller has to compare [`now`](Self::now) to `instant` and set the alarm again.
+                pub fn set_alarm(&mut self, instant: TimerInstantU64<FREQ>) {
+                    self.tim.ccr1().write(|w| unsafe { w.bits(instant.ticks() as u32); });
+                    self.tim.dier().modify(|_, w| { w.cc1ie().set_bit(); });
+                }
+
+                /// Disables the compare interrupt
+                pub fn disable_alarm(&mut self) {
+                    self.tim.dier().modify(|_, w| { w.cc1ie().clear_bit(); });
+                }
+
+                /// Returns `true` if the compare interrupt is pending
+                #[inline]
+                pub fn is_alarm_pending(&self) -> bool {
+                    self.tim.sr().read().cc1if().bit_is_set()
+                }
+
+                /// Clears the compare interrupt flag
+                #[inline]
+                pub fn clear_alarm(&mut self) {
+                    self.tim.sr().modify(|_, w| { w.cc1if().clear_bit(); });
+                }
+
+                /// Counts the overflows of the counter, call it from the timer interrupt
+                pub fn on_interrupt(&mut self) {
+                    if self.tim.sr().read().uif().bit_is_set() {
+                        self.tim.sr().modify(|_, w| { w.uif().clear_bit(); });
+                        self.overflows = self.overflows.wrapping_add(1);
+                    }
+                }
+
+                /// Stops the timer and releases it
+                pub fn release(self) -> $TIMX {
+                    self.tim.cr1().modify(|_, w| { w.cen().clear_bit(); });
+                    self.tim.dier().reset();
+                    self.tim
+                }
+            }
+
+            #[cfg(feature = "rtic-monotonic")]
+            impl<const FREQ: u32> rtic_monotonic::Monotonic for Monotonic<$TIMX, FREQ> {
+                type Instant = TimerInstantU64<FREQ>;
+                type Duration = TimerDurationU64<FREQ>;
+
+                fn now(&mut self) -> Self::Instant {
+                    Monotonic::now(self)
+                }
+
+                fn set_compare(&mut self, instant: Self::Instant) {
+                    self.set_alarm(instant);
+                }
+
+                fn clear_compare_flag(&mut self) {
+                    self.clear_alarm();
+                }
+
+                fn zero() -> Self::Instant {
+                    TimerInstantU64::from_ticks(0)
+                }
+
+                unsafe fn reset(&mut self) {
+                    self.tim.cnt().reset();
+                    self.tim.sr().modify(|_, w| { w.uif().clear_bit(); });
+                    self.overflows = 0;
+                }
+
+                fn on_interrupt(&mut self) {
+                    Monotonic::on_interrupt(self);
+                }
+            }
+        )+
+    };
+}
+
+monotonic! {
+    TIM2,
+}
+
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+monotonic! {
+    TIM5,
+}
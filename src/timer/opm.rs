@@ -0,0 +1,362 @@
+//! One-pulse mode
+//!
+//! In one-pulse mode (OPM), the counter of a timer stops at the end of its period, so that each
+//! trigger produces a single pulse on an output channel after a programmable delay. The
+//! advanced timers `TIM1`, `TIM8`, `TIM15`, `TIM16`, `TIM17` and `TIM20` support it.
+//!
+//! ```ignore
+//! let pin: PA8<Alternate<AF6>> = gpioa.pa8.into_alternate();
+//!
+//! // Pulses up to 1 ms long including the delay
+//! let mut opm = dp.TIM1.opm(pin, 1.millis(), &mut rcc);
+//! opm.set_delay(10.micros());
+//! opm.set_pulse_width(500.nanos());
+//! opm.enable();
+//!
+//! opm.trigger();
+//! ```
+//!
+//! The pulse can also be triggered in hardware, by the channel 1 or 2 input, by the ETR input or
+//! by the trigger output of another timer. Except for `TIM16` and `TIM17`, which have no slave
+//! mode controller, the timers also support the retriggerable one-pulse mode: the pulse starts
+//! without delay and is extended by any trigger happening before its end.
+//!
+//! The repetition counter turns each pulse into a train of pulses, see [`Opm::set_pulse_count`].
+use core::marker::PhantomData;
+
+use crate::pwm::{Pins, C1, C2, C3, C4};
+use crate::qei::IndexPin;
+use crate::rcc::{Enable, GetBusFreq, Rcc, Reset};
+use crate::stm32::RCC;
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+use crate::stm32::TIM20;
+use crate::stm32::{TIM1, TIM15, TIM16, TIM17, TIM8};
+use crate::time::{Duration, Hertz};
+use crate::timer::InternalTrigger;
+
+/// Edge of an input starting the pulse
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum TriggerEdge {
+    /// Rising edges
+    Rising,
+    /// Falling edges
+    Falling,
+}
+
+/// One-pulse output channel of a timer
+pub struct Opm<TIM, CHANNEL> {
+    tim: TIM,
+    clk: Hertz,
+    delay: u32,
+    width: u32,
+    retriggerable: bool,
+    _channel: PhantomData<CHANNEL>,
+}
+
+/// Allows the opm() method to be added to the timer peripherals
+pub trait OpmExt: Sized {
+    /// Configures the timer in one-pulse mode with the output on `pin`.
+    ///
+    /// The resolution of the delay and the pulse width is chosen so that their sum can reach
+    /// `max_duration`.
+    fn opm<PIN, CHANNEL, COMP, const DENOM: u32>(
+        self,
+        pin: PIN,
+        max_duration: Duration<u32, 1, DENOM>,
+        rcc: &mut Rcc,
+    ) -> Opm<Self, CHANNEL>
+    where
+        PIN: Pins<Self, CHANNEL, COMP>;
+}
+
+/// Number of timer ticks in a `duration`
+fn ticks<const DENOM: u32>(duration: Duration<u32, 1, DENOM>, clk: Hertz) -> u32 {
+    (clk.raw() as u64 * duration.ticks() as u64 / DENOM as u64) as u32
+}
+
+macro_rules! opm {
+    ($($TIMX:ident: ($rep:ty, [$(($CX:ident, $ccmr:ident, $ocm:ident, $ocm_3:ident, $ccr:ident, $cce:ident, $ccp:ident),)+]),)+) => {
+        $(
+            impl OpmExt for $TIMX {
+                fn opm<PIN, CHANNEL, COMP, const DENOM: u32>(
+                    self,
+                    _pin: PIN,
+                    max_duration: Duration<u32, 1, DENOM>,
+                    rcc: &mut Rcc,
+                ) -> Opm<Self, CHANNEL>
+                where
+                    PIN: Pins<Self, CHANNEL, COMP>,
+                {
+                    unsafe {
+                        let rcc_ptr = &(*RCC::ptr());
+                        $TIMX::enable(rcc_ptr);
+                        $TIMX::reset(rcc_ptr);
+                    }
+
+                    let clk = $TIMX::get_timer_frequency(&rcc.clocks);
+                    let psc = ticks(max_duration, clk).saturating_sub(1) / (1 << 16);
+                    assert!(psc <= 0xFFFF);
+                    self.psc().write(|w| unsafe { w.psc().bits(psc as u16); });
+                    let clk = Hertz::from_raw(clk.raw() / (psc + 1));
+
+                    // Stop the counter at the end of the pulse
+                    self.cr1().write(|w| { w.opm().set_bit(); });
+                    self.bdtr().write(|w| { w.moe().set_bit(); });
+
+                    let mut opm = Opm {
+                        tim: self,
+                        clk,
+                        delay: 1,
+                        width: 1,
+                        retriggerable: false,
+                        _channel: PhantomData,
+                    };
+                    opm.apply();
+                    opm
+                }
+            }
+
+            impl<CHANNEL> Opm<$TIMX, CHANNEL> {
+                /// Starts the pulse, or the pulse train
+                #[inline]
+                pub fn trigger(&mut self) {
+                    self.tim.cr1().modify(|_, w| { w.cen().set_bit(); });
+                }
+
+                /// Returns `true` while a pulse is being generated
+                #[inline]
+                pub fn is_running(&self) -> bool {
+                    self.tim.cr1().read().cen().bit_is_set()
+                }
+
+                /// Generates `count` pulses on each trigger instead of one, `count` has to be at
+                /// least 1. Each pulse of the train has the same delay and width.
+                pub fn set_pulse_count(&mut self, count: $rep) {
+                    assert!(count > 0);
+                    self.tim.rcr().write(|w| unsafe { w.rep().bits(count - 1); });
+                    if !self.is_running() {
+                        // Load the repetition counter without raising the update flag
+                        self.tim.cr1().modify(|_, w| { w.urs().set_bit(); });
+                        self.tim.egr().write(|w| { w.ug().set_bit(); });
+                        self.tim.cr1().modify(|_, w| { w.urs().clear_bit(); });
+                    }
+                }
+
+                /// Returns the frequency of the counter, the resolution of the delay and pulse width
+                #[inline]
+                pub fn frequency(&self) -> Hertz {
+                    self.clk
+                }
+
+                /// Stops the timer and releases it
+                pub fn release(self) -> $TIMX {
+                    self.tim.cr1().modify(|_, w| { w.cen().clear_bit(); });
+                    self.tim
+                }
+            }
+
+            $(
+                impl Opm<$TIMX, $CX> {
+                    /// Sets the delay between the trigger and the start of the pulse, the delay
+                    /// is at least one tick of the counter
+                    pub fn set_delay<const DENOM: u32>(&mut self, delay: Duration<u32, 1, DENOM>) {
+                        self.delay = ticks(delay, self.clk).max(1);
+                        self.apply();
+                    }
+
+                    /// Sets the width of the pulse
+                    pub fn set_pulse_width<const DENOM: u32>(&mut self, width: Duration<u32, 1, DENOM>) {
+                        self.width = ticks(width, self.clk).max(1);
+                        self.apply();
+                    }
+
+                    /// Inverts the output, the pulse is then active low
+                    #[inline]
+                    pub fn set_active_low(&mut self, active_low: bool) {
+                        self.tim.ccer().modify(|_, w| { w.$ccp().bit(active_low); });
+                    }
+
+                    /// Enables the output
+                    #[inline]
+                    pub fn enable(&mut self) {
+                        self.tim.ccer().modify(|_, w| { w.$cce().set_bit(); });
+                    }
+
+                    /// Disables the output
+                    #[inline]
+                    pub fn disable(&mut self) {
+                        self.tim.ccer().modify(|_, w| { w.$cce().clear_bit(); });
+                    }
+
+                    /// Writes the output mode and the timings
+                    fn apply(&mut self) {
+                        let (mode, ccr, arr) = if self.retriggerable {
+                            // Retriggerable OPM mode 2, the pulse starts on the trigger
+                            (0b1001, 0, self.width - 1)
+                        } else {
+                            // PWM mode 2, the pulse starts at the end of the delay
+                            (0b0111, self.delay, self.delay + self.width - 1)
+                        };
+                        assert!(arr <= 0xFFFF);
+
+                        self.tim.$ccmr().modify(|_, w| unsafe { w
+                            .$ocm().bits(mode & 0b111)
+                            .$ocm_3().bit(mode & 0b1000 != 0);
+                        });
+                        self.tim.$ccr().write(|w| unsafe { w.bits(ccr); });
+                        self.tim.arr().write(|w| unsafe { w.bits(arr); });
+                    }
+                }
+            )+
+        )+
+    };
+}
+
+macro_rules! opm_trigger {
+    ($($TIMX:ident: [$($CX:ident),+] $(, ETR: $etr:ident)?,)+) => {
+        $(
+            impl<CHANNEL> Opm<$TIMX, CHANNEL> {
+                /// Starts the pulse on the edges of the channel 1 input, the output has to be
+                /// on another channel
+                pub fn trigger_on_ti1<PIN, COMP>(&mut self, _pin: PIN, edge: TriggerEdge)
+                where
+                    PIN: Pins<$TIMX, C1, COMP>,
+                {
+                    self.tim.ccmr1_input().modify(|_, w| unsafe { w.cc1s().bits(0b01); });
+                    self.tim.ccer().modify(|_, w| { w.cc1p().bit(edge == TriggerEdge::Falling).cc1np().clear_bit(); });
+                    // TI1FP1
+                    self.set_slave_trigger(0b00101);
+                }
+
+                /// Starts the pulse on the edges of the channel 2 input, the output has to be
+                /// on another channel
+                pub fn trigger_on_ti2<PIN, COMP>(&mut self, _pin: PIN, edge: TriggerEdge)
+                where
+                    PIN: Pins<$TIMX, C2, COMP>,
+                {
+                    self.tim.ccmr1_input().modify(|_, w| unsafe { w.cc2s().bits(0b01); });
+                    self.tim.ccer().modify(|_, w| { w.cc2p().bit(edge == TriggerEdge::Falling).cc2np().clear_bit(); });
+                    // TI2FP2
+                    self.set_slave_trigger(0b00110);
+                }
+
+                $(
+                    /// Starts the pulse on the edges of the ETR input
+                    pub fn trigger_on_etr<PIN>(&mut self, _pin: &PIN, edge: TriggerEdge)
+                    where
+                        PIN: IndexPin<$TIMX>,
+                    {
+                        self.tim.$etr().modify(|_, w| { w.etp().bit(edge == TriggerEdge::Falling); });
+                        // ETRF
+                        self.set_slave_trigger(0b00111);
+                    }
+                )?
+
+                /// Starts the pulse on the trigger output of another timer
+                pub fn trigger_on_internal(&mut self, trigger: InternalTrigger) {
+                    self.set_slave_trigger(trigger as u8);
+                }
+
+                /// Only starts the pulse with [`trigger`](Self::trigger)
+                pub fn trigger_by_software(&mut self) {
+                    self.tim.smcr().modify(|_, w| unsafe { w.sms().bits(0).sms_3().clear_bit(); });
+                }
+
+                /// Selects the trigger input and the slave mode starting the counter
+                fn set_slave_trigger(&mut self, ts: u8) {
+                    let retriggerable = self.retriggerable;
+                    self.tim.smcr().modify(|_, w| unsafe {
+                        w.ts().bits(ts & 0b111).ts_4_3().bits(ts >> 3);
+                        if retriggerable {
+                            // Combined reset + trigger mode
+                            w.sms().bits(0b000).sms_3().set_bit();
+                        } else {
+                            // Trigger mode
+                            w.sms().bits(0b110).sms_3().clear_bit();
+                        }
+                    });
+                }
+            }
+
+            $(
+                impl Opm<$TIMX, $CX> {
+                    /// Selects the retriggerable one-pulse mode, in which the pulse starts on
+                    /// the trigger without delay and each trigger during the pulse extends it.
+                    ///
+                    /// Only hardware triggers restart the pulse, select one after this call.
+                    pub fn set_retriggerable(&mut self, retriggerable: bool) {
+                        self.retriggerable = retriggerable;
+                        self.apply();
+                    }
+                }
+            )+
+        )+
+    };
+}
+
+opm! {
+    TIM1: (u16, [
+        (C1, ccmr1_output, oc1m, oc1m_3, ccr1, cc1e, cc1p),
+        (C2, ccmr1_output, oc2m, oc2m_3, ccr2, cc2e, cc2p),
+        (C3, ccmr2_output, oc3m, oc3m_3, ccr3, cc3e, cc3p),
+        (C4, ccmr2_output, oc4m, oc4m_3, ccr4, cc4e, cc4p),
+    ]),
+    TIM8: (u16, [
+        (C1, ccmr1_output, oc1m, oc1m_3, ccr1, cc1e, cc1p),
+        (C2, ccmr1_output, oc2m, oc2m_3, ccr2, cc2e, cc2p),
+        (C3, ccmr2_output, oc3m, oc3m_3, ccr3, cc3e, cc3p),
+        (C4, ccmr2_output, oc4m, oc4m_3, ccr4, cc4e, cc4p),
+    ]),
+    TIM15: (u8, [
+        (C1, ccmr1_output, oc1m, oc1m_3, ccr1, cc1e, cc1p),
+        (C2, ccmr1_output, oc2m, oc2m_3, ccr2, cc2e, cc2p),
+    ]),
+    TIM16: (u8, [
+        (C1, ccmr1_output, oc1m, oc1m_3, ccr1, cc1e, cc1p),
+    ]),
+    TIM17: (u8, [
+        (C1, ccmr1_output, oc1m, oc1m_3, ccr1, cc1e, cc1p),
+    ]),
+}
+
+opm_trigger! {
+    TIM1: [C1, C2, C3, C4], ETR: smcr,
+    TIM8: [C1, C2, C3, C4], ETR: smcr,
+    TIM15: [C1, C2],
+}
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+opm! {
+    TIM20: (u16, [
+        (C1, ccmr1_output, oc1m, oc1m_3, ccr1, cc1e, cc1p),
+        (C2, ccmr1_output, oc2m, oc2m_3, ccr2, cc2e, cc2p),
+        (C3, ccmr2_output, oc3m, oc3m_3, ccr3, cc3e, cc3p),
+        (C4, ccmr2_output, oc4m, oc4m_3, ccr4, cc4e, cc4p),
+    ]),
+}
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+opm_trigger! {
+    TIM20: [C1, C2, C3, C4],
+}
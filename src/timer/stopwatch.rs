@@ -0,0 +1,130 @@
+//! Stopwatch
+//!
+//! The 32-bit counters of `TIM2` and `TIM5` count microseconds for about 71 minutes before
+//! wrapping around, which makes them suitable to measure durations and lap times.
+//!
+//! ```ignore
+//! let mut stopwatch = dp.TIM2.stopwatch(&mut rcc);
+//!
+//! let start = stopwatch.now();
+//! // ...
+//! let lap1 = stopwatch.lap();
+//! // ...
+//! let lap2 = stopwatch.lap();
+//! let total = stopwatch.elapsed(start);
+//! ```
+use crate::rcc::{Enable, GetBusFreq, Rcc, Reset};
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+use crate::stm32::TIM5;
+use crate::stm32::{RCC, TIM2};
+use crate::time::{Instant, MicroSecond};
+
+/// Microsecond counter measuring durations and lap times
+pub struct Stopwatch<TIM> {
+    tim: TIM,
+    lap: Instant,
+}
+
+/// Allows the stopwatch() method to be added to the 32-bit timer peripherals
+pub trait StopwatchExt: Sized {
+    /// Starts counting microseconds
+    fn stopwatch(self, rcc: &mut Rcc) -> Stopwatch<Self>;
+}
+
+macro_rules! stopwatch {
+    ($($TIMX:ident,)+) => {
+        $(
+            impl StopwatchExt for $TIMX {
+                fn stopwatch(self, rcc: &mut Rcc) -> Stopwatch<Self> {
+                    unsafe {
+                        let rcc_ptr = &(*RCC::ptr());
+                        $TIMX::enable(rcc_ptr);
+                        $TIMX::reset(rcc_ptr);
+                    }
+
+                    let clk = $TIMX::get_timer_frequency(&rcc.clocks).raw();
+                    assert!(clk % 1_000_000 == 0);
+                    let psc = clk / 1_000_000 - 1;
+                    self.psc().write(|w| unsafe { w.psc().bits(psc as u16); });
+                    self.arr().write(|w| unsafe { w.bits(u32::MAX); });
+                    // Load the prescaler
+                    self.egr().write(|w| { w.ug().set_bit(); });
+                    self.cr1().write(|w| { w.cen().set_bit(); });
+
+                    Stopwatch {
+                        tim: self,
+                        lap: Instant::from_ticks(0),
+                    }
+                }
+            }
+
+            impl Stopwatch<$TIMX> {
+                /// Returns the current time
+                #[inline]
+                pub fn now(&self) -> Instant {
+                    Instant::from_ticks(self.tim.cnt().read().bits())
+                }
+
+                /// Returns the time elapsed since `since`, which has to be less than 71 minutes ago
+                #[inline]
+                pub fn elapsed(&self, since: Instant) -> MicroSecond {
+                    MicroSecond::from_ticks(self.now().ticks().wrapping_sub(since.ticks()))
+                }
+
+                /// Returns the time elapsed since the previous lap, or since the stopwatch was
+                /// reset, and starts a new lap
+                pub fn lap(&mut self) -> MicroSecond {
+                    let now = self.now();
+                    let lap = MicroSecond::from_ticks(now.ticks().wrapping_sub(self.lap.ticks()));
+                    self.lap = now;
+                    lap
+                }
+
+                /// Resets the counter to zero and starts a new lap
+                pub fn reset(&mut self) {
+                    self.tim.cnt().reset();
+                    self.lap = Instant::from_ticks(0);
+                }
+
+                /// Pauses the counter
+                #[inline]
+                pub fn pause(&mut self) {
+                    self.tim.cr1().modify(|_, w| { w.cen().clear_bit(); });
+                }
+
+                /// Resumes the counter
+                #[inline]
+                pub fn resume(&mut self) {
+                    self.tim.cr1().modify(|_, w| { w.cen().set_bit(); });
+                }
+
+                /// Stops the counter and releases the timer
+                pub fn release(self) -> $TIMX {
+                    self.tim.cr1().modify(|_, w| { w.cen().clear_bit(); });
+                    self.tim
+                }
+            }
+        )+
+    };
+}
+
+stopwatch! {
+    TIM2,
+}
+
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+stopwatch! {
+    TIM5,
+}
@@ -0,0 +1,438 @@
+//! Timer synchronization
+//!
+//! The slave mode controller of a timer can reset, gate, start or clock its counter from a
+//! trigger input: its channel 1 or 2 input, its ETR input, or the trigger output (TRGO) of
+//! another timer through one of the internal trigger inputs (ITRx).
+//!
+//! [`TriggerFrom`] gives the internal trigger input connecting two timers, so that a slave can be
+//! synchronized to a master without looking up the connection table:
+//!
+//! ```ignore
+//! let (mut master, _) = dp.TIM1.pwm_advanced(pins1, &mut rcc).frequency(20.kHz()).finalize();
+//! let (mut slave, _) = dp.TIM8.pwm_advanced(pins8, &mut rcc).frequency(20.kHz()).finalize();
+//!
+//! // Reset TIM8 along with TIM1 to lock the phase of their PWM outputs
+//! master.set_trigger_output(TriggerSource::Reset);
+//! slave.sync_to::<TIM1>(SlaveMode::Reset);
+//! master.reset_counter();
+//! ```
+//!
+//! Two 16-bit timers can also be chained into a 32-bit counter, see [`Timer::chain`].
+use crate::pwm::PwmControl;
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+use crate::stm32::TIM20;
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+use crate::stm32::TIM5;
+use crate::stm32::{TIM1, TIM15, TIM2, TIM3, TIM4, TIM8};
+use crate::time::Hertz;
+
+use super::{Instance, InternalTrigger, Timer, TriggerSource};
+
+/// Mode of the slave mode controller
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum SlaveMode {
+    /// The counter is clocked by the internal clock and ignores the trigger input
+    Disabled = 0b0000,
+    /// The trigger resets the counter
+    Reset = 0b0100,
+    /// The counter runs while the trigger input is high
+    Gated = 0b0101,
+    /// The trigger starts the counter, which is stopped when selecting this mode
+    Trigger = 0b0110,
+    /// The rising edges of the trigger input clock the counter
+    ExternalClock = 0b0111,
+    /// The trigger resets and starts the counter, which is stopped when selecting this mode
+    ResetTrigger = 0b1000,
+    /// The trigger resets the counter, which then runs while the trigger input is high
+    GatedReset = 0b1001,
+}
+
+/// Trigger input of the slave mode controller
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum TriggerInput {
+    /// Trigger output of another timer
+    Internal(InternalTrigger),
+    /// Both edges of the channel 1 input
+    Ti1Edge,
+    /// Filtered channel 1 input, with the polarity of channel 1
+    Ti1,
+    /// Filtered channel 2 input, with the polarity of channel 2
+    Ti2,
+    /// Filtered ETR input
+    Etr,
+}
+
+impl TriggerInput {
+    /// The TS bits of the slave mode controller
+    fn ts(self) -> u8 {
+        match self {
+            TriggerInput::Internal(itr) => itr as u8,
+            TriggerInput::Ti1Edge => 0b00100,
+            TriggerInput::Ti1 => 0b00101,
+            TriggerInput::Ti2 => 0b00110,
+            TriggerInput::Etr => 0b00111,
+        }
+    }
+}
+
+/// Timers whose internal trigger input `ITR` is connected to the trigger output of `MASTER`
+pub trait TriggerFrom<MASTER> {
+    const ITR: InternalTrigger;
+}
+
+/// 16-bit timers that can be chained into a 32-bit counter
+pub trait Chainable: Instance {
+    #[doc(hidden)]
+    fn start_low(&self, psc: u16);
+    #[doc(hidden)]
+    fn start_high(&self, itr: InternalTrigger);
+    #[doc(hidden)]
+    fn count(&self) -> u16;
+    #[doc(hidden)]
+    fn stop(&self);
+}
+
+/// 32-bit counter made of two chained 16-bit timers
+pub struct ChainedTimer<LOW, HIGH> {
+    low: LOW,
+    high: HIGH,
+    clk: Hertz,
+}
+
+impl<LOW> Timer<LOW>
+where
+    LOW: Chainable,
+{
+    /// Chains `high` to this timer, which counts the low half-word at `frequency`, while `high`
+    /// counts its overflows.
+    pub fn chain<HIGH, T>(self, high: Timer<HIGH>, frequency: T) -> ChainedTimer<LOW, HIGH>
+    where
+        HIGH: Chainable + TriggerFrom<LOW>,
+        T: Into<Hertz>,
+    {
+        let psc = (self.clk.raw() / frequency.into().raw()).max(1) - 1;
+        assert!(psc <= 0xFFFF);
+
+        // The high timer waits for the first overflow of the low one
+        high.tim.start_high(HIGH::ITR);
+        self.tim.start_low(psc as u16);
+
+        ChainedTimer {
+            low: self.tim,
+            high: high.tim,
+            clk: Hertz::from_raw(self.clk.raw() / (psc + 1)),
+        }
+    }
+}
+
+impl<LOW, HIGH> ChainedTimer<LOW, HIGH>
+where
+    LOW: Chainable,
+    HIGH: Chainable,
+{
+    /// Returns the current count
+    pub fn now(&self) -> u32 {
+        loop {
+            let high = self.high.count();
+            let low = self.low.count();
+            // Retry if the low half-word overflowed between the reads
+            if self.high.count() == high {
+                return (high as u32) << 16 | low as u32;
+            }
+        }
+    }
+
+    /// Returns the frequency of the counter
+    #[inline]
+    pub fn frequency(&self) -> Hertz {
+        self.clk
+    }
+
+    /// Stops both timers and releases them
+    pub fn release(self) -> (LOW, HIGH) {
+        self.low.stop();
+        self.high.stop();
+        (self.low, self.high)
+    }
+}
+
+macro_rules! slave {
+    ($($TIM:ident: $mms:ident,)+) => {
+        $(
+            impl Timer<$TIM> {
+                /// Selects the mode and the trigger input of the slave mode controller
+                pub fn set_slave_mode(&mut self, mode: SlaveMode, input: TriggerInput) {
+                    let tim = unsafe { &*$TIM::ptr() };
+                    slave!(@write tim, mode, input);
+                }
+
+                /// Synchronizes this timer to the trigger output of `MASTER`
+                pub fn sync_to<MASTER>(&mut self, mode: SlaveMode)
+                where
+                    $TIM: TriggerFrom<MASTER>,
+                {
+                    self.set_slave_mode(mode, TriggerInput::Internal(<$TIM as TriggerFrom<MASTER>>::ITR));
+                }
+            }
+
+            impl<FAULT> PwmControl<$TIM, FAULT> {
+                /// Selects the mode and the trigger input of the slave mode controller
+                pub fn set_slave_mode(&mut self, mode: SlaveMode, input: TriggerInput) {
+                    let tim = unsafe { &*$TIM::ptr() };
+                    slave!(@write tim, mode, input);
+                }
+
+                /// Synchronizes this timer to the trigger output of `MASTER`
+                pub fn sync_to<MASTER>(&mut self, mode: SlaveMode)
+                where
+                    $TIM: TriggerFrom<MASTER>,
+                {
+                    self.set_slave_mode(mode, TriggerInput::Internal(<$TIM as TriggerFrom<MASTER>>::ITR));
+                }
+            }
+
+            master!($TIM: $mms);
+        )+
+    };
+    (@write $tim:ident, $mode:ident, $input:ident) => {{
+        let ts = $input.ts();
+        let sms = $mode as u8;
+
+        // The trigger input can only be changed while the slave mode controller is disabled
+        $tim.smcr().modify(|_, w| unsafe { w.sms().bits(0).sms_3().clear_bit(); });
+        $tim.smcr().modify(|_, w| unsafe { w.ts().bits(ts & 0b111).ts_4_3().bits(ts >> 3); });
+
+        if matches!($mode, SlaveMode::Trigger | SlaveMode::ResetTrigger) {
+            // Wait for the trigger to start the counter
+            $tim.cr1().modify(|_, w| { w.cen().clear_bit(); });
+            $tim.cnt().reset();
+        }
+
+        $tim.smcr().modify(|_, w| unsafe { w.sms().bits(sms & 0b111).sms_3().bit(sms & 0b1000 != 0); });
+    }};
+}
+
+macro_rules! master {
+    ($($TIM:ident: $mms:ident),+) => {
+        $(
+            impl<FAULT> PwmControl<$TIM, FAULT> {
+                /// Selects the trigger output (TRGO) of the timer, which can drive the slave mode
+                /// controller of other timers
+                pub fn set_trigger_output(&mut self, trigger_source: TriggerSource) {
+                    let tim = unsafe { &*$TIM::ptr() };
+                    tim.cr2().modify(|_, w| unsafe { w.$mms().bits(trigger_source as u8); });
+                }
+
+                /// Resets the counter, which also resets the slaves when the trigger output is
+                /// [`TriggerSource::Reset`]
+                pub fn reset_counter(&mut self) {
+                    let tim = unsafe { &*$TIM::ptr() };
+                    tim.egr().write(|w| { w.ug().set_bit(); });
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! chainable {
+    ($($TIM:ident: $mms:ident,)+) => {
+        $(
+            impl Chainable for $TIM {
+                fn start_low(&self, psc: u16) {
+                    self.psc().write(|w| unsafe { w.psc().bits(psc); });
+                    self.arr().write(|w| unsafe { w.bits(0xFFFF); });
+                    // Load the prescaler before the update event is routed to TRGO
+                    self.egr().write(|w| { w.ug().set_bit(); });
+                    self.cr2().modify(|_, w| unsafe { w.$mms().bits(TriggerSource::Update as u8); });
+                    self.cr1().modify(|_, w| { w.cen().set_bit(); });
+                }
+
+                fn start_high(&self, itr: InternalTrigger) {
+                    self.psc().write(|w| unsafe { w.psc().bits(0); });
+                    self.arr().write(|w| unsafe { w.bits(0xFFFF); });
+                    self.egr().write(|w| { w.ug().set_bit(); });
+                    let tim = self;
+                    let (mode, input) = (SlaveMode::ExternalClock, TriggerInput::Internal(itr));
+                    slave!(@write tim, mode, input);
+                    self.cr1().modify(|_, w| { w.cen().set_bit(); });
+                }
+
+                #[inline]
+                fn count(&self) -> u16 {
+                    self.cnt().read().bits() as u16
+                }
+
+                fn stop(&self) {
+                    self.cr1().modify(|_, w| { w.cen().clear_bit(); });
+                    self.smcr().modify(|_, w| unsafe { w.sms().bits(0).sms_3().clear_bit(); });
+                }
+            }
+        )+
+    };
+}
+
+// Internal trigger connections, ITRx of the slave is connected to the TRGO of the master
+macro_rules! trigger_from {
+    ($($(#[$attr:meta])* $SLAVE:ident <= $MASTER:ident: $itr:ident,)+) => {
+        $(
+            $(#[$attr])*
+            impl TriggerFrom<$MASTER> for $SLAVE {
+                const ITR: InternalTrigger = InternalTrigger::$itr;
+            }
+        )+
+    };
+}
+
+slave! {
+    TIM1: mms,
+    TIM2: mms2,
+    TIM3: mms2,
+    TIM4: mms2,
+    TIM8: mms,
+}
+
+master!(TIM15: mms);
+
+chainable! {
+    TIM1: mms,
+    TIM3: mms2,
+    TIM4: mms2,
+    TIM8: mms,
+}
+
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+slave! {
+    TIM5: mms2,
+}
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+slave! {
+    TIM20: mms,
+}
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+chainable! {
+    TIM20: mms,
+}
+
+trigger_from! {
+    TIM1 <= TIM2: Itr1,
+    TIM1 <= TIM3: Itr2,
+    TIM1 <= TIM4: Itr3,
+    TIM1 <= TIM8: Itr5,
+    TIM1 <= TIM15: Itr6,
+
+    TIM2 <= TIM1: Itr0,
+    TIM2 <= TIM3: Itr2,
+    TIM2 <= TIM4: Itr3,
+    TIM2 <= TIM8: Itr5,
+    TIM2 <= TIM15: Itr6,
+
+    TIM3 <= TIM1: Itr0,
+    TIM3 <= TIM2: Itr1,
+    TIM3 <= TIM4: Itr3,
+    TIM3 <= TIM8: Itr5,
+    TIM3 <= TIM15: Itr6,
+
+    TIM4 <= TIM1: Itr0,
+    TIM4 <= TIM2: Itr1,
+    TIM4 <= TIM3: Itr2,
+    TIM4 <= TIM8: Itr5,
+    TIM4 <= TIM15: Itr6,
+
+    TIM8 <= TIM1: Itr0,
+    TIM8 <= TIM2: Itr1,
+    TIM8 <= TIM3: Itr2,
+    TIM8 <= TIM4: Itr3,
+    TIM8 <= TIM15: Itr6,
+}
+
+#[cfg(any(
+    feature = "stm32g471",
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+trigger_from! {
+    TIM1 <= TIM5: Itr4,
+    TIM2 <= TIM5: Itr4,
+    TIM3 <= TIM5: Itr4,
+    TIM4 <= TIM5: Itr4,
+    TIM8 <= TIM5: Itr4,
+
+    TIM5 <= TIM1: Itr0,
+    TIM5 <= TIM2: Itr1,
+    TIM5 <= TIM3: Itr2,
+    TIM5 <= TIM4: Itr3,
+    TIM5 <= TIM8: Itr5,
+    TIM5 <= TIM15: Itr6,
+}
+
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484",
+    feature = "stm32g491",
+    feature = "stm32g4a1"
+))]
+trigger_from! {
+    TIM1 <= TIM20: Itr9,
+    TIM2 <= TIM20: Itr9,
+    TIM3 <= TIM20: Itr9,
+    TIM4 <= TIM20: Itr9,
+    TIM8 <= TIM20: Itr9,
+
+    TIM20 <= TIM1: Itr0,
+    TIM20 <= TIM2: Itr1,
+    TIM20 <= TIM3: Itr2,
+    TIM20 <= TIM4: Itr3,
+    TIM20 <= TIM8: Itr5,
+    TIM20 <= TIM15: Itr6,
+}
+
+// The STM32G491 and STM32G4A1 have TIM20 but no TIM5
+#[cfg(any(
+    feature = "stm32g473",
+    feature = "stm32g474",
+    feature = "stm32g483",
+    feature = "stm32g484"
+))]
+trigger_from! {
+    TIM5 <= TIM20: Itr9,
+    TIM20 <= TIM5: Itr4,
+}
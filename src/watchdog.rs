@@ -0,0 +1,153 @@
+//! Window Watchdog
+//!
+//! The window watchdog (WWDG) is clocked by the APB1 clock, which gives it a much finer
+//! resolution than the independent watchdog, at the cost of a shorter maximum timeout. It
+//! resets the system if it is not fed before the timeout, and also if it is fed too early,
+//! before the window opens.
+//!
+//! The early wakeup interrupt fires one counter tick before the reset, to save some state or
+//! to feed the watchdog from the interrupt handler.
+//!
+//! ```ignore
+//! let mut watchdog = dp.WWDG.constrain(&mut rcc);
+//! // Feed between 2 ms and 10 ms after the previous feed
+//! watchdog.start_windowed(2.millis(), 10.millis());
+//!
+//! loop {
+//!     // ...
+//!     watchdog.feed();
+//! }
+//! ```
+use crate::hal_02::watchdog::{Watchdog, WatchdogEnable};
+use crate::rcc::Rcc;
+use crate::stm32::WWDG;
+use crate::time::{Hertz, MicroSecond};
+
+/// Window watchdog
+pub struct WindowWatchdog {
+    wwdg: WWDG,
+    clk: Hertz,
+    reload: u8,
+}
+
+/// Allows the constrain() method to be added to the peripheral
+pub trait WWDGExt {
+    /// Enables the clock of the watchdog, which only starts with [`WindowWatchdog::start`]
+    fn constrain(self, rcc: &mut Rcc) -> WindowWatchdog;
+}
+
+impl WWDGExt for WWDG {
+    fn constrain(self, rcc: &mut Rcc) -> WindowWatchdog {
+        rcc.rb.apb1enr1().modify(|_, w| {
+            w.wwdgen().set_bit();
+        });
+
+        WindowWatchdog {
+            wwdg: self,
+            clk: rcc.clocks.apb1_clk,
+            reload: 0x7F,
+        }
+    }
+}
+
+impl WindowWatchdog {
+    /// The counter resets the system when it goes below this value
+    const COUNTER_MIN: u8 = 0x40;
+    /// Number of counter ticks before the reset
+    const MAX_TICKS: u64 = 64;
+
+    /// Starts the watchdog, it has to be fed before `period` has elapsed
+    pub fn start<T: Into<MicroSecond>>(&mut self, period: T) {
+        self.start_windowed(MicroSecond::from_ticks(0), period.into());
+    }
+
+    /// Starts the watchdog, it has to be fed after `min_time` and before `max_time` have
+    /// elapsed since the previous feed.
+    ///
+    /// The times are rounded down to the resolution of the counter, which has to leave
+    /// `min_time` shorter than `max_time`.
+    pub fn start_windowed<T: Into<MicroSecond>>(&mut self, min_time: T, max_time: T) {
+        let min_time = min_time.into().ticks() as u64;
+        let max_time = max_time.into().ticks() as u64;
+
+        // The counter is clocked by PCLK1 / 4096 / 2^WDGTB
+        let pclk = self.clk.raw() as u64;
+        let (wdgtb, ticks) = (0..8)
+            .map(|wdgtb| (wdgtb, max_time * pclk / ((4096 << wdgtb) * 1_000_000)))
+            .find(|&(_, ticks)| ticks <= Self::MAX_TICKS)
+            .expect("WWDG max time is greater than is possible");
+        assert!(ticks > 0, "WWDG max time is shorter than a counter tick");
+        let min_ticks = min_time * pclk / ((4096 << wdgtb) * 1_000_000);
+        assert!(
+            min_ticks < ticks,
+            "WWDG min time is not shorter than the max time at the counter resolution"
+        );
+
+        let counter = Self::COUNTER_MIN + ticks as u8 - 1;
+        let window = counter - min_ticks as u8;
+        self.reload = counter;
+
+        self.wwdg.cfr().modify(|_, w| unsafe {
+            w.wdgtb().bits(wdgtb).w().bits(window);
+        });
+        self.wwdg.cr().write(|w| unsafe {
+            w.t().bits(counter).wdga().set_bit();
+        });
+    }
+
+    /// Reloads the counter, outside of the window this resets the system
+    pub fn feed(&mut self) {
+        let reload = self.reload;
+        self.wwdg.cr().write(|w| unsafe {
+            w.t().bits(reload).wdga().set_bit();
+        });
+    }
+
+    /// Enables the early wakeup interrupt, which fires when the counter reaches `0x40`, one tick
+    /// before the reset. It can only be disabled by a system reset.
+    pub fn listen(&mut self) {
+        self.wwdg.cfr().modify(|_, w| {
+            w.ewi().set_bit();
+        });
+    }
+
+    /// Returns `true` if the early wakeup interrupt is pending
+    pub fn is_early_wakeup(&self) -> bool {
+        self.wwdg.sr().read().ewif().bit_is_set()
+    }
+
+    /// Clears the early wakeup interrupt flag
+    pub fn clear_early_wakeup(&mut self) {
+        self.wwdg.sr().write(|w| {
+            w.ewif().clear_bit();
+        });
+    }
+
+    /// Returns the time left before the reset
+    pub fn time_left(&self) -> MicroSecond {
+        let counter = self.wwdg.cr().read().t().bits();
+        let wdgtb = self.wwdg.cfr().read().wdgtb().bits();
+        let ticks = (counter.saturating_sub(Self::COUNTER_MIN) as u64) + 1;
+        let us = ticks * (4096 << wdgtb) * 1_000_000 / self.clk.raw() as u64;
+        MicroSecond::from_ticks(us as u32)
+    }
+
+    /// Releases the peripheral, the watchdog keeps running once started
+    pub fn release(self) -> WWDG {
+        self.wwdg
+    }
+}
+
+impl Watchdog for WindowWatchdog {
+    fn feed(&mut self) {
+        WindowWatchdog::feed(self);
+    }
+}
+
+impl WatchdogEnable for WindowWatchdog {
+    type Time = MicroSecond;
+
+    fn start<T: Into<Self::Time>>(&mut self, period: T) {
+        WindowWatchdog::start(self, period);
+    }
+}